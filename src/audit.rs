@@ -0,0 +1,296 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read as _, Seek as _, SeekFrom, Write as _},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use rmcp::{ErrorData, model::JsonObject};
+
+use crate::{Response, output_store};
+
+/// Number of invocations kept in memory, starting with the last ones of the audit log file
+const MAX_RECENT_INVOCATIONS: usize = 200;
+
+/// Size of the blocks the end of the audit log file is read in at startup
+const TAIL_CHUNK_BYTES: u64 = 64 * 1024;
+
+static AUDIT_LOG: Mutex<AuditLog> = Mutex::new(AuditLog {
+    file: None,
+    recent: VecDeque::new(),
+});
+
+struct AuditLog {
+    file: Option<File>,
+    recent: VecDeque<Invocation>,
+}
+
+/// A tool call recorded in the audit log
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub(crate) struct Invocation {
+    /// Start of the call, RFC 3339 in UTC
+    pub(crate) timestamp: String,
+    pub(crate) tool: String,
+    /// Arguments as sent by the client, with the configured defaults applied
+    pub(crate) arguments: JsonObject,
+    /// Command line of the last command run by the tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) command_line: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) working_directory: Option<PathBuf>,
+    pub(crate) duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) exit_code: Option<i32>,
+    /// The command failed or the tool returned an error
+    pub(crate) is_error: bool,
+    /// Error returned by the tool instead of a result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+    pub(crate) stdout_bytes: usize,
+    pub(crate) stderr_bytes: usize,
+    /// ID of the full output for `get-command-output`, while the server still keeps it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) output_id: Option<String>,
+}
+
+/// Start time of a tool call
+pub(crate) struct CallStart {
+    time: SystemTime,
+    instant: Instant,
+}
+
+impl CallStart {
+    pub(crate) fn now() -> Self {
+        Self {
+            time: SystemTime::now(),
+            instant: Instant::now(),
+        }
+    }
+}
+
+impl Invocation {
+    pub(crate) fn new(
+        tool: &str,
+        arguments: JsonObject,
+        working_directory: Option<PathBuf>,
+        start: &CallStart,
+        result: &Result<Response, ErrorData>,
+    ) -> Self {
+        let mut invocation = Self {
+            timestamp: format_timestamp(start.time),
+            tool: tool.to_owned(),
+            arguments,
+            command_line: None,
+            working_directory,
+            duration_ms: u64::try_from(start.instant.elapsed().as_millis()).unwrap_or(u64::MAX),
+            exit_code: None,
+            is_error: false,
+            error: None,
+            stdout_bytes: 0,
+            stderr_bytes: 0,
+            output_id: None,
+        };
+        match result {
+            Ok(response) => {
+                if let Some(output) = response.output() {
+                    invocation.command_line = Some(output.cmd_line.0.clone());
+                    invocation.exit_code = output.exit_status.code();
+                    invocation.is_error = !output.success();
+                }
+                if let Some(stored) = response.output_id().and_then(output_store::get) {
+                    invocation.stdout_bytes = stored.stdout.len();
+                    invocation.stderr_bytes = stored.stderr.len();
+                    invocation.output_id = Some(stored.id.clone());
+                }
+            }
+            Err(e) => {
+                invocation.is_error = true;
+                invocation.error = Some(e.message.to_string());
+            }
+        }
+        invocation
+    }
+}
+
+/// Appends the invocations to `path`, or only keeps them in memory if `None`.
+/// The last invocations of earlier sessions are loaded from the file.
+pub(crate) fn init(path: Option<PathBuf>) {
+    let recent = path
+        .as_deref()
+        .filter(|path| path.is_file())
+        .and_then(|path| {
+            read_tail(path, MAX_RECENT_INVOCATIONS)
+                .inspect_err(|e| tracing::warn!("Failed to read audit log {}: {e}", path.display()))
+                .ok()
+        })
+        .unwrap_or_default();
+    let file = path.as_ref().and_then(|path| {
+        if let Some(dir) = path.parent() {
+            _ = std::fs::create_dir_all(dir);
+        }
+        File::options()
+            .create(true)
+            .append(true)
+            .open(path)
+            .inspect_err(|e| tracing::warn!("Failed to open audit log {}: {e}", path.display()))
+            .ok()
+    });
+    let mut log = AUDIT_LOG.lock().unwrap_or_else(|e| e.into_inner());
+    log.file = file;
+    log.recent = recent;
+}
+
+/// Reads the last `count` invocations of an audit log, scanning it backwards from the end
+fn read_tail(path: &Path, count: usize) -> std::io::Result<VecDeque<Invocation>> {
+    let mut file = File::open(path)?;
+    let mut start = file.metadata()?.len();
+    let mut tail = Vec::new();
+    // one more line than needed, the first one may be cut
+    while start > 0 && tail.iter().filter(|b| **b == b'\n').count() <= count {
+        let chunk_start = start.saturating_sub(TAIL_CHUNK_BYTES);
+        let mut chunk = vec![0; usize::try_from(start - chunk_start).unwrap_or_default()];
+        file.seek(SeekFrom::Start(chunk_start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.append(&mut tail);
+        tail = chunk;
+        start = chunk_start;
+    }
+
+    let text = String::from_utf8_lossy(&tail);
+    let mut lines = text.lines();
+    if start > 0 {
+        lines.next();
+    }
+    let invocations: Vec<_> = lines
+        .filter_map(|line| serde_json::from_str::<Invocation>(line).ok())
+        .collect();
+    let skip = invocations.len().saturating_sub(count);
+    Ok(invocations.into_iter().skip(skip).collect())
+}
+
+pub(crate) fn record(invocation: Invocation) {
+    let mut log = AUDIT_LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(file) = &mut log.file {
+        match serde_json::to_string(&invocation) {
+            Ok(mut line) => {
+                line.push('\n');
+                if let Err(e) = file.write_all(line.as_bytes()) {
+                    tracing::warn!("Failed to write to the audit log: {e}");
+                }
+            }
+            Err(e) => tracing::error!("Failed to serialize invocation: {e}"),
+        }
+    }
+    if log.recent.len() == MAX_RECENT_INVOCATIONS {
+        log.recent.pop_front();
+    }
+    log.recent.push_back(invocation);
+}
+
+/// The last `limit` invocations accepted by `filter`, most recent first, including those of earlier
+/// sessions loaded from the audit log file. Output IDs the server no longer holds are left out.
+pub(crate) fn recent(limit: usize, filter: impl Fn(&Invocation) -> bool) -> Vec<Invocation> {
+    let mut invocations: Vec<_> = AUDIT_LOG
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .recent
+        .iter()
+        .rev()
+        .filter(|invocation| filter(invocation))
+        .take(limit)
+        .cloned()
+        .collect();
+    for invocation in &mut invocations {
+        invocation.output_id = invocation
+            .output_id
+            .take()
+            .filter(|id| output_store::get(id).is_some());
+    }
+    invocations
+}
+
+/// Formats a time as RFC 3339 in UTC with millisecond precision
+fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_millis(1_709_210_096_789)),
+            "2024-02-29T12:34:56.789Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(4_102_444_799)),
+            "2099-12-31T23:59:59.000Z"
+        );
+    }
+
+    #[test]
+    fn test_read_tail() {
+        let path = std::env::temp_dir().join("rust-mcp-server-audit-test.jsonl");
+        let mut text = String::new();
+        for i in 0..2000 {
+            let invocation = Invocation {
+                timestamp: format_timestamp(UNIX_EPOCH),
+                tool: format!("tool-{i}"),
+                arguments: JsonObject::new(),
+                command_line: Some(format!("cargo check --package package-{i}")),
+                working_directory: None,
+                duration_ms: i,
+                exit_code: Some(0),
+                is_error: false,
+                error: None,
+                stdout_bytes: 0,
+                stderr_bytes: 0,
+                output_id: None,
+            };
+            text.push_str(&serde_json::to_string(&invocation).unwrap());
+            text.push('\n');
+        }
+        assert!(text.len() as u64 > 2 * TAIL_CHUNK_BYTES);
+        std::fs::write(&path, text).unwrap();
+
+        let tail = read_tail(&path, MAX_RECENT_INVOCATIONS).unwrap();
+        assert_eq!(tail.len(), MAX_RECENT_INVOCATIONS);
+        assert_eq!(tail.front().unwrap().tool, "tool-1800");
+        assert_eq!(tail.back().unwrap().tool, "tool-1999");
+
+        let all = read_tail(&path, 5000).unwrap();
+        assert_eq!(all.len(), 2000);
+        assert_eq!(all.front().unwrap().tool, "tool-0");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
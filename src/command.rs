@@ -1,13 +1,25 @@
+use std::{process::Stdio, time::Duration};
+
 use rmcp::{
     ErrorData,
     model::{
         AnnotateAble, Annotated, Annotations, CallToolResult, RawContent, RawTextContent, Role,
     },
 };
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    sync::mpsc::UnboundedSender,
+};
 
 use crate::meta::Meta;
+use crate::output_store::{self, Stream};
+use crate::progress::{ProgressParser, strip_progress_bars};
+use crate::tool::ToolContext;
 use crate::workspace::apply_workspace_root;
 
+/// Tool parameter overriding the server default timeout, accepted by every tool
+pub(crate) const TIMEOUT_SECS_PARAM: &str = "timeout_secs";
+
 #[derive(Debug, Clone)]
 pub(crate) struct CommandLine(pub String);
 
@@ -52,22 +64,45 @@ impl From<Stderr> for Annotated<RawContent> {
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct ExitStatus(pub std::process::ExitStatus);
+pub(crate) enum ExitStatus {
+    /// The process ran to completion
+    Exited(std::process::ExitStatus),
+    /// The process was killed after exceeding the given time limit
+    TimedOut(Duration),
+}
 
 impl ExitStatus {
+    pub(crate) fn success(&self) -> bool {
+        matches!(self, ExitStatus::Exited(status) if status.success())
+    }
+
+    pub(crate) fn code(&self) -> Option<i32> {
+        match self {
+            ExitStatus::Exited(status) => status.code(),
+            ExitStatus::TimedOut(_) => None,
+        }
+    }
+
     fn as_content(&self, tool_name: &str) -> Annotated<RawContent> {
-        let status_str = if self.0.success() {
-            format!("✅ {tool_name}: Success")
-        } else if let Some(code) = self.0.code() {
-            format!("❌ {tool_name}: Failure, exit code: {code}")
-        } else {
-            format!("❌ {tool_name}: Failure")
+        let status_str = match self {
+            ExitStatus::Exited(status) if status.success() => format!("✅ {tool_name}: Success"),
+            ExitStatus::Exited(status) => match status.code() {
+                Some(code) => format!("❌ {tool_name}: Failure, exit code: {code}"),
+                None => format!("❌ {tool_name}: Failure"),
+            },
+            ExitStatus::TimedOut(timeout) => format!(
+                "⏱️ {tool_name}: Timed out after {}s, the process was killed. The output above is partial",
+                timeout.as_secs()
+            ),
         };
 
         let mut meta = Meta::new().with_description("command exit status");
-        if let Some(code) = self.0.code() {
+        if let Some(code) = self.code() {
             meta = meta.with_i32("exit_code", code);
         }
+        if let ExitStatus::TimedOut(_) = self {
+            meta = meta.with_bool("timed_out", true);
+        }
 
         let content = RawContent::Text(RawTextContent {
             text: status_str,
@@ -115,27 +150,31 @@ pub(crate) struct Output {
 }
 
 impl Output {
-    fn new(tool_name: String, cmd_line: String, output: std::process::Output) -> Self {
+    fn new(
+        tool_name: String,
+        cmd_line: String,
+        exit_status: ExitStatus,
+        stdout: &[u8],
+        stderr: &[u8],
+    ) -> Self {
         let cmd_line = CommandLine(cmd_line);
 
-        let stdout = if !output.stdout.is_empty() {
+        let stdout = if !stdout.is_empty() {
             Some(Stdout(
-                String::from_utf8_lossy(output.stdout.trim_ascii()).to_string(),
+                String::from_utf8_lossy(stdout.trim_ascii()).to_string(),
             ))
         } else {
             None
         };
 
-        let stderr = if !output.stderr.is_empty() {
+        let stderr = if !stderr.is_empty() {
             Some(Stderr(
-                String::from_utf8_lossy(output.stderr.trim_ascii()).to_string(),
+                String::from_utf8_lossy(stderr.trim_ascii()).to_string(),
             ))
         } else {
             None
         };
 
-        let exit_status = ExitStatus(output.status);
-
         Output {
             tool_name,
             cmd_line,
@@ -146,7 +185,31 @@ impl Output {
     }
 
     pub(crate) fn success(&self) -> bool {
-        self.exit_status.0.success()
+        self.exit_status.success()
+    }
+
+    /// Keeps the full output for `get-command-output` and elides the middle of the streams
+    /// longer than `max_bytes`, returns the ID of the stored output
+    pub(crate) fn keep(&mut self, max_bytes: Option<usize>) -> String {
+        let id = output_store::store(
+            &self.cmd_line.0,
+            self.stdout.as_ref().map_or("", |stdout| stdout.0.as_str()),
+            self.stderr.as_ref().map_or("", |stderr| stderr.0.as_str()),
+        );
+        let Some(max_bytes) = max_bytes else {
+            return id;
+        };
+        if let Some(stdout) = &mut self.stdout
+            && let Some(elided) = output_store::elide(&stdout.0, max_bytes, &id, Stream::Stdout)
+        {
+            stdout.0 = elided;
+        }
+        if let Some(stderr) = &mut self.stderr
+            && let Some(elided) = output_store::elide(&stderr.0, max_bytes, &id, Stream::Stderr)
+        {
+            stderr.0 = elided;
+        }
+        id
     }
 }
 
@@ -167,16 +230,17 @@ impl From<Output> for CallToolResult {
 
         let mut result = CallToolResult::default();
         result.content = content;
-        result.is_error = Some(!val.exit_status.0.success());
+        result.is_error = Some(!val.exit_status.success());
         result
     }
 }
 
-pub(crate) fn execute_command(
+pub(crate) async fn execute_command(
     mut cmd: std::process::Command,
     tool_name: &str,
+    ctx: &ToolContext,
 ) -> Result<Output, ErrorData> {
-    apply_workspace_root(&mut cmd);
+    apply_workspace_root(&mut cmd, ctx);
 
     let cmd_line = format!(
         "{} {}",
@@ -186,40 +250,263 @@ pub(crate) fn execute_command(
             .collect::<Vec<_>>()
             .join(" ")
     );
+    let program = cmd.get_program().to_string_lossy().into_owned();
+
+    if let Some(confirmation) = ctx.confirmation() {
+        confirmation
+            .confirm(tool_name, &cmd_line, ctx.cancellation_token())
+            .await?;
+    }
+
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if ctx.progress().is_some() {
+        // cargo only draws its `N/M` progress bar on a terminal unless asked otherwise
+        cmd.env("CARGO_TERM_PROGRESS_WHEN", "always")
+            .env("CARGO_TERM_PROGRESS_WIDTH", "100");
+    }
+    if crate::globals::is_read_only() {
+        // keep tests using insta from writing `.snap.new` files next to the sources
+        cmd.env("INSTA_UPDATE", "no");
+    }
+    let mut cmd = tokio::process::Command::from(cmd);
+    cmd.kill_on_drop(true);
+    // Put the child into its own process group so that cancellation also reaches
+    // the processes it spawns (rustc, test binaries, build scripts, ...)
+    #[cfg(unix)]
+    cmd.process_group(0);
 
     tracing::info!("Executing command for {tool_name}: {cmd_line}");
-    match cmd.output() {
-        Ok(output) => {
-            let output = Output::new(tool_name.to_owned(), cmd_line, output);
-            if output.success() {
-                tracing::info!(
-                    "Command executed successfully for {tool_name}\nstdout=\n{}\n\nstderr=\n{}",
-                    output.stdout.as_ref().map(|s| s.0.as_str()).unwrap_or(""),
-                    output.stderr.as_ref().map(|s| s.0.as_str()).unwrap_or(""),
-                );
-            } else {
-                tracing::warn!(
-                    "Command execution failed for {tool_name} (status: {:?}): stdout='\n{}\n', stderr='\n{}\n'",
-                    output.exit_status.0.code(),
-                    output.stdout.as_ref().map(|s| s.0.as_str()).unwrap_or(""),
-                    output.stderr.as_ref().map(|s| s.0.as_str()).unwrap_or(""),
-                );
-            }
-            Ok(output)
-        }
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
             tracing::error!("Command not found: {e}");
-            let program = cmd.get_program().to_string_lossy();
-            Err(ErrorData::internal_error(
+            return Err(ErrorData::internal_error(
                 format!(
                     "The command `{program}` was not found, please ensure it is installed and accessible. You can try running the following command yourself to verify: `{cmd_line}`",
                 ),
                 None,
-            ))
+            ));
+        }
+        Err(e) => {
+            tracing::error!("Failed to execute command: {e}");
+            return Err(ErrorData::internal_error(e.to_string(), None));
+        }
+    };
+
+    let pid = child.id();
+    let mut stdout_pipe = child.stdout.take().expect("stdout is piped");
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let (line_tx, mut line_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let line_tx = ctx.progress().is_some().then_some(line_tx);
+    let report_progress = async {
+        if let Some(progress) = ctx.progress() {
+            let mut parser = ProgressParser::default();
+            while let Some(line) = line_rx.recv().await {
+                if let Some(update) = parser.parse_line(&line) {
+                    progress.notify(update).await;
+                }
+            }
+        }
+        Ok(())
+    };
+    let mut run = Box::pin(async {
+        let (status, _, _, ()) = tokio::try_join!(
+            child.wait(),
+            read_output(&mut stdout_pipe, &mut stdout, line_tx.clone()),
+            read_output(&mut stderr_pipe, &mut stderr, line_tx),
+            report_progress,
+        )?;
+        Ok::<_, std::io::Error>(status)
+    });
+
+    let timeout = ctx.timeout();
+    let deadline = async {
+        match timeout {
+            Some(timeout) => tokio::time::sleep(timeout).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let status = tokio::select! {
+        status = &mut run => status.map(ExitStatus::Exited),
+        () = ctx.cancellation_token().cancelled() => {
+            tracing::warn!("Request cancelled by the client, killing command for {tool_name}: {cmd_line}");
+            if let Some(pid) = pid {
+                kill_process_tree(pid);
+            }
+            return Err(ErrorData::internal_error(
+                format!("Command `{cmd_line}` was cancelled"),
+                None,
+            ));
+        }
+        () = deadline => {
+            let timeout = timeout.unwrap_or_default();
+            tracing::warn!("Command for {tool_name} timed out after {}s, killing it: {cmd_line}", timeout.as_secs());
+            Ok(ExitStatus::TimedOut(timeout))
         }
+    };
+
+    if let Ok(ExitStatus::TimedOut(_)) = status {
+        if let Some(pid) = pid {
+            kill_process_tree(pid);
+        }
+        // Collect whatever is still buffered in the pipes. Processes that escaped the
+        // process group may keep them open, so don't wait for too long.
+        if tokio::time::timeout(Duration::from_secs(5), &mut run)
+            .await
+            .is_err()
+        {
+            tracing::warn!("Pipes of the timed out command for {tool_name} were not closed");
+        }
+    }
+    drop(run);
+
+    let status = match status {
+        Ok(status) => status,
         Err(e) => {
             tracing::error!("Failed to execute command: {e}");
-            Err(ErrorData::internal_error(e.to_string(), None))
+            return Err(ErrorData::internal_error(e.to_string(), None));
         }
+    };
+
+    if ctx.progress().is_some() {
+        stderr = strip_progress_bars(&stderr);
+    }
+    let output = Output::new(tool_name.to_owned(), cmd_line, status, &stdout, &stderr);
+    if output.success() {
+        tracing::info!(
+            "Command executed successfully for {tool_name}\nstdout=\n{}\n\nstderr=\n{}",
+            output.stdout.as_ref().map(|s| s.0.as_str()).unwrap_or(""),
+            output.stderr.as_ref().map(|s| s.0.as_str()).unwrap_or(""),
+        );
+    } else {
+        tracing::warn!(
+            "Command execution failed for {tool_name} (status: {:?}): stdout='\n{}\n', stderr='\n{}\n'",
+            output.exit_status.code(),
+            output.stdout.as_ref().map(|s| s.0.as_str()).unwrap_or(""),
+            output.stderr.as_ref().map(|s| s.0.as_str()).unwrap_or(""),
+        );
+    }
+    Ok(output)
+}
+
+/// Reads the whole stream into `buf`, forwarding every `\n` or `\r` terminated line to `lines`
+async fn read_output(
+    pipe: &mut (impl AsyncRead + Unpin),
+    buf: &mut Vec<u8>,
+    lines: Option<UnboundedSender<String>>,
+) -> std::io::Result<()> {
+    let Some(lines) = lines else {
+        pipe.read_to_end(buf).await?;
+        return Ok(());
+    };
+
+    let mut line_start = buf.len();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = pipe.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..read]);
+        while let Some(pos) = buf[line_start..]
+            .iter()
+            .position(|b| *b == b'\n' || *b == b'\r')
+        {
+            let line = String::from_utf8_lossy(&buf[line_start..line_start + pos]);
+            let _ = lines.send(line.into_owned());
+            line_start += pos + 1;
+        }
+    }
+    if line_start < buf.len() {
+        let _ = lines.send(String::from_utf8_lossy(&buf[line_start..]).into_owned());
+    }
+    Ok(())
+}
+
+/// Kills the process together with everything in its process group
+fn kill_process_tree(pid: u32) {
+    #[cfg(unix)]
+    {
+        let Ok(pgid) = i32::try_from(pid) else {
+            return;
+        };
+        // SAFETY: `kill` has no memory safety requirements, a negative pid targets the process group
+        if unsafe { libc::kill(-pgid, libc::SIGKILL) } != 0 {
+            tracing::warn!(
+                "Failed to kill process group {pgid}: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    #[cfg(windows)]
+    {
+        let result = std::process::Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .output();
+        if let Err(e) = result {
+            tracing::warn!("Failed to kill process tree {pid}: {e}");
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_execute_command_captures_output() {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg("echo out; echo err >&2; exit 3");
+
+        let output = execute_command(cmd, "test", &ToolContext::default())
+            .await
+            .expect("command should run");
+
+        assert_eq!(output.stdout.unwrap().0, "out");
+        assert_eq!(output.stderr.unwrap().0, "err");
+        assert_eq!(output.exit_status.code(), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_timeout_keeps_partial_output() {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg("echo partial; sleep 30");
+
+        let ctx = ToolContext::default().with_timeout_secs(Some(1));
+        let start = Instant::now();
+        let output = execute_command(cmd, "test", &ctx)
+            .await
+            .expect("timed out command should still produce output");
+
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(matches!(output.exit_status, ExitStatus::TimedOut(_)));
+        assert!(!output.success());
+        assert_eq!(output.stdout.unwrap().0, "partial");
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_cancellation_kills_process() {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg("sleep 30 & wait");
+
+        let ctx = ToolContext::default();
+        let ct = ctx.cancellation_token().clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            ct.cancel();
+        });
+
+        let start = Instant::now();
+        let result = execute_command(cmd, "test", &ctx).await;
+
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(10));
     }
 }
@@ -0,0 +1,267 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use ohno::IntoAppError;
+use rmcp::model::JsonObject;
+use serde::Deserialize;
+
+use crate::confirmation::ConfirmationPolicy;
+
+const CONFIG_FILE_NAME: &str = "rust-mcp-server.toml";
+
+/// Settings read from `rust-mcp-server.toml`, command line arguments take precedence over them
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Default cargo registry, same as `--registry`
+    pub(crate) registry: Option<String>,
+    /// Default command time limit in seconds, same as `--default-timeout`
+    pub(crate) default_timeout: Option<u64>,
+    /// Maximum size in bytes of each output stream, same as `--max-output-bytes`
+    pub(crate) max_output_bytes: Option<usize>,
+    /// JSONL file every tool call is appended to, relative to the config file. Empty to disable it.
+    pub(crate) audit_log: Option<PathBuf>,
+    /// Directory with additional prompt files, relative to the config file
+    pub(crate) prompts_dir: Option<PathBuf>,
+    /// Same as `--read-only`
+    pub(crate) read_only: bool,
+    /// If set, only these tools are enabled
+    pub(crate) enabled_tools: Option<Vec<String>>,
+    /// Tools to disable, added to `--disable-tool`
+    pub(crate) disabled_tools: Vec<String>,
+    /// When the user is asked to confirm the commands of a tool through MCP elicitation
+    pub(crate) confirmation: ConfirmationPolicy,
+    /// Confirmation policy of a single tool, e.g. `cargo-clean = "never"`
+    pub(crate) tool_confirmation: HashMap<String, ConfirmationPolicy>,
+    /// Default parameters of every tool that accepts them, e.g. `locking_mode = "locked"`
+    pub(crate) defaults: JsonObject,
+    /// Default parameters of a single tool, e.g. `[tools.cargo-test] no_fail_fast = true`
+    pub(crate) tools: HashMap<String, JsonObject>,
+}
+
+impl Config {
+    /// Loads the config from `explicit_path`, or the first `rust-mcp-server.toml` found
+    /// in the workspace root and then in the user config directory
+    pub(crate) fn load(
+        explicit_path: Option<&Path>,
+        workspace: Option<&Path>,
+    ) -> Result<Self, ohno::AppError> {
+        let path = match explicit_path {
+            Some(path) => Some(path.to_path_buf()),
+            None => workspace
+                .map(|dir| dir.join(CONFIG_FILE_NAME))
+                .into_iter()
+                .chain(user_config_dir().map(|dir| dir.join(CONFIG_FILE_NAME)))
+                .find(|path| path.is_file()),
+        };
+        let Some(path) = path else {
+            tracing::info!("No {CONFIG_FILE_NAME} found, using command line arguments only");
+            return Ok(Self::default());
+        };
+
+        tracing::info!("Loading config file: {}", path.display());
+        let text = std::fs::read_to_string(&path)
+            .into_app_err_with(|| format!("Failed to read config file {}", path.display()))?;
+        let mut config = Self::parse(&text)
+            .into_app_err_with(|| format!("Invalid config file {}", path.display()))?;
+        if let (Some(prompts_dir), Some(config_dir)) = (&config.prompts_dir, path.parent()) {
+            config.prompts_dir = Some(config_dir.join(prompts_dir));
+        }
+        if let (Some(audit_log), Some(config_dir)) = (&config.audit_log, path.parent())
+            && !audit_log.as_os_str().is_empty()
+        {
+            config.audit_log = Some(config_dir.join(audit_log));
+        }
+        Ok(config)
+    }
+
+    fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    pub(crate) fn confirmation_policy(&self, tool_name: &str) -> ConfirmationPolicy {
+        self.tool_confirmation
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.confirmation)
+    }
+
+    /// Default parameters of a tool: the shared defaults it declares in its input schema, overridden by its own table
+    pub(crate) fn tool_defaults(
+        &self,
+        tool_name: &str,
+        input_schema: &serde_json::Map<String, serde_json::Value>,
+    ) -> JsonObject {
+        let properties = input_schema
+            .get("properties")
+            .and_then(|properties| properties.as_object());
+        let accepts = |name: &str| properties.is_some_and(|p| p.contains_key(name));
+
+        let mut defaults: JsonObject = self
+            .defaults
+            .iter()
+            .filter(|(name, _)| accepts(name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        for (name, value) in self.tools.get(tool_name).into_iter().flatten() {
+            if !accepts(name) {
+                tracing::warn!("Config sets unknown parameter `{name}` of tool {tool_name}");
+            }
+            defaults.insert(name.clone(), value.clone());
+        }
+        defaults
+    }
+}
+
+/// Adds the default parameters the request does not set explicitly
+pub(crate) fn apply_defaults(arguments: &mut Option<JsonObject>, defaults: &JsonObject) {
+    if defaults.is_empty() {
+        return;
+    }
+    let arguments = arguments.get_or_insert_default();
+    for (name, value) in defaults {
+        if !arguments.contains_key(name) {
+            arguments.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+/// Default location of the audit log, in the user state directory
+pub(crate) fn default_audit_log() -> Option<PathBuf> {
+    let state_dir = if cfg!(windows) {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+            })
+    };
+    state_dir.map(|dir| dir.join("rust-mcp-server").join("audit.jsonl"))
+}
+
+fn user_config_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+registry = "my-registry"
+default_timeout = 600
+disabled_tools = ["cargo-deny-install"]
+confirmation = "always"
+
+[tool_confirmation]
+cargo-clean = "never"
+
+[defaults]
+locking_mode = "unlocked"
+
+[tools]
+cargo-test.no_fail_fast = true
+cargo-clippy.warnings_as_errors = true
+cargo-clippy.locking_mode = "offline"
+"#;
+
+    fn schema(properties: &[&str]) -> serde_json::Map<String, serde_json::Value> {
+        let properties: serde_json::Map<_, _> = properties
+            .iter()
+            .map(|name| (name.to_string(), serde_json::json!({})))
+            .collect();
+        serde_json::json!({ "properties": properties })
+            .as_object()
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(CONFIG).unwrap();
+        assert_eq!(config.registry.as_deref(), Some("my-registry"));
+        assert_eq!(config.default_timeout, Some(600));
+        assert_eq!(config.disabled_tools, ["cargo-deny-install"]);
+        assert_eq!(config.enabled_tools, None);
+        assert_eq!(
+            config.tools["cargo-test"]["no_fail_fast"],
+            serde_json::Value::Bool(true)
+        );
+
+        assert_eq!(
+            config.confirmation_policy("cargo-clean"),
+            ConfirmationPolicy::Never
+        );
+        assert_eq!(
+            config.confirmation_policy("rustup-update"),
+            ConfirmationPolicy::Always
+        );
+        assert_eq!(
+            Config::default().confirmation_policy("cargo-clean"),
+            ConfirmationPolicy::Destructive
+        );
+
+        assert!(Config::parse("unknown_key = 1").is_err());
+        assert!(Config::parse("confirmation = \"sometimes\"").is_err());
+    }
+
+    #[test]
+    fn test_tool_defaults() {
+        let config = Config::parse(CONFIG).unwrap();
+
+        let defaults =
+            config.tool_defaults("cargo-test", &schema(&["no_fail_fast", "locking_mode"]));
+        assert_eq!(
+            serde_json::Value::Object(defaults),
+            serde_json::json!({"no_fail_fast": true, "locking_mode": "unlocked"})
+        );
+
+        let defaults = config.tool_defaults(
+            "cargo-clippy",
+            &schema(&["warnings_as_errors", "locking_mode"]),
+        );
+        assert_eq!(
+            serde_json::Value::Object(defaults),
+            serde_json::json!({"warnings_as_errors": true, "locking_mode": "offline"})
+        );
+
+        // Shared defaults only apply to tools that accept them
+        assert!(
+            config
+                .tool_defaults("rustc-explain", &schema(&["error_code"]))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let defaults = serde_json::json!({"no_fail_fast": true, "locking_mode": "locked"})
+            .as_object()
+            .unwrap()
+            .clone();
+
+        let mut arguments = serde_json::json!({"locking_mode": "offline"})
+            .as_object()
+            .cloned();
+        apply_defaults(&mut arguments, &defaults);
+        assert_eq!(
+            serde_json::Value::Object(arguments.unwrap()),
+            serde_json::json!({"no_fail_fast": true, "locking_mode": "offline"})
+        );
+
+        let mut arguments = None;
+        apply_defaults(&mut arguments, &defaults);
+        assert_eq!(arguments, Some(defaults));
+    }
+}
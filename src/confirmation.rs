@@ -0,0 +1,179 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use rmcp::{
+    ErrorData, Peer, RoleServer,
+    model::{CreateElicitationRequestParams, ElicitationAction, ElicitationSchema},
+    service::ElicitationMode,
+};
+use tokio_util::sync::CancellationToken;
+
+/// Longest list of changes shown in a confirmation request
+const MAX_CHANGES_LEN: usize = 20_000;
+
+/// When the user is asked to confirm the commands spawned by a tool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ConfirmationPolicy {
+    /// Every call
+    Always,
+    /// Calls that may overwrite or delete existing data
+    #[default]
+    Destructive,
+    /// Never
+    Never,
+}
+
+impl ConfirmationPolicy {
+    pub(crate) fn requires_confirmation(self, destructive: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Destructive => destructive,
+            Self::Never => false,
+        }
+    }
+}
+
+/// Asks the user, through MCP elicitation, to confirm the command a tool is about to run
+#[derive(Debug, Clone)]
+pub(crate) struct Confirmation {
+    policy: ConfirmationPolicy,
+    peer: Peer<RoleServer>,
+    required: bool,
+    /// Set once the user accepted, a tool that runs several commands only asks once
+    confirmed: Arc<AtomicBool>,
+}
+
+impl Confirmation {
+    pub(crate) fn new(policy: ConfirmationPolicy, peer: Peer<RoleServer>) -> Self {
+        Self {
+            policy,
+            peer,
+            required: policy.requires_confirmation(true),
+            confirmed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Narrows the confirmation down to the side effects of the actual call
+    pub(crate) fn set_destructive(&mut self, destructive: bool) {
+        self.required = self.policy.requires_confirmation(destructive);
+    }
+
+    /// Asks before a command runs
+    pub(crate) async fn confirm(
+        &self,
+        tool_name: &str,
+        cmd_line: &str,
+        ct: &CancellationToken,
+    ) -> Result<(), ErrorData> {
+        self.ask(
+            tool_name,
+            &format!("running `{cmd_line}`"),
+            format!("Allow `{tool_name}` to run the following command?\n\n{cmd_line}"),
+            ct,
+        )
+        .await
+    }
+
+    /// Asks before a tool changes files itself, showing the changes it is about to make.
+    /// `action` describes them, e.g. "applying 3 suggestion(s) to 2 file(s)".
+    pub(crate) async fn confirm_changes(
+        &self,
+        tool_name: &str,
+        action: &str,
+        changes: &str,
+        ct: &CancellationToken,
+    ) -> Result<(), ErrorData> {
+        let mut changes = changes.to_owned();
+        if changes.len() > MAX_CHANGES_LEN {
+            let end = changes.floor_char_boundary(MAX_CHANGES_LEN);
+            changes.truncate(end);
+            changes.push_str("\n... (truncated)");
+        }
+        self.ask(
+            tool_name,
+            action,
+            format!("Allow `{tool_name}` to proceed with {action}?\n\n{changes}"),
+            ct,
+        )
+        .await
+    }
+
+    /// `action` completes "requires user confirmation before ...", e.g. "running `cargo fix`"
+    async fn ask(
+        &self,
+        tool_name: &str,
+        action: &str,
+        message: String,
+        ct: &CancellationToken,
+    ) -> Result<(), ErrorData> {
+        if !self.required || self.confirmed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        if !self
+            .peer
+            .supported_elicitation_modes()
+            .contains(&ElicitationMode::Form)
+        {
+            return Err(ErrorData::invalid_request(
+                format!(
+                    "`{tool_name}` requires user confirmation before {action}, \
+                    but the MCP client does not support elicitation. Ask the user to do it themselves \
+                    or to change the confirmation policy of `{tool_name}` in rust-mcp-server.toml."
+                ),
+                None,
+            ));
+        }
+
+        let params = CreateElicitationRequestParams::FormElicitationParams {
+            meta: None,
+            message,
+            requested_schema: ElicitationSchema::new(BTreeMap::new()),
+        };
+        tracing::info!("Asking the user to confirm {tool_name} before {action}");
+        let result = tokio::select! {
+            result = self.peer.create_elicitation(params) => result,
+            () = ct.cancelled() => {
+                return Err(ErrorData::internal_error(
+                    format!("Confirmation of `{tool_name}` was cancelled"),
+                    None,
+                ));
+            }
+        }
+        .map_err(|e| {
+            ErrorData::internal_error(format!("Failed to ask for confirmation: {e}"), None)
+        })?;
+
+        match result.action {
+            ElicitationAction::Accept => {
+                self.confirmed.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            ElicitationAction::Decline | ElicitationAction::Cancel => {
+                Err(ErrorData::invalid_request(
+                    format!("The user did not allow `{tool_name}` to proceed with {action}"),
+                    None,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_confirmation() {
+        assert!(ConfirmationPolicy::Always.requires_confirmation(false));
+        assert!(ConfirmationPolicy::Destructive.requires_confirmation(true));
+        assert!(!ConfirmationPolicy::Destructive.requires_confirmation(false));
+        assert!(!ConfirmationPolicy::Never.requires_confirmation(true));
+    }
+}
@@ -0,0 +1,760 @@
+use std::{collections::HashSet, fmt::Write as _};
+
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, Annotations, RawContent, RawTextContent, Role},
+};
+
+use crate::{
+    Response,
+    command::{Output, Stdout},
+    meta::Meta,
+    serde_utils::{deserialize_string, deserialize_string_vec},
+};
+
+/// Cargo flag that makes it print every compiler message as a JSON line on stdout
+pub(crate) const MESSAGE_FORMAT_JSON: &str = "--message-format=json";
+
+/// Compiler diagnostics reported by `cargo check`, `cargo build` or `cargo clippy`
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct DiagnosticsReport {
+    /// Number of distinct errors, including the omitted ones
+    errors: usize,
+    /// Number of distinct warnings, including the omitted ones
+    warnings: usize,
+    /// Number of diagnostics left out by the request filters
+    omitted: usize,
+    /// Diagnostics in the order they were emitted by the compiler
+    diagnostics: Vec<Diagnostic>,
+    /// Level, code, message and span of the diagnostics pushed so far, the short ID may collide
+    #[serde(skip)]
+    #[schemars(skip)]
+    seen: HashSet<(String, Option<String>, String, Option<Span>)>,
+}
+
+/// Request parameters that narrow down the returned diagnostics
+#[derive(Debug, Default, serde::Deserialize, schemars::JsonSchema)]
+pub(crate) struct DiagnosticsFilter {
+    /// Maximum number of diagnostics to return. Errors are kept before warnings.
+    #[serde(default)]
+    max_diagnostics: Option<usize>,
+
+    /// Minimum level of the returned diagnostics.
+    ///
+    /// Valid options:
+    /// - "error": Only errors
+    /// - "warning": Errors and warnings
+    /// - "note" (default): Everything the compiler reports
+    #[serde(default, deserialize_with = "deserialize_string")]
+    min_level: Option<String>,
+
+    /// Glob patterns matched against the file a diagnostic points at, e.g. "src/parser/**" or "**/lib.rs"
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    only_files: Option<Vec<String>>,
+
+    /// Only return diagnostics with these error codes or lint names, e.g. "E0308" or "clippy::*"
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    lint_codes: Option<Vec<String>>,
+
+    /// Leave out diagnostics with these error codes or lint names, e.g. "dead_code" or "clippy::pedantic*"
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    exclude_lint_codes: Option<Vec<String>>,
+}
+
+impl DiagnosticsFilter {
+    /// Checks the parameters before running the command
+    pub(crate) fn validate(&self) -> Result<(), ErrorData> {
+        self.min_severity().map(|_| ())
+    }
+
+    fn min_severity(&self) -> Result<u8, ErrorData> {
+        Ok(match self.min_level.as_deref() {
+            None | Some("note") => 0,
+            Some("warning") => severity("warning"),
+            Some("error") => severity("error"),
+            Some(unknown) => {
+                return Err(ErrorData::invalid_params(
+                    format!(
+                        "Unknown diagnostic level: {unknown}. Valid options are: error, warning, note"
+                    ),
+                    None,
+                ));
+            }
+        })
+    }
+
+    fn matches(&self, diagnostic: &Diagnostic, min_severity: u8) -> bool {
+        if severity(&diagnostic.level) < min_severity {
+            return false;
+        }
+
+        if let Some(patterns) = &self.only_files {
+            let Some(span) = &diagnostic.span else {
+                return false;
+            };
+            let file = span.file.replace('\\', "/");
+            if !patterns.iter().any(|pattern| glob_match(pattern, &file)) {
+                return false;
+            }
+        }
+
+        let code = diagnostic.code.as_deref().unwrap_or_default();
+        if let Some(codes) = &self.lint_codes
+            && !codes.iter().any(|pattern| glob_match(pattern, code))
+        {
+            return false;
+        }
+        if let Some(codes) = &self.exclude_lint_codes
+            && codes.iter().any(|pattern| glob_match(pattern, code))
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+fn severity(level: &str) -> u8 {
+    match level {
+        "warning" => 1,
+        level if level.starts_with("error") => 2,
+        _ => 0,
+    }
+}
+
+/// Matches `text` against a glob pattern where `*` and `?` don't cross `/`, and `**` does
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let text = text.as_bytes();
+
+    // (pattern index, text index) pairs still to explore
+    let mut stack = vec![(0, 0)];
+    let mut visited = std::collections::HashSet::new();
+    while let Some((p, t)) = stack.pop() {
+        if !visited.insert((p, t)) {
+            continue;
+        }
+        match pattern.get(p) {
+            None if t == text.len() => return true,
+            None => {}
+            Some(b'*') if pattern.get(p + 1) == Some(&b'*') => {
+                // `**/` also matches no directory at all
+                if pattern.get(p + 2) == Some(&b'/') {
+                    stack.push((p + 3, t));
+                }
+                stack.push((p + 2, t));
+                if t < text.len() {
+                    stack.push((p, t + 1));
+                }
+            }
+            Some(b'*') => {
+                stack.push((p + 1, t));
+                if t < text.len() && text[t] != b'/' {
+                    stack.push((p, t + 1));
+                }
+            }
+            Some(b'?') if t < text.len() && text[t] != b'/' => stack.push((p + 1, t + 1)),
+            Some(c) if text.get(t) == Some(c) => stack.push((p + 1, t + 1)),
+            Some(_) => {}
+        }
+    }
+    false
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct Diagnostic {
+    /// Identifier that stays the same between runs as long as the diagnostic doesn't move
+    id: String,
+    /// Severity: "error", "warning", "note", "help" or "error: internal compiler error"
+    level: String,
+    /// Error code or lint name, e.g. "E0308" or "clippy::len_zero"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    /// Main diagnostic message
+    message: String,
+    /// Location the diagnostic points at
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<Span>,
+    /// Additional notes and help messages without a code suggestion
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<String>,
+    /// Code changes proposed by the compiler
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<Suggestion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct Span {
+    /// Path relative to the workspace root
+    file: String,
+    /// 1-based line of the span start
+    line: usize,
+    /// 1-based column of the span start
+    column: usize,
+    /// 1-based line of the span end
+    end_line: usize,
+    /// 1-based column of the span end, exclusive
+    end_column: usize,
+    /// Label the compiler attached to the span
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip)]
+    byte_start: usize,
+    #[serde(skip)]
+    byte_end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct Suggestion {
+    /// What the suggestion does
+    message: String,
+    /// Text range to replace
+    span: Span,
+    /// Text to put in place of the span
+    replacement: String,
+    /// How confident the compiler is: "MachineApplicable", "MaybeIncorrect", "HasPlaceholders" or "Unspecified"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    applicability: Option<String>,
+    /// Suggestions with the same group are parts of one change and must be applied together
+    #[serde(skip)]
+    group: usize,
+}
+
+/// Line printed by cargo with `--message-format=json`
+#[derive(serde::Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<RustcDiagnostic>,
+}
+
+#[derive(serde::Deserialize)]
+struct RustcDiagnostic {
+    message: String,
+    level: String,
+    code: Option<RustcCode>,
+    #[serde(default)]
+    spans: Vec<RustcSpan>,
+    #[serde(default)]
+    children: Vec<RustcDiagnostic>,
+}
+
+#[derive(serde::Deserialize)]
+struct RustcCode {
+    code: String,
+}
+
+#[derive(serde::Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    byte_start: usize,
+    byte_end: usize,
+    line_start: usize,
+    line_end: usize,
+    column_start: usize,
+    column_end: usize,
+    is_primary: bool,
+    label: Option<String>,
+    suggested_replacement: Option<String>,
+    suggestion_applicability: Option<String>,
+}
+
+impl From<&RustcSpan> for Span {
+    fn from(span: &RustcSpan) -> Self {
+        Span {
+            file: span.file_name.clone(),
+            line: span.line_start,
+            column: span.column_start,
+            end_line: span.line_end,
+            end_column: span.column_end,
+            label: span.label.clone(),
+            byte_start: span.byte_start,
+            byte_end: span.byte_end,
+        }
+    }
+}
+
+impl From<RustcDiagnostic> for Diagnostic {
+    fn from(diagnostic: RustcDiagnostic) -> Self {
+        let span = diagnostic
+            .spans
+            .iter()
+            .find(|span| span.is_primary)
+            .map(Span::from);
+
+        let mut notes = Vec::new();
+        let mut suggestions = Vec::new();
+        for (group, child) in diagnostic.children.iter().enumerate() {
+            let mut has_suggestion = false;
+            for span in &child.spans {
+                if let Some(replacement) = &span.suggested_replacement {
+                    has_suggestion = true;
+                    suggestions.push(Suggestion {
+                        message: child.message.clone(),
+                        span: Span::from(span),
+                        replacement: replacement.clone(),
+                        applicability: span.suggestion_applicability.clone(),
+                        group,
+                    });
+                }
+            }
+            if !has_suggestion {
+                notes.push(format!("{}: {}", child.level, child.message));
+            }
+        }
+
+        let code = diagnostic.code.map(|code| code.code);
+        let location = span
+            .as_ref()
+            .map(|span| format!("{}:{}:{}", span.file, span.line, span.column))
+            .unwrap_or_default();
+        let id = stable_hash(&[
+            &diagnostic.level,
+            code.as_deref().unwrap_or_default(),
+            &diagnostic.message,
+            &location,
+        ]);
+
+        Diagnostic {
+            id,
+            level: diagnostic.level,
+            code,
+            message: diagnostic.message,
+            span,
+            notes,
+            suggestions,
+        }
+    }
+}
+
+/// FNV-1a of the parts, unlike `DefaultHasher` it doesn't change between Rust releases
+pub(crate) fn stable_hash(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    format!("{:08x}", hash as u32)
+}
+
+impl Diagnostic {
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(crate) fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    pub(crate) fn file(&self) -> Option<&str> {
+        self.span.as_ref().map(|span| span.file.as_str())
+    }
+
+    pub(crate) fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+
+    fn render(&self, out: &mut String) {
+        let _ = match &self.code {
+            Some(code) => writeln!(
+                out,
+                "{}[{code}]: {} (id: {})",
+                self.level, self.message, self.id
+            ),
+            None => writeln!(out, "{}: {} (id: {})", self.level, self.message, self.id),
+        };
+        if let Some(span) = &self.span {
+            let _ = write!(out, "  --> {}:{}:{}", span.file, span.line, span.column);
+            if let Some(label) = span.label.as_deref().filter(|label| !label.is_empty()) {
+                let _ = write!(out, " {label}");
+            }
+            out.push('\n');
+        }
+        for note in &self.notes {
+            let _ = writeln!(out, "  = {note}");
+        }
+        for suggestion in &self.suggestions {
+            let _ = writeln!(
+                out,
+                "  = help: {} at {}:{}:{}: `{}`",
+                suggestion.message,
+                suggestion.span.file,
+                suggestion.span.line,
+                suggestion.span.column,
+                suggestion.replacement
+            );
+        }
+    }
+}
+
+impl Suggestion {
+    pub(crate) fn file(&self) -> &str {
+        &self.span.file
+    }
+
+    /// Byte range of the replaced text
+    pub(crate) fn range(&self) -> std::ops::Range<usize> {
+        self.span.byte_start..self.span.byte_end
+    }
+
+    pub(crate) fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    pub(crate) fn applicability(&self) -> Option<&str> {
+        self.applicability.as_deref()
+    }
+
+    pub(crate) fn group(&self) -> usize {
+        self.group
+    }
+}
+
+impl DiagnosticsReport {
+    /// Parses the JSON messages out of the command's stdout.
+    ///
+    /// Lines that are not compiler messages (e.g. printed by build scripts) are left in stdout.
+    pub(crate) fn take_from(output: &mut Output) -> Self {
+        let mut report = DiagnosticsReport::default();
+        let Some(Stdout(stdout)) = output.stdout.take() else {
+            return report;
+        };
+
+        let mut other_lines = Vec::new();
+        for line in stdout.lines() {
+            match serde_json::from_str::<CargoMessage>(line) {
+                Ok(CargoMessage {
+                    reason,
+                    message: Some(diagnostic),
+                }) if reason == "compiler-message" => report.push(diagnostic.into()),
+                Ok(_) => {}
+                Err(_) => other_lines.push(line),
+            }
+        }
+
+        if !other_lines.is_empty() {
+            output.stdout = Some(Stdout(other_lines.join("\n")));
+        }
+        report
+    }
+
+    fn push(&mut self, diagnostic: Diagnostic) {
+        // "For more information about this error, try `rustc --explain E0308`"
+        if diagnostic.level == "failure-note" {
+            return;
+        }
+        // Code shared by several targets (lib, bins, tests) is reported once per target
+        let key = (
+            diagnostic.level.clone(),
+            diagnostic.code.clone(),
+            diagnostic.message.clone(),
+            diagnostic.span.clone(),
+        );
+        if !self.seen.insert(key) {
+            return;
+        }
+        match severity(&diagnostic.level) {
+            1 => self.warnings += 1,
+            2 => self.errors += 1,
+            _ => {}
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Drops the diagnostics the client isn't interested in
+    pub(crate) fn filter(&mut self, filter: &DiagnosticsFilter) -> Result<(), ErrorData> {
+        let min_severity = filter.min_severity()?;
+        let total = self.diagnostics.len();
+
+        self.diagnostics
+            .retain(|diagnostic| filter.matches(diagnostic, min_severity));
+        if let Some(max) = filter.max_diagnostics
+            && self.diagnostics.len() > max
+        {
+            self.diagnostics
+                .sort_by_key(|diagnostic| std::cmp::Reverse(severity(&diagnostic.level)));
+            self.diagnostics.truncate(max);
+        }
+
+        self.omitted = total - self.diagnostics.len();
+        Ok(())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub(crate) fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Compact text rendering for clients that don't read `structuredContent`
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::new();
+        for diagnostic in &self.diagnostics {
+            diagnostic.render(&mut out);
+        }
+        let _ = write!(
+            out,
+            "{} error(s), {} warning(s)",
+            self.errors, self.warnings
+        );
+        if self.omitted > 0 {
+            let _ = write!(out, ", {} diagnostic(s) omitted by filters", self.omitted);
+        }
+        out
+    }
+
+    /// Adds the report to the response both as structured content and as text
+    pub(crate) fn attach_to(self, response: &mut Response) {
+        if !self.is_empty() || self.omitted > 0 {
+            let content = RawContent::Text(RawTextContent {
+                text: self.render(),
+                meta: Some(Meta::new().with_description("compiler diagnostics").into()),
+            });
+            let mut annotations = Annotations::default();
+            annotations.audience = Some(vec![Role::User, Role::Assistant]);
+            annotations.priority = Some(1.);
+            response.add_content(content.annotate(annotations));
+        }
+        response.set_structured_content(&self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::{CommandLine, ExitStatus};
+
+    use super::*;
+
+    const CLIPPY_STDOUT: &str = r#"{"reason":"compiler-message","package_id":"path+file:///tmp/diag#0.1.0","message":{"rendered":"warning: unused variable: `x`\n","$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"note","message":"`#[warn(unused_variables)]` on by default","rendered":null,"spans":[]},{"children":[],"code":null,"level":"help","message":"if this is intentional, prefix it with an underscore","rendered":null,"spans":[{"byte_end":21,"byte_start":20,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":"_x","suggestion_applicability":"MachineApplicable","text":[]}]}],"level":"warning","message":"unused variable: `x`","spans":[{"byte_end":21,"byte_start":20,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}],"code":{"code":"unused_variables","explanation":null}}}
+{"reason":"compiler-message","package_id":"path+file:///tmp/diag#0.1.0","message":{"rendered":"error[E0308]: mismatched types\n","$message_type":"diagnostic","children":[],"level":"error","message":"mismatched types","spans":[{"byte_end":104,"byte_start":101,"column_end":21,"column_start":18,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":"expected `u32`, found `&str`","line_end":5,"line_start":5,"suggested_replacement":null,"suggestion_applicability":null,"text":[]},{"byte_end":98,"byte_start":95,"column_end":15,"column_start":12,"expansion":null,"file_name":"src/main.rs","is_primary":false,"label":"expected due to this","line_end":5,"line_start":5,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}],"code":{"code":"E0308","explanation":"Expected type did not match the received type."}}}
+{"reason":"compiler-message","package_id":"path+file:///tmp/diag#0.1.0","message":{"rendered":"For more information about this error, try `rustc --explain E0308`.\n","$message_type":"diagnostic","children":[],"level":"failure-note","message":"For more information about this error, try `rustc --explain E0308`.","spans":[],"code":null}}
+hello from build script
+{"reason":"build-finished","success":false}"#;
+
+    fn output(stdout: &str) -> Output {
+        Output {
+            tool_name: "cargo-clippy".into(),
+            cmd_line: CommandLine("cargo clippy".into()),
+            stdout: Some(Stdout(stdout.into())),
+            stderr: None,
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
+        }
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let mut output = output(CLIPPY_STDOUT);
+        let report = DiagnosticsReport::take_from(&mut output);
+
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.warnings, 1);
+        assert_eq!(
+            output.stdout.map(|s| s.0).as_deref(),
+            Some("hello from build script")
+        );
+
+        let [warning, error] = &report.diagnostics[..] else {
+            panic!("expected 2 diagnostics: {report:?}");
+        };
+        assert_eq!(warning.code.as_deref(), Some("unused_variables"));
+        assert_eq!(
+            warning.notes,
+            ["note: `#[warn(unused_variables)]` on by default"]
+        );
+        assert_eq!(
+            warning.suggestions,
+            [Suggestion {
+                message: "if this is intentional, prefix it with an underscore".into(),
+                span: Span {
+                    file: "src/main.rs".into(),
+                    line: 2,
+                    column: 9,
+                    end_line: 2,
+                    end_column: 10,
+                    label: None,
+                    byte_start: 20,
+                    byte_end: 21,
+                },
+                replacement: "_x".into(),
+                applicability: Some("MachineApplicable".into()),
+                group: 1,
+            }]
+        );
+        assert_eq!(error.level, "error");
+        assert_eq!(
+            error.span.as_ref().map(|s| (s.line, s.column)),
+            Some((5, 18))
+        );
+    }
+
+    #[test]
+    fn test_render_diagnostics() {
+        let report = DiagnosticsReport::take_from(&mut output(CLIPPY_STDOUT));
+        assert_eq!(
+            report.render(),
+            "warning[unused_variables]: unused variable: `x` (id: fa7f1f8b)
+  --> src/main.rs:2:9
+  = note: `#[warn(unused_variables)]` on by default
+  = help: if this is intentional, prefix it with an underscore at src/main.rs:2:9: `_x`
+error[E0308]: mismatched types (id: 1329dd26)
+  --> src/main.rs:5:18 expected `u32`, found `&str`
+1 error(s), 1 warning(s)"
+        );
+    }
+
+    #[test]
+    fn test_attach_structured_content() {
+        let report = DiagnosticsReport::take_from(&mut output(CLIPPY_STDOUT));
+        let mut response: Response = output("").into();
+        report.attach_to(&mut response);
+
+        let result = response.into_rmcp_result(false);
+        let structured = result.structured_content.expect("structured content");
+        assert_eq!(structured["errors"], 1);
+        assert_eq!(structured["diagnostics"][1]["code"], "E0308");
+        assert!(
+            result
+                .content
+                .iter()
+                .any(|c| c.as_text().is_some_and(|t| t.text.starts_with("warning[")))
+        );
+    }
+
+    fn report(diagnostics: &[(&str, Option<&str>, &str)]) -> DiagnosticsReport {
+        let mut report = DiagnosticsReport::default();
+        for (level, code, file) in diagnostics {
+            report.push(Diagnostic {
+                id: stable_hash(&[level, file]),
+                level: (*level).into(),
+                code: code.map(Into::into),
+                message: format!("{level} in {file}"),
+                span: Some(Span {
+                    file: (*file).into(),
+                    line: 1,
+                    column: 1,
+                    end_line: 1,
+                    end_column: 2,
+                    label: None,
+                    byte_start: 0,
+                    byte_end: 1,
+                }),
+                notes: Vec::new(),
+                suggestions: Vec::new(),
+            });
+        }
+        report
+    }
+
+    fn filter(json: serde_json::Value) -> DiagnosticsFilter {
+        serde_json::from_value(json).expect("valid filter")
+    }
+
+    #[test]
+    fn test_dedup_diagnostics() {
+        let stdout = format!("{CLIPPY_STDOUT}\n{CLIPPY_STDOUT}");
+        let report = DiagnosticsReport::take_from(&mut output(&stdout));
+        assert_eq!(report.diagnostics.len(), 2);
+        assert_eq!((report.errors, report.warnings), (1, 1));
+    }
+
+    #[test]
+    fn test_dedup_keeps_colliding_ids() {
+        let diagnostic = |message: &str| Diagnostic {
+            id: "0123abcd".into(),
+            level: "error".into(),
+            code: Some("E0308".into()),
+            message: message.into(),
+            span: None,
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        };
+        let mut report = DiagnosticsReport::default();
+        report.push(diagnostic("mismatched types"));
+        report.push(diagnostic("cannot find value `x` in this scope"));
+        report.push(diagnostic("mismatched types"));
+        assert_eq!(report.diagnostics.len(), 2);
+        assert_eq!(report.errors, 2);
+    }
+
+    #[test]
+    fn test_filter_min_level_and_max() {
+        let mut report = report(&[
+            ("warning", Some("dead_code"), "src/a.rs"),
+            ("error", Some("E0308"), "src/b.rs"),
+            ("warning", Some("clippy::len_zero"), "src/c.rs"),
+            ("error", Some("E0425"), "src/d.rs"),
+        ]);
+        report
+            .filter(&filter(serde_json::json!({"max_diagnostics": 3})))
+            .unwrap();
+        let levels: Vec<_> = report.diagnostics.iter().map(|d| &d.message).collect();
+        assert_eq!(
+            levels,
+            [
+                "error in src/b.rs",
+                "error in src/d.rs",
+                "warning in src/a.rs"
+            ]
+        );
+        assert_eq!(report.omitted, 1);
+
+        report
+            .filter(&filter(serde_json::json!({"min_level": "error"})))
+            .unwrap();
+        assert_eq!(report.diagnostics.len(), 2);
+
+        assert!(
+            filter(serde_json::json!({"min_level": "fatal"}))
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_filter_files_and_codes() {
+        let diagnostics = [
+            ("warning", Some("dead_code"), "src/parser/mod.rs"),
+            ("warning", Some("clippy::len_zero"), "src/parser/lexer.rs"),
+            ("warning", Some("clippy::needless_return"), "src/main.rs"),
+            ("error", None, "src/lib.rs"),
+        ];
+
+        let mut only_parser = report(&diagnostics);
+        only_parser
+            .filter(&filter(serde_json::json!({"only_files": "src/parser/**"})))
+            .unwrap();
+        assert_eq!(only_parser.diagnostics.len(), 2);
+
+        let mut only_clippy = report(&diagnostics);
+        only_clippy
+            .filter(&filter(serde_json::json!({
+                "lint_codes": ["clippy::*"],
+                "exclude_lint_codes": ["clippy::needless_return"],
+            })))
+            .unwrap();
+        let [diagnostic] = &only_clippy.diagnostics[..] else {
+            panic!("expected 1 diagnostic: {only_clippy:?}");
+        };
+        assert_eq!(diagnostic.code.as_deref(), Some("clippy::len_zero"));
+        assert!(
+            only_clippy
+                .render()
+                .ends_with("3 diagnostic(s) omitted by filters")
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("src/*.rs", "src/main.rs"));
+        assert!(!glob_match("src/*.rs", "src/tools/mod.rs"));
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/tools/cargo/mod.rs"));
+        assert!(glob_match("**/mod.rs", "src/tools/mod.rs"));
+        assert!(glob_match("src/ma?n.rs", "src/main.rs"));
+        assert!(!glob_match("src/main.rs", "src/main.rs.bak"));
+        assert!(glob_match("*", ""));
+    }
+}
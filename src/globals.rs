@@ -1,23 +1,15 @@
-use std::{path::PathBuf, sync::OnceLock};
+use std::{
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
-static WORKSPACE_ROOT: OnceLock<PathBuf> = OnceLock::new();
 static DEFAULT_REGISTRY: OnceLock<String> = OnceLock::new();
-
-pub fn set_workspace_root(root: impl Into<PathBuf>) {
-    WORKSPACE_ROOT
-        .set(root.into())
-        .expect("Workspace root can only be set once");
-}
-
-/// Attempts to set the workspace root. Returns `true` if set successfully,
-/// `false` if it was already set (e.g. via CLI argument).
-pub fn try_set_workspace_root(root: impl Into<PathBuf>) -> bool {
-    WORKSPACE_ROOT.set(root.into()).is_ok()
-}
-
-pub fn get_workspace_root() -> Option<&'static PathBuf> {
-    WORKSPACE_ROOT.get()
-}
+static DEFAULT_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+static MAX_OUTPUT_BYTES: OnceLock<usize> = OnceLock::new();
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
 
 pub fn set_default_registry(registry: String) {
     DEFAULT_REGISTRY
@@ -28,3 +20,31 @@ pub fn set_default_registry(registry: String) {
 pub fn get_default_registry() -> Option<&'static str> {
     DEFAULT_REGISTRY.get().map(|s| s.as_str())
 }
+
+pub fn set_default_timeout(timeout: Duration) {
+    DEFAULT_TIMEOUT
+        .set(timeout)
+        .expect("Default timeout can only be set once");
+}
+
+pub fn get_default_timeout() -> Option<Duration> {
+    DEFAULT_TIMEOUT.get().copied()
+}
+
+pub fn set_max_output_bytes(max_bytes: usize) {
+    MAX_OUTPUT_BYTES
+        .set(max_bytes)
+        .expect("Maximum output size can only be set once");
+}
+
+pub fn get_max_output_bytes() -> Option<usize> {
+    MAX_OUTPUT_BYTES.get().copied()
+}
+
+pub fn set_read_only() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
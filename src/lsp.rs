@@ -0,0 +1,540 @@
+//! Minimal LSP client driving one `rust-analyzer` process per workspace.
+//!
+//! The processes are started by the first query in a workspace and kept running so that
+//! the following queries don't wait for the workspace to be loaded again.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::ErrorData;
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, ChildStdout},
+    sync::{oneshot, watch},
+};
+
+use crate::{ToolContext, workspace::path_to_file_uri};
+
+const RUST_ANALYZER: &str = "rust-analyzer";
+
+/// Time a query waits for rust-analyzer to load the workspace before it is sent anyway
+const INDEXING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Time rust-analyzer gets to answer the `initialize` request before it is killed
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time rust-analyzer gets to exit after the `shutdown` request before it is killed
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Error returned when the workspace changed while rust-analyzer was answering, the request can be retried
+const CONTENT_MODIFIED: i64 = -32801;
+const MAX_RETRIES: usize = 3;
+
+/// Number of stderr lines kept to explain why rust-analyzer exited
+const MAX_STDERR_LINES: usize = 20;
+
+/// rust-analyzer of each workspace, the calls that need it while it starts wait for the same cell
+type AnalyzerCell = Arc<tokio::sync::OnceCell<Arc<LspClient>>>;
+
+static ANALYZERS: Mutex<BTreeMap<PathBuf, AnalyzerCell>> = Mutex::new(BTreeMap::new());
+
+type PendingRequests = Mutex<HashMap<u64, oneshot::Sender<Result<Value, ResponseError>>>>;
+
+#[derive(Debug, serde::Deserialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+/// A running `rust-analyzer` and the state of the documents it was sent
+pub(crate) struct LspClient {
+    root: PathBuf,
+    child: tokio::sync::Mutex<Child>,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    next_id: AtomicU64,
+    pending: PendingRequests,
+    alive: AtomicBool,
+    quiescent: watch::Receiver<bool>,
+    stderr: Mutex<VecDeque<String>>,
+    /// Version and text of the documents opened in rust-analyzer, by URI
+    documents: tokio::sync::Mutex<HashMap<String, (i64, String)>>,
+}
+
+/// Returns the rust-analyzer of the workspace the tool call runs in, starting it if needed
+pub(crate) async fn rust_analyzer(ctx: &ToolContext) -> Result<Arc<LspClient>, ErrorData> {
+    let root = match ctx.workspace_root() {
+        Some(root) => root.to_path_buf(),
+        None => std::env::current_dir().map_err(|e| {
+            ErrorData::internal_error(format!("Failed to get current directory: {e}"), None)
+        })?,
+    };
+
+    let cell = {
+        let mut analyzers = ANALYZERS.lock().unwrap_or_else(|e| e.into_inner());
+        let cell = analyzers.entry(root.clone()).or_default();
+        if cell
+            .get()
+            .is_some_and(|client| !client.alive.load(Ordering::Acquire))
+        {
+            *cell = AnalyzerCell::default();
+        }
+        cell.clone()
+    };
+    cell.get_or_try_init(|| LspClient::start(&root, ctx))
+        .await
+        .cloned()
+}
+
+/// Asks every running rust-analyzer to exit, killing the ones that don't in time
+pub(crate) async fn shutdown() {
+    let analyzers = std::mem::take(&mut *ANALYZERS.lock().unwrap_or_else(|e| e.into_inner()));
+    let clients = analyzers
+        .into_values()
+        .filter_map(|cell| cell.get().cloned());
+    let shutdowns = clients.map(|client| async move {
+        tracing::info!("Shutting down rust-analyzer in {}", client.root.display());
+        let exited = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+            if client.alive.load(Ordering::Acquire) {
+                _ = client.send_request("shutdown", Value::Null).await;
+                _ = client.notify("exit", Value::Null).await;
+            }
+            client.child.lock().await.wait().await
+        })
+        .await;
+        if !matches!(exited, Ok(Ok(_))) {
+            tracing::warn!(
+                "rust-analyzer in {} did not exit, killing it",
+                client.root.display()
+            );
+            _ = client.child.lock().await.kill().await;
+        }
+    });
+    for shutdown in shutdowns {
+        shutdown.await;
+    }
+}
+
+impl LspClient {
+    async fn start(root: &Path, ctx: &ToolContext) -> Result<Arc<Self>, ErrorData> {
+        let mut cmd = tokio::process::Command::new(RUST_ANALYZER);
+        cmd.current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        // Keep Ctrl+C in the terminal from killing it before it is shut down
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        tracing::info!("Starting rust-analyzer in {}", root.display());
+        let mut child = cmd.spawn().map_err(|e| {
+            tracing::error!("Failed to start rust-analyzer: {e}");
+            if e.kind() == std::io::ErrorKind::NotFound {
+                ErrorData::internal_error(
+                    "rust-analyzer was not found, install it with `rustup component add rust-analyzer`",
+                    None,
+                )
+            } else {
+                ErrorData::internal_error(format!("Failed to start rust-analyzer: {e}"), None)
+            }
+        })?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let (quiescent_tx, quiescent) = watch::channel(false);
+        let client = Arc::new(Self {
+            root: root.to_path_buf(),
+            child: tokio::sync::Mutex::new(child),
+            stdin: tokio::sync::Mutex::new(stdin),
+            next_id: AtomicU64::new(1),
+            pending: Mutex::new(HashMap::new()),
+            alive: AtomicBool::new(true),
+            quiescent,
+            stderr: Mutex::new(VecDeque::new()),
+            documents: tokio::sync::Mutex::new(HashMap::new()),
+        });
+
+        let reader = client.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::debug!("rust-analyzer: {line}");
+                let mut stderr = reader.stderr.lock().unwrap_or_else(|e| e.into_inner());
+                if stderr.len() == MAX_STDERR_LINES {
+                    stderr.pop_front();
+                }
+                stderr.push_back(line);
+            }
+        });
+        tokio::spawn(client.clone().read_messages(stdout, quiescent_tx));
+
+        let root_uri = path_to_file_uri(root);
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let params = json!({
+            "processId": std::process::id(),
+            "rootUri": root_uri,
+            "workspaceFolders": [{ "uri": root_uri, "name": name }],
+            "capabilities": {
+                "textDocument": {
+                    "hover": { "contentFormat": ["markdown", "plaintext"] },
+                    "definition": { "linkSupport": true },
+                    "documentSymbol": { "hierarchicalDocumentSymbolSupport": true },
+                },
+                "experimental": { "serverStatusNotification": true },
+            },
+            "initializationOptions": {
+                // `cargo check` on save would compete with the cargo tools for the build directory lock
+                "checkOnSave": false,
+                "cargo": { "targetDir": true },
+                "files": { "watcher": "server" },
+            },
+        });
+        let initialize = async {
+            client.send_request("initialize", params).await?;
+            client.notify("initialized", json!({})).await
+        };
+        let initialized = tokio::select! {
+            initialized = tokio::time::timeout(INITIALIZE_TIMEOUT, initialize) => {
+                initialized.unwrap_or_else(|_| {
+                    Err(ErrorData::internal_error(
+                        format!(
+                            "rust-analyzer did not answer `initialize` within {} seconds",
+                            INITIALIZE_TIMEOUT.as_secs()
+                        ),
+                        None,
+                    ))
+                })
+            }
+            () = ctx.cancellation_token().cancelled() => {
+                Err(ErrorData::internal_error("Request was cancelled while rust-analyzer was starting", None))
+            }
+        };
+        if let Err(e) = initialized {
+            // the reader tasks keep the client alive, the process has to be killed explicitly
+            _ = client.child.lock().await.kill().await;
+            return Err(e);
+        }
+        Ok(client)
+    }
+
+    /// Dispatches the responses and notifications of rust-analyzer until it exits
+    async fn read_messages(self: Arc<Self>, stdout: ChildStdout, quiescent: watch::Sender<bool>) {
+        let mut reader = BufReader::new(stdout);
+        loop {
+            let message = match read_message(&mut reader).await {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Failed to read from rust-analyzer: {e}");
+                    break;
+                }
+            };
+
+            let method = message.get("method").and_then(Value::as_str);
+            let id = message.get("id");
+            match (method, id) {
+                // Response to one of our requests
+                (None, Some(id)) => {
+                    let sender = id.as_u64().and_then(|id| {
+                        self.pending
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .remove(&id)
+                    });
+                    let result = match message.get("error") {
+                        Some(error) => Err(serde_json::from_value(error.clone()).unwrap_or(
+                            ResponseError {
+                                code: 0,
+                                message: error.to_string(),
+                            },
+                        )),
+                        None => Ok(message.get("result").cloned().unwrap_or_default()),
+                    };
+                    if let Some(sender) = sender {
+                        _ = sender.send(result);
+                    }
+                }
+                // Request from rust-analyzer, none of them needs more than an acknowledgement
+                (Some(method), Some(id)) => {
+                    tracing::debug!("rust-analyzer request: {method}");
+                    let response = json!({ "jsonrpc": "2.0", "id": id, "result": null });
+                    _ = write_message(&mut *self.stdin.lock().await, &response).await;
+                }
+                (Some("experimental/serverStatus"), None) => {
+                    let params = &message["params"];
+                    if let Some(status) = params["message"].as_str() {
+                        tracing::info!("rust-analyzer status: {status}");
+                    }
+                    quiescent.send_replace(params["quiescent"].as_bool().unwrap_or(false));
+                }
+                (Some(method), None) => tracing::trace!("rust-analyzer notification: {method}"),
+                (None, None) => tracing::warn!("Unexpected message from rust-analyzer: {message}"),
+            }
+        }
+
+        tracing::info!("rust-analyzer in {} exited", self.root.display());
+        self.alive.store(false, Ordering::Release);
+        // Dropping the senders fails the requests still waiting for a response
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Waits until rust-analyzer has loaded the workspace. Returns `false` if it is still
+    /// indexing after [`INDEXING_TIMEOUT`], queries then may return partial results.
+    pub(crate) async fn wait_until_ready(&self, ctx: &ToolContext) -> Result<bool, ErrorData> {
+        let mut quiescent = self.quiescent.clone();
+        tokio::select! {
+            ready = tokio::time::timeout(INDEXING_TIMEOUT, quiescent.wait_for(|quiescent| *quiescent)) => {
+                match ready {
+                    Ok(Ok(_)) => Ok(true),
+                    Ok(Err(_)) => Err(self.exited_error()),
+                    Err(_) => Ok(false),
+                }
+            }
+            () = ctx.cancellation_token().cancelled() => {
+                Err(ErrorData::internal_error("Request was cancelled while rust-analyzer was indexing", None))
+            }
+        }
+    }
+
+    /// Makes rust-analyzer see the current content of `path`, returns that content
+    pub(crate) async fn sync_document(&self, path: &Path) -> Result<String, ErrorData> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            ErrorData::invalid_params(format!("Failed to read {}: {e}", path.display()), None)
+        })?;
+        let uri = path_to_file_uri(path);
+
+        let mut documents = self.documents.lock().await;
+        match documents.get_mut(&uri) {
+            Some((_, synced)) if *synced == text => {}
+            Some((version, synced)) => {
+                *version += 1;
+                *synced = text.clone();
+                self.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": *version },
+                        "contentChanges": [{ "text": text }],
+                    }),
+                )
+                .await?;
+            }
+            None => {
+                self.notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text },
+                    }),
+                )
+                .await?;
+                documents.insert(uri, (1, text.clone()));
+            }
+        }
+        Ok(text)
+    }
+
+    /// Sends a request, retrying it while rust-analyzer reports that the workspace changed meanwhile
+    pub(crate) async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        ctx: &ToolContext,
+    ) -> Result<Value, ErrorData> {
+        let mut attempt = 1;
+        loop {
+            let (id, response) = self.start_request(method, params.clone()).await?;
+            let response = tokio::select! {
+                response = response => response,
+                () = ctx.cancellation_token().cancelled() => {
+                    self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+                    _ = self.notify("$/cancelRequest", json!({ "id": id })).await;
+                    return Err(ErrorData::internal_error(format!("Request `{method}` was cancelled"), None));
+                }
+            };
+            match response {
+                Ok(Err(error)) if error.code == CONTENT_MODIFIED && attempt < MAX_RETRIES => {
+                    tracing::debug!("rust-analyzer content modified, retrying {method}");
+                    attempt += 1;
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+                response => return self.response_result(method, response),
+            }
+        }
+    }
+
+    async fn send_request(&self, method: &str, params: Value) -> Result<Value, ErrorData> {
+        let (_, response) = self.start_request(method, params).await?;
+        self.response_result(method, response.await)
+    }
+
+    async fn start_request(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<(u64, oneshot::Receiver<Result<Value, ResponseError>>), ErrorData> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, tx);
+        self.write(&message(Some(id), method, params)).await?;
+        Ok((id, rx))
+    }
+
+    fn response_result(
+        &self,
+        method: &str,
+        response: Result<Result<Value, ResponseError>, oneshot::error::RecvError>,
+    ) -> Result<Value, ErrorData> {
+        match response {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(error)) => Err(ErrorData::internal_error(
+                format!(
+                    "rust-analyzer failed to answer `{method}`: {}",
+                    error.message
+                ),
+                None,
+            )),
+            Err(_) => Err(self.exited_error()),
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<(), ErrorData> {
+        self.write(&message(None, method, params)).await
+    }
+
+    async fn write(&self, message: &Value) -> Result<(), ErrorData> {
+        if !self.alive.load(Ordering::Acquire) {
+            return Err(self.exited_error());
+        }
+        write_message(&mut *self.stdin.lock().await, message)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to write to rust-analyzer: {e}");
+                self.exited_error()
+            })
+    }
+
+    fn exited_error(&self) -> ErrorData {
+        let stderr = self.stderr.lock().unwrap_or_else(|e| e.into_inner());
+        let stderr = stderr.iter().map(String::as_str).collect::<Vec<_>>();
+        ErrorData::internal_error(
+            format!(
+                "rust-analyzer in {} exited unexpectedly, it is restarted by the next call. stderr:\n{}",
+                self.root.display(),
+                stderr.join("\n")
+            ),
+            None,
+        )
+    }
+}
+
+fn message(id: Option<u64>, method: &str, params: Value) -> Value {
+    let mut message = json!({ "jsonrpc": "2.0", "method": method });
+    if let Some(id) = id {
+        message["id"] = id.into();
+    }
+    // `shutdown` and `exit` have no parameters
+    if !params.is_null() {
+        message["params"] = params;
+    }
+    message
+}
+
+/// Reads one `Content-Length` framed message, `None` at the end of the stream
+async fn read_message(reader: &mut (impl AsyncBufRead + Unpin)) -> std::io::Result<Option<Value>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = Some(value.trim().parse::<usize>().map_err(|e| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
+            })?);
+        }
+    }
+
+    let Some(content_length) = content_length else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "message without Content-Length header",
+        ));
+    };
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+async fn write_message(
+    writer: &mut (impl AsyncWrite + Unpin),
+    message: &Value,
+) -> std::io::Result<()> {
+    let body = message.to_string();
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n{body}", body.len()).as_bytes())
+        .await?;
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_message_framing_round_trip() {
+        let messages = [
+            message(
+                Some(1),
+                "initialize",
+                json!({ "rootUri": "file:///tmp/café" }),
+            ),
+            message(None, "exit", Value::Null),
+        ];
+        let mut stream = Vec::new();
+        for message in &messages {
+            write_message(&mut stream, message).await.unwrap();
+        }
+        // other headers are ignored and the header name is case insensitive
+        stream.extend_from_slice(
+            b"content-length: 2\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
+        );
+
+        let mut reader = stream.as_slice();
+        for message in &messages {
+            assert_eq!(
+                read_message(&mut reader).await.unwrap().as_ref(),
+                Some(message)
+            );
+        }
+        assert_eq!(read_message(&mut reader).await.unwrap(), Some(json!({})));
+        assert_eq!(read_message(&mut reader).await.unwrap(), None);
+
+        let mut reader: &[u8] = b"Content-Type: application/json\r\n\r\n{}";
+        assert!(read_message(&mut reader).await.is_err());
+    }
+}
@@ -1,11 +1,23 @@
+mod audit;
 mod command;
+mod config;
+mod confirmation;
+mod diagnostics;
 mod globals;
+mod lsp;
 mod meta;
+mod output_store;
+mod progress;
+mod prompts;
+mod resources;
 mod response;
 mod rmcp_server;
+mod rustdoc;
 mod serde_utils;
+mod test_report;
 mod tool;
 mod tools;
+mod transport;
 mod version;
 mod workspace;
 
@@ -13,11 +25,10 @@ use clap::Parser;
 use command::execute_command;
 use ohno::IntoAppError;
 use response::Response;
-use rmcp::ServiceExt;
-use rmcp::service::QuitReason;
-use tool::Tool;
+use tool::{Tool, ToolContext, ToolHints};
 use tracing_appender::rolling;
 use tracing_subscriber::{EnvFilter, fmt};
+use transport::TransportKind;
 use version::AppVersion;
 
 const RMCP_VERSION: &str = env!("RMCP_VERSION");
@@ -25,6 +36,10 @@ const RMCP_VERSION: &str = env!("RMCP_VERSION");
 #[derive(Parser, Debug)]
 #[command(author, version = AppVersion, about = "Rust MCP Server", long_about = None)]
 struct Args {
+    /// Config file path. By default, `rust-mcp-server.toml` is looked up in the workspace root and then in the user config directory.
+    #[arg(long)]
+    config: Option<String>,
+
     /// Log level (error, warn, info, debug, trace)
     #[arg(long, default_value = "info")]
     log_level: String,
@@ -45,13 +60,45 @@ struct Args {
     #[arg(long)]
     registry: Option<String>,
 
+    /// Default time limit in seconds for commands spawned by tools. Commands that exceed it are killed. By default, there is no limit.
+    #[arg(long)]
+    default_timeout: Option<u64>,
+
+    /// Maximum size in bytes of each output stream returned by tools, the middle of longer streams is elided and can be read with get-command-output. `0` disables the limit. [default: 65536]
+    #[arg(long)]
+    max_output_bytes: Option<usize>,
+
+    /// JSONL file every tool call is appended to. An empty path disables it. [default: $XDG_STATE_HOME/rust-mcp-server/audit.jsonl]
+    #[arg(long)]
+    audit_log: Option<String>,
+
+    /// Directory with additional prompt files (*.md) loaded at startup. Files override built-in prompts with the same name.
+    #[arg(long)]
+    prompts_dir: Option<String>,
+
     /// Generate tools.md documentation file and exit
     #[arg(long)]
     generate_docs: Option<String>,
 
+    /// Hide the tools that modify the workspace, lockfile or toolchains and reject arguments that would write
+    #[arg(long)]
+    read_only: bool,
+
     /// Disable experimental recommendations for agent in tool responses
     #[arg(long)]
     no_recommendations: bool,
+
+    /// Transport used to communicate with MCP clients
+    #[arg(long, value_enum, default_value = "stdio")]
+    transport: TransportKind,
+
+    /// Address to listen on when using the HTTP transport
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: std::net::SocketAddr,
+
+    /// Additional `Host` header value accepted by the HTTP transport. Only loopback hosts are allowed by default. Can be specified multiple times.
+    #[arg(long = "allowed-host")]
+    allowed_hosts: Vec<String>,
 }
 
 #[tokio::main]
@@ -79,24 +126,73 @@ async fn main() -> Result<(), ohno::AppError> {
     tracing::info!("Server version: {}", AppVersion::version());
     tracing::info!("RMCP crate version: {RMCP_VERSION}");
 
-    let detect_workspace = args.workspace.is_none();
-    if let Some(workspace) = args.workspace {
-        tracing::info!("Workspace root has been overridden: {workspace}");
-        globals::set_workspace_root(workspace);
+    let config_workspace = args
+        .workspace
+        .as_ref()
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::current_dir().ok());
+    let mut config = config::Config::load(
+        args.config.as_deref().map(std::path::Path::new),
+        config_workspace.as_deref(),
+    )?;
+
+    let fixed_workspace = args.workspace.map(std::path::PathBuf::from);
+    if let Some(workspace) = &fixed_workspace {
+        tracing::info!(
+            "Workspace root has been overridden: {}",
+            workspace.display()
+        );
     } else {
         tracing::info!("No workspace root specified, workspace auto-detection enabled");
     }
 
-    if let Some(registry) = args.registry {
+    if let Some(registry) = args.registry.or(config.registry.take()) {
         tracing::info!("Default cargo registry has been set: {registry}");
         globals::set_default_registry(registry);
     }
 
-    let server = rmcp_server::Server::new(
-        &args.disabled_tools,
-        args.no_recommendations,
-        detect_workspace,
-    );
+    if let Some(timeout) = args
+        .default_timeout
+        .or(config.default_timeout)
+        .filter(|t| *t > 0)
+    {
+        tracing::info!("Default command timeout has been set: {timeout}s");
+        globals::set_default_timeout(std::time::Duration::from_secs(timeout));
+    }
+
+    let max_output_bytes = args
+        .max_output_bytes
+        .or(config.max_output_bytes)
+        .unwrap_or(output_store::DEFAULT_MAX_OUTPUT_BYTES);
+    tracing::info!("Maximum output size has been set: {max_output_bytes} bytes");
+    globals::set_max_output_bytes(max_output_bytes);
+
+    let audit_log = args
+        .audit_log
+        .map(std::path::PathBuf::from)
+        .or(config.audit_log.take())
+        .or_else(config::default_audit_log)
+        .filter(|path| !path.as_os_str().is_empty());
+    match &audit_log {
+        Some(path) => tracing::info!("Audit log: {}", path.display()),
+        None => tracing::info!("Audit log disabled"),
+    }
+    audit::init(audit_log);
+
+    let prompts_dir = args
+        .prompts_dir
+        .map(std::path::PathBuf::from)
+        .or(config.prompts_dir.take());
+    let prompts = prompts::PromptLibrary::load(prompts_dir.as_deref());
+
+    if args.read_only || config.read_only {
+        tracing::info!("Read-only mode enabled");
+        globals::set_read_only();
+    }
+
+    config.disabled_tools.extend(args.disabled_tools);
+    let server =
+        rmcp_server::Server::new(&config, args.no_recommendations, fixed_workspace, prompts);
 
     // Handle documentation generation mode
     if let Some(output_file) = args.generate_docs {
@@ -107,30 +203,10 @@ async fn main() -> Result<(), ohno::AppError> {
         return Ok(());
     }
 
-    let service = server
-        .serve(rmcp::transport::stdio())
-        .await
-        .into_app_err("Failed to start server")?;
-
-    eprintln!("Rust MCP Server started on stdio");
-
-    let result = service.waiting().await;
-
-    match result {
-        Ok(QuitReason::Closed) => tracing::info!("Server closed normally"),
-        Ok(QuitReason::Cancelled) => tracing::info!("Server was cancelled"),
-        Ok(QuitReason::JoinError(error)) => {
-            tracing::error!("Server join error: {error}");
-            return Err(error.into());
-        }
-        Ok(reason) => {
-            tracing::info!("Server exited with reason: {reason:?}");
-        }
-        Err(error) => {
-            tracing::error!("Server encountered an error: {error}");
-            return Err(error.into());
-        }
-    }
-
-    Ok(())
+    let result = match args.transport {
+        TransportKind::Stdio => transport::serve_stdio(server).await,
+        TransportKind::Http => transport::serve_http(server, args.bind, &args.allowed_hosts).await,
+    };
+    lsp::shutdown().await;
+    result
 }
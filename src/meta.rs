@@ -16,6 +16,12 @@ impl Meta {
         self
     }
 
+    pub(crate) fn with_bool(mut self, key: impl Into<String>, value: bool) -> Self {
+        self.inner
+            .insert(key.into(), serde_json::Value::Bool(value));
+        self
+    }
+
     pub(crate) fn with_i32(mut self, key: impl Into<String>, value: i32) -> Self {
         self.inner
             .insert(key.into(), serde_json::Value::Number(value.into()));
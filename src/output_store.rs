@@ -0,0 +1,175 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Tool parameter limiting the size of each output stream, accepted by every tool
+pub(crate) const MAX_OUTPUT_BYTES_PARAM: &str = "max_output_bytes";
+
+/// Size of each output stream kept in a tool result when neither the server nor the call sets one
+pub(crate) const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Number of full outputs kept for `get-command-output`, the oldest ones are dropped first
+const MAX_STORED_OUTPUTS: usize = 32;
+
+static OUTPUTS: Mutex<VecDeque<Arc<StoredOutput>>> = Mutex::new(VecDeque::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Keeps the IDs of this process apart from those an earlier server recorded in the audit log
+static ID_PREFIX: LazyLock<String> = LazyLock::new(|| {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u32;
+    format!("output-{:08x}", nanos ^ std::process::id().rotate_left(16))
+});
+
+/// Full output of a recent command
+#[derive(Debug)]
+pub(crate) struct StoredOutput {
+    pub(crate) id: String,
+    pub(crate) cmd_line: String,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+}
+
+impl StoredOutput {
+    pub(crate) fn stream(&self, stream: Stream) -> &str {
+        match stream {
+            Stream::Stdout => &self.stdout,
+            Stream::Stderr => &self.stderr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Keeps the full output of a command and returns the ID it can be retrieved with
+pub(crate) fn store(cmd_line: &str, stdout: &str, stderr: &str) -> String {
+    let id = format!("{}-{}", *ID_PREFIX, NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let mut outputs = OUTPUTS.lock().unwrap_or_else(|e| e.into_inner());
+    if outputs.len() == MAX_STORED_OUTPUTS {
+        outputs.pop_front();
+    }
+    outputs.push_back(Arc::new(StoredOutput {
+        id: id.clone(),
+        cmd_line: cmd_line.to_owned(),
+        stdout: stdout.to_owned(),
+        stderr: stderr.to_owned(),
+    }));
+    id
+}
+
+pub(crate) fn get(id: &str) -> Option<Arc<StoredOutput>> {
+    OUTPUTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|output| output.id == id)
+        .cloned()
+}
+
+/// Replaces the middle of a stream longer than `max_bytes` with a summary of what was elided,
+/// the head and the tail are cut at line boundaries when possible
+pub(crate) fn elide(text: &str, max_bytes: usize, id: &str, stream: Stream) -> Option<String> {
+    if text.len() <= max_bytes {
+        return None;
+    }
+
+    let mut head_end = text.floor_char_boundary(max_bytes / 2);
+    if let Some(pos) = text[..head_end].rfind('\n') {
+        head_end = pos + 1;
+    }
+    let mut tail_start = text
+        .ceil_char_boundary(text.len() - max_bytes / 2)
+        .max(head_end);
+    if !text[..tail_start].ends_with('\n')
+        && let Some(pos) = text[tail_start..].find('\n')
+    {
+        tail_start += pos + 1;
+    }
+
+    let line_of = |offset: usize| {
+        text.as_bytes()[..offset]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count()
+            + 1
+    };
+    let first_line = line_of(head_end);
+    let last_line = line_of(tail_start.saturating_sub(1).max(head_end));
+    let elided = &text[head_end..tail_start];
+    let count = |word: &str| elided.lines().filter(|line| line.contains(word)).count();
+
+    Some(format!(
+        "{}\n[... lines {first_line}-{last_line} elided ({} bytes, {} lines containing \"error\", {} containing \"warning\"). \
+        Call get-command-output with output_id \"{id}\" and stream \"{}\" to read them by line range or search them with a pattern ...]\n{}",
+        text[..head_end].trim_end_matches('\n'),
+        elided.len(),
+        count("error"),
+        count("warning"),
+        stream.name(),
+        &text[tail_start..],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elide() {
+        let text: String = (1..=100).map(|i| format!("line {i}\n")).collect();
+        let text = text.trim_end();
+        assert_eq!(elide(text, text.len(), "output-1", Stream::Stdout), None);
+
+        let elided = elide(text, 80, "output-1", Stream::Stderr).unwrap();
+        let lines: Vec<_> = elided.lines().collect();
+        assert_eq!(
+            lines[..5],
+            ["line 1", "line 2", "line 3", "line 4", "line 5"]
+        );
+        assert_eq!(
+            lines[5],
+            "[... lines 6-95 elided (716 bytes, 0 lines containing \"error\", 0 containing \"warning\"). \
+            Call get-command-output with output_id \"output-1\" and stream \"stderr\" to read them by line range or search them with a pattern ...]"
+        );
+        assert_eq!(
+            lines[6..],
+            ["line 96", "line 97", "line 98", "line 99", "line 100"]
+        );
+
+        // A single long line is cut in the middle, on a char boundary
+        let elided = elide(&"é".repeat(100), 11, "output-2", Stream::Stdout).unwrap();
+        assert!(elided.starts_with("éé\n[... lines 1-1 elided (192 bytes,"));
+        assert!(elided.ends_with("...]\néé"));
+    }
+
+    #[test]
+    fn test_store() {
+        let id = store("cargo build", "out", "err");
+        let output = get(&id).unwrap();
+        assert_eq!(output.cmd_line, "cargo build");
+        assert_eq!(output.stream(Stream::Stderr), "err");
+        assert!(get("output-0").is_none());
+        // IDs recorded by an earlier process do not resolve to this one's outputs
+        assert!(get(&id.replace(ID_PREFIX.as_str(), "output-00000000")).is_none());
+    }
+}
@@ -0,0 +1,308 @@
+use rmcp::{
+    Peer, RoleServer,
+    model::{ProgressNotificationParam, ProgressToken},
+    service::RequestContext,
+};
+
+/// Sends `notifications/progress` for a request that carries a progress token
+#[derive(Debug, Clone)]
+pub(crate) struct ProgressReporter {
+    peer: Peer<RoleServer>,
+    token: ProgressToken,
+}
+
+impl ProgressReporter {
+    /// Returns `None` if the client didn't ask for progress notifications
+    pub(crate) fn from_request(context: &RequestContext<RoleServer>) -> Option<Self> {
+        let token = context.meta.get_progress_token()?;
+        Some(Self {
+            peer: context.peer.clone(),
+            token,
+        })
+    }
+
+    pub(crate) async fn notify(&self, update: ProgressUpdate) {
+        let mut param = ProgressNotificationParam::new(self.token.clone(), update.progress)
+            .with_message(update.message);
+        param.total = update.total;
+
+        if let Err(e) = self.peer.notify_progress(param).await {
+            tracing::debug!("Failed to send progress notification: {e}");
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProgressUpdate {
+    pub(crate) progress: f64,
+    pub(crate) total: Option<f64>,
+    pub(crate) message: String,
+}
+
+/// Turns cargo status lines, cargo progress bars and libtest results into progress updates.
+///
+/// Reported progress only ever increases: compilation uses cargo's `N/M` unit counters,
+/// and every test result adds one on top of that.
+#[derive(Debug, Default)]
+pub(crate) struct ProgressParser {
+    progress: f64,
+    total: Option<f64>,
+    last_status: Option<String>,
+}
+
+const CARGO_STATUSES: &[&str] = &[
+    "Compiling",
+    "Checking",
+    "Documenting",
+    "Scraping",
+    "Packaging",
+    "Verifying",
+    "Downloaded",
+];
+
+impl ProgressParser {
+    pub(crate) fn parse_line(&mut self, line: &str) -> Option<ProgressUpdate> {
+        let line = line.trim();
+
+        if let Some((done, total, units)) = parse_progress_bar(line) {
+            self.total = Some(total);
+            if done <= self.progress {
+                return None;
+            }
+            self.progress = done;
+            let message = match self.last_status.take() {
+                Some(status) => format!("{status} ({done}/{total})"),
+                None => format!("Building {done}/{total}: {units}"),
+            };
+            return Some(self.update(message));
+        }
+
+        if CARGO_STATUSES.iter().any(|status| {
+            line.strip_prefix(status)
+                .is_some_and(|s| s.starts_with(' '))
+        }) {
+            if self.total.is_some() {
+                // reported together with the next progress bar update
+                self.last_status = Some(line.to_owned());
+                return None;
+            }
+            self.progress += 1.;
+            return Some(self.update(line.to_owned()));
+        }
+
+        if let Some(count) = line
+            .strip_prefix("running ")
+            .and_then(|s| s.strip_suffix(" tests").or_else(|| s.strip_suffix(" test")))
+            .and_then(|count| count.parse::<u32>().ok())
+        {
+            self.total = Some(self.progress + f64::from(count));
+            return None;
+        }
+
+        if line.starts_with("test ")
+            && [" ... ok", " ... FAILED", " ... ignored"]
+                .iter()
+                .any(|result| line.contains(result))
+        {
+            self.progress += 1.;
+            return Some(self.update(line.to_owned()));
+        }
+
+        // cargo-nextest status lines, e.g. `Starting 3 tests across 2 binaries` and `PASS [   0.004s] foo tests::bar`
+        if let Some(count) = line
+            .strip_prefix("Starting ")
+            .and_then(|s| s.split_once(" test"))
+            .and_then(|(count, _)| count.parse::<u32>().ok())
+        {
+            self.total = Some(self.progress + f64::from(count));
+            return None;
+        }
+
+        if ["PASS [", "FAIL [", "SKIP [", "TIMEOUT ["]
+            .iter()
+            .any(|status| line.starts_with(status))
+        {
+            self.progress += 1.;
+            return Some(self.update(line.to_owned()));
+        }
+
+        // `--format json` events, printed by `cargo test` on nightly toolchains
+        if let Some(count) = line
+            .strip_prefix(r#"{ "type": "suite", "event": "started", "test_count": "#)
+            .and_then(|s| s.strip_suffix(" }"))
+            .and_then(|count| count.parse::<u32>().ok())
+        {
+            self.total = Some(self.progress + f64::from(count));
+            return None;
+        }
+
+        if line.starts_with(r#"{ "type": "test", "name": "#)
+            && let Some(name) = line.split('"').nth(7)
+            && let Some(result) = ["ok", "failed", "ignored"]
+                .into_iter()
+                .find(|result| line.contains(&format!(r#""event": "{result}""#)))
+        {
+            self.progress += 1.;
+            return Some(self.update(format!("test {name} ... {result}")));
+        }
+
+        None
+    }
+
+    fn update(&self, message: String) -> ProgressUpdate {
+        ProgressUpdate {
+            progress: self.progress,
+            total: self.total,
+            message,
+        }
+    }
+}
+
+/// Parses `Building [=====>   ] 12/103: foo, bar` (the `Building` label is missing in quiet mode)
+fn parse_progress_bar(line: &str) -> Option<(f64, f64, &str)> {
+    let line = line.strip_prefix("Building").unwrap_or(line).trim_start();
+    let rest = line.strip_prefix('[')?;
+    let (_, rest) = rest.split_once(']')?;
+    let (counter, units) = rest
+        .trim_start()
+        .split_once(':')
+        .unwrap_or((rest.trim(), ""));
+    let (done, total) = counter.split_once('/')?;
+    Some((done.parse().ok()?, total.parse().ok()?, units.trim()))
+}
+
+/// Removes the progress bars cargo redraws with `\r`, keeping what a terminal would show
+pub(crate) fn strip_progress_bars(output: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(output.len());
+    for line in output.split(|b| *b == b'\n') {
+        let visible = line.rsplit(|b| *b == b'\r').next().unwrap_or(line);
+        if visible.trim_ascii().is_empty() && line.contains(&b'\r') {
+            continue;
+        }
+        result.extend_from_slice(visible);
+        result.push(b'\n');
+    }
+    result.pop();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_progress() {
+        let mut parser = ProgressParser::default();
+        let lines = [
+            "   Compiling proc-macro2 v1.0.106",
+            "    Building [                           ] 0/103: proc-macro2(build.rs)       ",
+            "    Building [                           ] 1/103: proc-macro2(build)          ",
+            "   Compiling quote v1.0.46",
+            "    Building [                           ] 2/103: quote(build.rs)             ",
+            "[=>                          ] 3/103: quote                       ",
+        ];
+        let updates: Vec<_> = lines
+            .iter()
+            .filter_map(|line| parser.parse_line(line))
+            .collect();
+
+        assert_eq!(
+            updates,
+            [
+                ProgressUpdate {
+                    progress: 1.,
+                    total: None,
+                    message: "Compiling proc-macro2 v1.0.106".into()
+                },
+                ProgressUpdate {
+                    progress: 2.,
+                    total: Some(103.),
+                    message: "Compiling quote v1.0.46 (2/103)".into()
+                },
+                ProgressUpdate {
+                    progress: 3.,
+                    total: Some(103.),
+                    message: "Building 3/103: quote".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_test_progress() {
+        let mut parser = ProgressParser::default();
+        let lines = [
+            "running 2 tests",
+            "test tests::first ... ok",
+            "test tests::second ... FAILED",
+            "test result: FAILED. 1 passed; 1 failed; 0 ignored",
+            "running 1 test",
+            "test src/lib.rs - foo (line 5) ... ignored",
+        ];
+        let updates: Vec<_> = lines
+            .iter()
+            .filter_map(|line| parser.parse_line(line))
+            .map(|u| (u.progress, u.total))
+            .collect();
+
+        assert_eq!(updates, [(1., Some(2.)), (2., Some(2.)), (3., Some(3.))]);
+    }
+
+    #[test]
+    fn test_nextest_progress() {
+        let mut parser = ProgressParser::default();
+        let lines = [
+            "    Starting 2 tests across 1 binary",
+            "        PASS [   0.004s] tproj tests::passes",
+            "        FAIL [   0.005s] tproj tests::fails",
+            "     Summary [   0.010s] 2 tests run: 1 passed, 1 failed, 0 skipped",
+        ];
+        let updates: Vec<_> = lines
+            .iter()
+            .filter_map(|line| parser.parse_line(line))
+            .map(|u| (u.progress, u.total))
+            .collect();
+
+        assert_eq!(updates, [(1., Some(2.)), (2., Some(2.))]);
+    }
+
+    #[test]
+    fn test_json_test_progress() {
+        let mut parser = ProgressParser::default();
+        let lines = [
+            r#"{ "type": "suite", "event": "started", "test_count": 2 }"#,
+            r#"{ "type": "test", "event": "started", "name": "tests::first" }"#,
+            r#"{ "type": "test", "name": "tests::first", "event": "ok", "exec_time": 0.01 }"#,
+            r#"{ "type": "test", "name": "tests::second", "event": "failed", "stdout": "" }"#,
+        ];
+        let updates: Vec<_> = lines
+            .iter()
+            .filter_map(|line| parser.parse_line(line))
+            .collect();
+
+        assert_eq!(
+            updates,
+            [
+                ProgressUpdate {
+                    progress: 1.,
+                    total: Some(2.),
+                    message: "test tests::first ... ok".into()
+                },
+                ProgressUpdate {
+                    progress: 2.,
+                    total: Some(2.),
+                    message: "test tests::second ... failed".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strip_progress_bars() {
+        let stderr = b"   Compiling foo v0.1.0\n    Building [ ] 0/2: foo         \r   Compiling bar v0.1.0\n    Building [=> ] 1/2: bar      \r                    \rerror: oops";
+        assert_eq!(
+            String::from_utf8(strip_progress_bars(stderr)).unwrap(),
+            "   Compiling foo v0.1.0\n   Compiling bar v0.1.0\nerror: oops"
+        );
+    }
+}
@@ -0,0 +1,477 @@
+use std::path::Path;
+
+use rmcp::{
+    ErrorData,
+    model::{
+        GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole,
+    },
+};
+
+/// Prompts shipped with the server, they can be overridden by files with the same name
+const BUILTIN_PROMPTS: &[(&str, &str)] = &[
+    (
+        "add-dependency",
+        include_str!("../docs/prompts/add-dependency.md"),
+    ),
+    (
+        "fix-code-issues",
+        include_str!("../docs/prompts/fix-code-issues.md"),
+    ),
+    (
+        "load-metadata",
+        include_str!("../docs/prompts/load-metadata.md"),
+    ),
+    (
+        "verify-changes",
+        include_str!("../docs/prompts/verify-changes.md"),
+    ),
+    (
+        "verify-code",
+        include_str!("../docs/prompts/verify-code.md"),
+    ),
+];
+
+const REQUIRED_SUFFIX: &str = "(required)";
+
+/// Prompt templates exposed through `prompts/list` and `prompts/get`
+#[derive(Debug, Default)]
+pub(crate) struct PromptLibrary {
+    prompts: Vec<PromptTemplate>,
+}
+
+impl PromptLibrary {
+    /// Loads the built-in prompts and the `*.md` files of `dir`, files override built-in prompts with the same name
+    pub(crate) fn load(dir: Option<&Path>) -> Self {
+        let mut library = Self::default();
+        for (name, source) in BUILTIN_PROMPTS {
+            match PromptTemplate::parse(name, source) {
+                Ok(prompt) => library.insert(prompt),
+                Err(e) => tracing::error!("Invalid built-in prompt {name}: {e}"),
+            }
+        }
+
+        if let Some(dir) = dir {
+            library.load_dir(dir);
+        }
+        library
+    }
+
+    fn load_dir(&mut self, dir: &Path) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("Failed to read prompts directory {}: {e}", dir.display());
+                return;
+            }
+        };
+        let mut paths: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let prompt = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| PromptTemplate::parse(name, &source));
+            match prompt {
+                Ok(prompt) => {
+                    tracing::info!("Loaded prompt {name} from {}", path.display());
+                    self.insert(prompt);
+                }
+                Err(e) => tracing::warn!("Skipping prompt file {}: {e}", path.display()),
+            }
+        }
+    }
+
+    fn insert(&mut self, prompt: PromptTemplate) {
+        match self.prompts.binary_search_by(|p| p.name.cmp(&prompt.name)) {
+            Ok(index) => self.prompts[index] = prompt,
+            Err(index) => self.prompts.insert(index, prompt),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.prompts.len()
+    }
+
+    pub(crate) fn list(&self) -> Vec<Prompt> {
+        self.prompts.iter().map(PromptTemplate::to_prompt).collect()
+    }
+
+    pub(crate) fn get(
+        &self,
+        name: &str,
+        arguments: Option<&JsonObject>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let prompt = self
+            .prompts
+            .iter()
+            .find(|prompt| prompt.name == name)
+            .ok_or_else(|| ErrorData::invalid_params(format!("Prompt '{name}' not found"), None))?;
+
+        let text = prompt
+            .render(arguments)
+            .map_err(|e| ErrorData::invalid_params(e, None))?;
+        let mut result =
+            GetPromptResult::new(vec![PromptMessage::new_text(PromptMessageRole::User, text)]);
+        if let Some(description) = &prompt.description {
+            result = result.with_description(description);
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug)]
+struct PromptTemplate {
+    name: String,
+    title: Option<String>,
+    description: Option<String>,
+    arguments: Vec<TemplateArgument>,
+    body: Vec<Node>,
+}
+
+#[derive(Debug)]
+struct TemplateArgument {
+    name: String,
+    description: Option<String>,
+    required: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Text(String),
+    /// `{{name}}`
+    Value(String),
+    /// `{{#name}}...{{/name}}` rendered when the argument is set, `{{^name}}...{{/name}}` when it is not
+    Section {
+        name: String,
+        inverted: bool,
+        nodes: Vec<Node>,
+    },
+}
+
+impl PromptTemplate {
+    /// Parses a markdown prompt with an optional front matter:
+    ///
+    /// ```text
+    /// ---
+    /// title: Short title
+    /// description: What the prompt does
+    /// arguments:
+    ///   name: Argument description (required)
+    /// ---
+    /// Prompt text with {{name}} placeholders
+    /// ```
+    fn parse(name: &str, source: &str) -> Result<Self, String> {
+        let mut prompt = Self {
+            name: name.to_owned(),
+            title: None,
+            description: None,
+            arguments: Vec::new(),
+            body: Vec::new(),
+        };
+
+        let source = source.replace("\r\n", "\n");
+        let body = match source.strip_prefix("---\n") {
+            Some(rest) => {
+                let (front_matter, body) = rest
+                    .split_once("\n---\n")
+                    .or_else(|| rest.strip_suffix("\n---").map(|fm| (fm, "")))
+                    .ok_or("front matter is not closed with `---`")?;
+                prompt.parse_front_matter(front_matter)?;
+                body
+            }
+            None => source.as_str(),
+        };
+
+        let mut tokens = tokenize(body.trim())?.into_iter();
+        prompt.body = parse_nodes(&mut tokens, None)?;
+        prompt.check_placeholders(&prompt.body)?;
+        Ok(prompt)
+    }
+
+    fn parse_front_matter(&mut self, front_matter: &str) -> Result<(), String> {
+        let mut in_arguments = false;
+        for line in front_matter.lines() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("expected `key: value`, got `{line}`"))?;
+            let value = value.trim();
+
+            if in_arguments && line.starts_with([' ', '\t']) {
+                let (description, required) = match value.strip_suffix(REQUIRED_SUFFIX) {
+                    Some(description) => (description.trim_end(), true),
+                    None => (value, false),
+                };
+                self.arguments.push(TemplateArgument {
+                    name: key.trim().to_owned(),
+                    description: (!description.is_empty()).then(|| description.to_owned()),
+                    required,
+                });
+                continue;
+            }
+
+            in_arguments = false;
+            match key {
+                "title" => self.title = Some(value.to_owned()),
+                "description" => self.description = Some(value.to_owned()),
+                "arguments" if value.is_empty() => in_arguments = true,
+                _ => return Err(format!("unexpected front matter key `{key}`")),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_placeholders(&self, nodes: &[Node]) -> Result<(), String> {
+        for node in nodes {
+            let name = match node {
+                Node::Text(_) => continue,
+                Node::Value(name) => name,
+                Node::Section { name, nodes, .. } => {
+                    self.check_placeholders(nodes)?;
+                    name
+                }
+            };
+            if !self.arguments.iter().any(|arg| &arg.name == name) {
+                return Err(format!("placeholder `{name}` is not a declared argument"));
+            }
+        }
+        Ok(())
+    }
+
+    fn to_prompt(&self) -> Prompt {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|arg| {
+                let argument = PromptArgument::new(&arg.name).with_required(arg.required);
+                match &arg.description {
+                    Some(description) => argument.with_description(description),
+                    None => argument,
+                }
+            })
+            .collect::<Vec<_>>();
+        let prompt = Prompt::new(
+            &self.name,
+            self.description.as_deref(),
+            (!arguments.is_empty()).then_some(arguments),
+        );
+        match &self.title {
+            Some(title) => prompt.with_title(title),
+            None => prompt,
+        }
+    }
+
+    fn render(&self, arguments: Option<&JsonObject>) -> Result<String, String> {
+        let value = |name: &str| {
+            let value = arguments?.get(name)?;
+            let value = match value {
+                serde_json::Value::Null => return None,
+                serde_json::Value::String(s) => s.trim().to_owned(),
+                other => other.to_string(),
+            };
+            (!value.is_empty()).then_some(value)
+        };
+
+        if let Some(arg) = self
+            .arguments
+            .iter()
+            .find(|arg| arg.required && value(&arg.name).is_none())
+        {
+            return Err(format!(
+                "Missing required argument '{}' for prompt '{}'",
+                arg.name, self.name
+            ));
+        }
+
+        let mut output = String::new();
+        render_nodes(&self.body, &value, &mut output);
+        Ok(output)
+    }
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Text(&'a str),
+    Value(&'a str),
+    Open { name: &'a str, inverted: bool },
+    Close(&'a str),
+}
+
+fn tokenize(mut body: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    while let Some(start) = body.find("{{") {
+        let end = body[start..]
+            .find("}}")
+            .map(|end| start + end)
+            .ok_or("placeholder is not closed with `}}`")?;
+        let tag = body[start + 2..end].trim();
+        let mut rest = &body[end + 2..];
+
+        let (token, is_section) = match tag.chars().next() {
+            Some('#') => (
+                Token::Open {
+                    name: tag[1..].trim(),
+                    inverted: false,
+                },
+                true,
+            ),
+            Some('^') => (
+                Token::Open {
+                    name: tag[1..].trim(),
+                    inverted: true,
+                },
+                true,
+            ),
+            Some('/') => (Token::Close(tag[1..].trim()), true),
+            _ => (Token::Value(tag), false),
+        };
+
+        // A section tag on its own line does not leave an empty line behind
+        let text = &body[..start];
+        if is_section && (text.is_empty() || text.ends_with('\n')) && rest.starts_with('\n') {
+            rest = &rest[1..];
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(text));
+        }
+        tokens.push(token);
+        body = rest;
+    }
+    if !body.is_empty() {
+        tokens.push(Token::Text(body));
+    }
+    Ok(tokens)
+}
+
+fn parse_nodes<'a>(
+    tokens: &mut impl Iterator<Item = Token<'a>>,
+    section: Option<&str>,
+) -> Result<Vec<Node>, String> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.to_owned())),
+            Token::Value(name) => nodes.push(Node::Value(name.to_owned())),
+            Token::Open { name, inverted } => nodes.push(Node::Section {
+                name: name.to_owned(),
+                inverted,
+                nodes: parse_nodes(tokens, Some(name))?,
+            }),
+            Token::Close(name) if section == Some(name) => return Ok(nodes),
+            Token::Close(name) => return Err(format!("unexpected `{{{{/{name}}}}}`")),
+        }
+    }
+    match section {
+        Some(name) => Err(format!("section `{name}` is not closed")),
+        None => Ok(nodes),
+    }
+}
+
+fn render_nodes(nodes: &[Node], value: &impl Fn(&str) -> Option<String>, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Value(name) => output.push_str(&value(name).unwrap_or_default()),
+            Node::Section {
+                name,
+                inverted,
+                nodes,
+            } => {
+                if value(name).is_some() != *inverted {
+                    render_nodes(nodes, value, output);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "---
+title: Add a dependency
+description: Adds a crate
+arguments:
+  crate: Crate to add (required)
+  package: Target package
+---
+Add `{{crate}}`{{#package}} to `{{package}}`{{/package}}.
+{{^package}}
+Find the package first.
+{{/package}}
+Build it.
+";
+
+    fn args(value: serde_json::Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_parse_front_matter() {
+        let prompt = PromptTemplate::parse("add", SOURCE).unwrap();
+        assert_eq!(prompt.title.as_deref(), Some("Add a dependency"));
+        assert_eq!(prompt.description.as_deref(), Some("Adds a crate"));
+        let arguments: Vec<_> = prompt
+            .arguments
+            .iter()
+            .map(|arg| (arg.name.as_str(), arg.description.as_deref(), arg.required))
+            .collect();
+        assert_eq!(
+            arguments,
+            [
+                ("crate", Some("Crate to add"), true),
+                ("package", Some("Target package"), false),
+            ]
+        );
+
+        assert!(PromptTemplate::parse("bad", "Use {{unknown}}").is_err());
+        assert!(PromptTemplate::parse("bad", "---\ntitle: x\nBody").is_err());
+        assert!(PromptTemplate::parse("plain", "No front matter").is_ok());
+    }
+
+    #[test]
+    fn test_render() {
+        let prompt = PromptTemplate::parse("add", SOURCE).unwrap();
+        assert_eq!(
+            prompt
+                .render(Some(&args(serde_json::json!({"crate": "serde"}))))
+                .unwrap(),
+            "Add `serde`.\nFind the package first.\nBuild it."
+        );
+        assert_eq!(
+            prompt
+                .render(Some(&args(
+                    serde_json::json!({"crate": "serde", "package": "app"})
+                )))
+                .unwrap(),
+            "Add `serde` to `app`.\nBuild it."
+        );
+        assert!(prompt.render(None).is_err());
+    }
+
+    #[test]
+    fn test_builtin_prompts() {
+        let library = PromptLibrary::load(None);
+        assert_eq!(library.len(), BUILTIN_PROMPTS.len());
+        assert!(
+            library
+                .get(
+                    "verify-changes",
+                    Some(&args(serde_json::json!({"package": "app"})))
+                )
+                .is_ok()
+        );
+        assert!(library.get("add-dependency", None).is_err());
+    }
+}
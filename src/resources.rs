@@ -0,0 +1,420 @@
+use std::path::{Component, Path, PathBuf};
+
+use rmcp::{
+    ErrorData,
+    model::{
+        AnnotateAble, RawResource, RawResourceTemplate, ReadResourceResult, Resource,
+        ResourceContents, ResourceTemplate,
+    },
+};
+
+use crate::{
+    ToolContext, audit,
+    tools::{
+        cargo::CargoMetadata,
+        invocations::{DEFAULT_LIMIT, InvocationList},
+    },
+    workspace::{file_uri_to_path, path_to_file_uri},
+};
+
+/// Kinds of workspace files published as resources, listed in the generated documentation
+pub(crate) const RESOURCE_KINDS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Manifest of every workspace member"),
+    ("Cargo.lock", "Lockfile of the workspace"),
+    (
+        "rust-toolchain.toml",
+        "Toolchain the workspace is pinned to, if present",
+    ),
+    ("deny.toml", "cargo-deny configuration, if present"),
+    (
+        "target/doc/<crate>/index.html",
+        "Documentation index page of every workspace crate, once generated by cargo-doc",
+    ),
+    (
+        RECENT_INVOCATIONS_URI,
+        "The last tool calls recorded in the audit log, as JSON",
+    ),
+    (
+        CRATE_SRC_TEMPLATE,
+        "Source files of any package in the dependency graph, a path ending with `/` lists the files",
+    ),
+];
+
+const TOML: &str = "application/toml";
+const JSON: &str = "application/json";
+
+const RECENT_INVOCATIONS_URI: &str = "invocations://recent";
+
+const CRATE_SRC_SCHEME: &str = "crate-src://";
+const CRATE_SRC_TEMPLATE: &str = "crate-src://{name}/{version}/{path}";
+
+/// Directory listings are cut off after this many files
+const MAX_LISTED_FILES: usize = 2000;
+
+/// A workspace file that can be read as a resource
+#[derive(Debug)]
+struct WorkspaceFile {
+    path: PathBuf,
+    name: String,
+    description: String,
+    mime_type: &'static str,
+}
+
+impl WorkspaceFile {
+    fn into_resource(self) -> Resource {
+        let mut resource = RawResource::new(path_to_file_uri(&self.path), self.name);
+        resource.description = Some(self.description);
+        resource.mime_type = Some(self.mime_type.to_owned());
+        resource.size = std::fs::metadata(&self.path)
+            .ok()
+            .and_then(|m| u32::try_from(m.len()).ok());
+        resource.no_annotation()
+    }
+}
+
+/// Lists the files of the workspace members reported by `cargo metadata --no-deps`
+fn workspace_files(metadata: &CargoMetadata) -> Vec<WorkspaceFile> {
+    let mut files = Vec::new();
+    for package in &metadata.packages {
+        files.push(WorkspaceFile {
+            path: PathBuf::from(&package.manifest_path),
+            name: format!("{}/Cargo.toml", package.name),
+            description: format!("Manifest of the `{}` package", package.name),
+            mime_type: TOML,
+        });
+    }
+
+    let root = &metadata.workspace_root;
+    let root_files = [
+        ("Cargo.lock", "Lockfile of the workspace", TOML),
+        (
+            "rust-toolchain.toml",
+            "Toolchain the workspace is pinned to",
+            TOML,
+        ),
+        (
+            "rust-toolchain",
+            "Toolchain the workspace is pinned to",
+            "text/plain",
+        ),
+        ("deny.toml", "cargo-deny configuration", TOML),
+    ];
+    for (name, description, mime_type) in root_files {
+        let path = root.join(name);
+        if path.is_file() {
+            files.push(WorkspaceFile {
+                path,
+                name: name.to_owned(),
+                description: description.to_owned(),
+                mime_type,
+            });
+        }
+    }
+
+    let doc_dir = metadata.target_directory.join("doc");
+    for package in &metadata.packages {
+        for target in &package.targets {
+            if !target
+                .kind
+                .iter()
+                .any(|kind| matches!(kind.as_str(), "lib" | "rlib" | "proc-macro" | "bin"))
+            {
+                continue;
+            }
+            let crate_name = target.name.replace('-', "_");
+            let path = doc_dir.join(&crate_name).join("index.html");
+            if path.is_file() && !files.iter().any(|file| file.path == path) {
+                files.push(WorkspaceFile {
+                    path,
+                    name: format!("{crate_name} docs"),
+                    description: format!(
+                        "Documentation index page of the `{crate_name}` crate generated by cargo doc"
+                    ),
+                    mime_type: "text/html",
+                });
+            }
+        }
+    }
+    files
+}
+
+pub(crate) async fn list_resources(ctx: &ToolContext) -> Result<Vec<Resource>, ErrorData> {
+    let metadata = CargoMetadata::load(ctx).await?;
+    let mut recent_invocations = RawResource::new(RECENT_INVOCATIONS_URI, "recent-invocations");
+    recent_invocations.title = Some("Recent tool calls".to_owned());
+    recent_invocations.description = Some(format!(
+        "The last {DEFAULT_LIMIT} tool calls recorded in the audit log, most recent first"
+    ));
+    recent_invocations.mime_type = Some(JSON.to_owned());
+
+    Ok(std::iter::once(recent_invocations.no_annotation())
+        .chain(
+            workspace_files(&metadata)
+                .into_iter()
+                .map(WorkspaceFile::into_resource),
+        )
+        .collect())
+}
+
+pub(crate) fn resource_templates() -> Vec<ResourceTemplate> {
+    vec![
+        RawResourceTemplate::new(CRATE_SRC_TEMPLATE, "crate-src")
+            .with_title("Dependency source code")
+            .with_description(
+                "Source file of a package in the dependency graph as resolved by cargo metadata, \
+                read from the unpacked registry sources, git checkouts or vendored sources. \
+                An empty path or a path ending with `/` lists the files of the package or directory.",
+            )
+            .no_annotation(),
+    ]
+}
+
+/// Reads a resource, only workspace files that are currently listed and dependency sources can be read
+pub(crate) async fn read_resource(
+    uri: &str,
+    ctx: &ToolContext,
+) -> Result<ReadResourceResult, ErrorData> {
+    if let Some(location) = uri.strip_prefix(CRATE_SRC_SCHEME) {
+        return read_crate_source(uri, location, ctx).await;
+    }
+    if uri == RECENT_INVOCATIONS_URI {
+        let list = InvocationList {
+            invocations: audit::recent(DEFAULT_LIMIT, |_| true),
+        };
+        let text = serde_json::to_string_pretty(&list).map_err(|e| {
+            ErrorData::internal_error(format!("Failed to serialize invocations: {e}"), None)
+        })?;
+        return Ok(ReadResourceResult::new(vec![
+            ResourceContents::text(text, uri).with_mime_type(JSON),
+        ]));
+    }
+
+    let not_found = || ErrorData::resource_not_found(format!("Resource not found: {uri}"), None);
+    let path = file_uri_to_path(uri).ok_or_else(not_found)?;
+    let metadata = CargoMetadata::load(ctx).await?;
+    let file = workspace_files(&metadata)
+        .into_iter()
+        .find(|file| same_file(&file.path, &path))
+        .ok_or_else(not_found)?;
+
+    let text = std::fs::read_to_string(&file.path).map_err(|e| {
+        ErrorData::internal_error(format!("Failed to read {}: {e}", file.path.display()), None)
+    })?;
+    Ok(ReadResourceResult::new(vec![
+        ResourceContents::text(text, uri).with_mime_type(file.mime_type),
+    ]))
+}
+
+async fn read_crate_source(
+    uri: &str,
+    location: &str,
+    ctx: &ToolContext,
+) -> Result<ReadResourceResult, ErrorData> {
+    let (name, version, path) = parse_crate_src(location).ok_or_else(|| {
+        ErrorData::invalid_params(
+            format!("Invalid URI {uri}, expected {CRATE_SRC_TEMPLATE}"),
+            None,
+        )
+    })?;
+
+    let metadata = CargoMetadata::load_with_dependencies(ctx).await?;
+    let package = metadata
+        .packages
+        .iter()
+        .find(|package| package.name == name && package.version == version)
+        .ok_or_else(|| {
+            let versions: Vec<_> = metadata
+                .packages
+                .iter()
+                .filter(|package| package.name == name)
+                .map(|package| package.version.as_str())
+                .collect();
+            let message = if versions.is_empty() {
+                format!("Package `{name}` is not in the dependency graph")
+            } else {
+                format!(
+                    "Package `{name}` {version} is not in the dependency graph, available versions: {}",
+                    versions.join(", ")
+                )
+            };
+            ErrorData::resource_not_found(message, None)
+        })?;
+    let root = Path::new(&package.manifest_path).parent().ok_or_else(|| {
+        ErrorData::internal_error(
+            format!("Unexpected manifest path: {}", package.manifest_path),
+            None,
+        )
+    })?;
+
+    let full_path = resolve_source_path(root, &path)
+        .ok_or_else(|| ErrorData::resource_not_found(format!("Resource not found: {uri}"), None))?;
+    let text = if full_path.is_dir() {
+        let mut files = Vec::new();
+        list_files(&full_path, &mut files);
+        files.sort();
+        let base = format!("{CRATE_SRC_SCHEME}{name}/{version}/");
+        let mut listing: Vec<_> = files
+            .iter()
+            .take(MAX_LISTED_FILES)
+            .filter_map(|file| file.strip_prefix(root).ok())
+            .map(|file| format!("{base}{}", file.to_string_lossy().replace('\\', "/")))
+            .collect();
+        if files.len() > MAX_LISTED_FILES {
+            listing.push(format!(
+                "... {} more file(s), list a subdirectory to see them",
+                files.len() - MAX_LISTED_FILES
+            ));
+        }
+        listing.join("\n")
+    } else {
+        std::fs::read_to_string(&full_path).map_err(|e| {
+            ErrorData::internal_error(format!("Failed to read {}: {e}", full_path.display()), None)
+        })?
+    };
+
+    Ok(ReadResourceResult::new(vec![
+        ResourceContents::text(text, uri).with_mime_type("text/plain"),
+    ]))
+}
+
+/// Splits `{name}/{version}/{path}`, the path may be empty
+fn parse_crate_src(location: &str) -> Option<(&str, &str, String)> {
+    let mut parts = location.splitn(3, '/');
+    let name = parts.next().filter(|name| !name.is_empty())?;
+    let version = parts.next().filter(|version| !version.is_empty())?;
+    let path = percent_encoding::percent_decode_str(parts.next().unwrap_or_default())
+        .decode_utf8()
+        .ok()?;
+    Some((name, version, path.into_owned()))
+}
+
+/// Joins a relative path to the package root, rejecting paths that leave it
+fn resolve_source_path(root: &Path, path: &str) -> Option<PathBuf> {
+    if !Path::new(path)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    let full_path = root.join(path).canonicalize().ok()?;
+    full_path
+        .starts_with(root.canonicalize().ok()?)
+        .then_some(full_path)
+}
+
+/// Collects the files below `dir`, skipping hidden entries and build output.
+/// Symlinks to files are listed, symlinks to directories are not followed so that loops end.
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') || name == "target" {
+            continue;
+        }
+        // `file_type` does not follow symlinks
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            list_files(&path, files);
+        } else if !file_type.is_symlink() || path.is_file() {
+            files.push(path);
+        }
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (a.canonicalize(), b.canonicalize()),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_files() {
+        let root = std::env::temp_dir().join("rust-mcp-server-resources-test");
+        let doc = root.join("target/doc/my_crate");
+        std::fs::create_dir_all(&doc).unwrap();
+        std::fs::write(root.join("Cargo.lock"), "").unwrap();
+        std::fs::write(doc.join("index.html"), "").unwrap();
+
+        let metadata: CargoMetadata = serde_json::from_value(serde_json::json!({
+            "packages": [{
+                "name": "my-crate",
+                "version": "0.1.0",
+                "manifest_path": root.join("Cargo.toml"),
+                "targets": [
+                    { "name": "my-crate", "kind": ["lib"] },
+                    { "name": "my-crate", "kind": ["bin"] },
+                    { "name": "integration", "kind": ["test"] },
+                ],
+            }],
+            "workspace_root": root,
+            "target_directory": root.join("target"),
+        }))
+        .unwrap();
+
+        let files: Vec<_> = workspace_files(&metadata)
+            .into_iter()
+            .map(|file| (file.name, file.path))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("my-crate/Cargo.toml".to_owned(), root.join("Cargo.toml")),
+                ("Cargo.lock".to_owned(), root.join("Cargo.lock")),
+                ("my_crate docs".to_owned(), doc.join("index.html")),
+            ]
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_crate_src() {
+        assert_eq!(
+            parse_crate_src("serde/1.0.219/src/de/mod.rs"),
+            Some(("serde", "1.0.219", "src/de/mod.rs".to_owned()))
+        );
+        assert_eq!(
+            parse_crate_src("serde/1.0.219"),
+            Some(("serde", "1.0.219", String::new()))
+        );
+        assert_eq!(parse_crate_src("serde"), None);
+    }
+
+    #[test]
+    fn test_resolve_source_path() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert!(resolve_source_path(root, "src/main.rs").is_some());
+        assert!(resolve_source_path(root, "").is_some());
+        assert!(resolve_source_path(root, "../other/Cargo.toml").is_none());
+        assert!(resolve_source_path(root, "/etc/passwd").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_files_skips_symlinked_dirs() {
+        let root = std::env::temp_dir().join("rust-mcp-server-list-files-test");
+        _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::os::unix::fs::symlink(".", root.join("src/loop")).unwrap();
+        std::os::unix::fs::symlink("lib.rs", root.join("src/alias.rs")).unwrap();
+
+        let mut files = Vec::new();
+        list_files(&root, &mut files);
+        files.sort();
+        assert_eq!(files, [root.join("src/alias.rs"), root.join("src/lib.rs")]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
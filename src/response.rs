@@ -3,12 +3,26 @@ use rmcp::model::Annotated;
 use crate::command::{AgentRecommendation, Output};
 
 pub(crate) struct Response {
-    output: Output,
+    /// Output of the command run by the tool, `None` for tools that answer without running one
+    output: Option<Output>,
+    output_id: Option<String>,
     additional_content: Vec<Annotated<rmcp::model::RawContent>>,
     recommendations: Vec<AgentRecommendation>,
+    structured_content: Option<serde_json::Value>,
 }
 
 impl Response {
+    /// Response of a tool that does not run a command, its content is added afterwards
+    pub(crate) fn without_output() -> Self {
+        Response {
+            output: None,
+            output_id: None,
+            additional_content: Vec::new(),
+            recommendations: Vec::new(),
+            structured_content: None,
+        }
+    }
+
     pub(crate) fn add_content(&mut self, content: Annotated<rmcp::model::RawContent>) {
         self.additional_content.push(content);
     }
@@ -18,12 +32,40 @@ impl Response {
             .push(AgentRecommendation(recommendation.into()));
     }
 
+    /// Sets the `structuredContent` of the result, it must match the tool's output schema
+    pub(crate) fn set_structured_content(&mut self, content: &impl serde::Serialize) {
+        match serde_json::to_value(content) {
+            Ok(value) => self.structured_content = Some(value),
+            Err(e) => tracing::error!("Failed to serialize structured content: {e}"),
+        }
+    }
+
+    pub(crate) fn output(&self) -> Option<&Output> {
+        self.output.as_ref()
+    }
+
+    /// ID of the stored command output, set by [`Response::keep_output`]
+    pub(crate) fn output_id(&self) -> Option<&str> {
+        self.output_id.as_deref()
+    }
+
+    /// Stores the command output for `get-command-output` and limits the size of each of its streams
+    pub(crate) fn keep_output(&mut self, max_bytes: Option<usize>) {
+        if let Some(output) = &mut self.output {
+            self.output_id = Some(output.keep(max_bytes));
+        }
+    }
+
     pub(crate) fn into_rmcp_result(
         self,
         ignore_recommendations: bool,
     ) -> rmcp::model::CallToolResult {
-        let mut result: rmcp::model::CallToolResult = self.output.into();
+        let mut result = match self.output {
+            Some(output) => output.into(),
+            None => rmcp::model::CallToolResult::success(Vec::new()),
+        };
         result.content.extend(self.additional_content);
+        result.structured_content = self.structured_content;
         if !ignore_recommendations {
             result
                 .content
@@ -36,9 +78,8 @@ impl Response {
 impl From<Output> for Response {
     fn from(val: Output) -> Self {
         Response {
-            output: val,
-            additional_content: Vec::new(),
-            recommendations: Vec::new(),
+            output: Some(val),
+            ..Response::without_output()
         }
     }
 }
@@ -58,7 +99,7 @@ mod tests {
             stdout: Some(Stdout("This is a test output".into())),
             stderr: None,
             cmd_line: CommandLine("test_command --option".into()),
-            exit_status: ExitStatus(std::process::ExitStatus::default()),
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
         };
         let response: Response = output.into();
         let rmcp_result = response.into_rmcp_result(false);
@@ -82,7 +123,7 @@ mod tests {
             stdout: Some(Stdout("This is a test output".into())),
             stderr: None,
             cmd_line: CommandLine("test_command --option".into()),
-            exit_status: ExitStatus(std::process::ExitStatus::default()),
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
         };
         let mut response: Response = output.into();
         response
@@ -125,7 +166,7 @@ mod tests {
             stdout: Some(Stdout("This is a test output".into())),
             stderr: None,
             cmd_line: CommandLine("test_command --option".into()),
-            exit_status: ExitStatus(std::process::ExitStatus::default()),
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
         };
         let mut response: Response = output.into();
         response.add_recommendation("Consider checking the logs.");
@@ -143,4 +184,28 @@ mod tests {
         assert_eq!(stdout.as_text().unwrap().text, "This is a test output");
         assert_eq!(exit_status.as_text().unwrap().text, "✅ test_tool: Success");
     }
+
+    #[test]
+    fn timed_out() {
+        let output = Output {
+            tool_name: "test_tool".into(),
+            stdout: Some(Stdout("partial output".into())),
+            stderr: None,
+            cmd_line: CommandLine("test_command --option".into()),
+            exit_status: ExitStatus::TimedOut(std::time::Duration::from_secs(30)),
+        };
+        let response: Response = output.into();
+        let rmcp_result = response.into_rmcp_result(false);
+
+        let [_, stdout, exit_status] = &rmcp_result.content[..] else {
+            panic!("expected 3 content items: {rmcp_result:?}");
+        };
+
+        assert_eq!(stdout.as_text().unwrap().text, "partial output");
+        assert_eq!(
+            exit_status.as_text().unwrap().text,
+            "⏱️ test_tool: Timed out after 30s, the process was killed. The output above is partial"
+        );
+        assert_eq!(rmcp_result.is_error, Some(true));
+    }
 }
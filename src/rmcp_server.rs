@@ -1,19 +1,30 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use rmcp::{
     ErrorData,
-    model::{ListToolsResult, PaginatedRequestParams, ServerInfo},
+    model::{
+        GetPromptRequestParams, GetPromptResult, ListPromptsResult, ListResourceTemplatesResult,
+        ListResourcesResult, ListToolsResult, PaginatedRequestParams, ReadResourceRequestParams,
+        ReadResourceResult, ServerInfo,
+    },
     service::{NotificationContext, RequestContext},
 };
 
 use crate::{
-    Tool,
+    Tool, ToolContext, ToolHints,
+    config::{self, Config},
+    confirmation::{Confirmation, ConfirmationPolicy},
+    globals,
+    progress::ProgressReporter,
+    prompts::PromptLibrary,
+    resources::{self, RESOURCE_KINDS},
     tool::DynTool,
     tools::{
         cargo::{
-            CargoAddRmcpTool, CargoBuildRmcpTool, CargoCheckRmcpTool, CargoCleanRmcpTool,
-            CargoClippyRmcpTool, CargoDocRmcpTool, CargoFmtRmcpTool, CargoGenerateLockfileRmcpTool,
-            CargoInfoRmcpTool, CargoListRmcpTool, CargoMetadataRmcpTool, CargoNewRmcpTool,
+            CargoAddRmcpTool, CargoApplySuggestionsRmcpTool, CargoBuildRmcpTool,
+            CargoCheckRmcpTool, CargoCleanRmcpTool, CargoClippyRmcpTool, CargoDocQueryRmcpTool,
+            CargoDocRmcpTool, CargoFmtRmcpTool, CargoGenerateLockfileRmcpTool, CargoInfoRmcpTool,
+            CargoListRmcpTool, CargoMetadataRmcpTool, CargoNewRmcpTool, CargoOutdatedRmcpTool,
             CargoPackageRmcpTool, CargoRemoveRmcpTool, CargoSearchRmcpTool, CargoTestRmcpTool,
             CargoTreeRmcpTool, CargoUpdateRmcpTool, CargoWorkspaceInfoRmcpTool,
         },
@@ -24,34 +35,60 @@ use crate::{
         cargo_expand::CargoExpandRmcpTool,
         cargo_hack::{CargoHackInstallRmcpTool, CargoHackRmcpTool},
         cargo_insta::CargoInstaUpdateSnapshotsRmcpTool,
+        cargo_llvm_cov::{CargoLlvmCovInstallRmcpTool, CargoLlvmCovRmcpTool},
         cargo_machete::{CargoMacheteInstallRmcpTool, CargoMacheteRmcpTool},
+        cargo_nextest::{
+            CargoNextestInstallRmcpTool, CargoNextestListRmcpTool, CargoNextestRunRmcpTool,
+        },
+        cargo_semver_checks::{CargoSemverChecksInstallRmcpTool, CargoSemverChecksRmcpTool},
+        command_output::GetCommandOutputRmcpTool,
+        invocations::ListRecentInvocationsRmcpTool,
+        rust_analyzer::{
+            DocumentSymbolsRmcpTool, FindDefinitionRmcpTool, FindReferencesRmcpTool, HoverRmcpTool,
+            WorkspaceSymbolRmcpTool,
+        },
         rustc::RustcExplainRmcpTool,
         rustup::{RustupShowRmcpTool, RustupToolchainAddRmcpTool, RustupUpdateRmcpTool},
+        std_doc::StdDocRmcpTool,
+        workspaces::WorkspaceListRmcpTool,
     },
     version::AppVersion,
+    workspace::SessionWorkspaces,
 };
 
+#[derive(Clone)]
 pub struct Server {
     ignore_recommendations: bool,
-    detect_workspace: bool,
-    tools: HashMap<&'static str, Box<dyn DynTool + Send + Sync>>,
+    /// Workspace given on the command line, client roots are not queried if set
+    fixed_workspace: Option<PathBuf>,
+    workspaces: SessionWorkspaces,
+    tools: Arc<HashMap<&'static str, Box<dyn DynTool + Send + Sync>>>,
+    prompts: Arc<PromptLibrary>,
+    tool_defaults: Arc<HashMap<&'static str, rmcp::model::JsonObject>>,
+    confirmation_policies: Arc<HashMap<&'static str, ConfirmationPolicy>>,
 }
 
 impl Server {
     pub fn new(
-        disabled_tools: &[String],
+        config: &Config,
         ignore_recommendations: bool,
-        detect_workspace: bool,
+        fixed_workspace: Option<PathBuf>,
+        prompts: PromptLibrary,
     ) -> Self {
         let mut tools: HashMap<&'static str, Box<dyn DynTool + Send + Sync>> = HashMap::new();
 
         // Cargo tools
         tools.insert(CargoAddRmcpTool::NAME, Box::new(CargoAddRmcpTool));
+        tools.insert(
+            CargoApplySuggestionsRmcpTool::NAME,
+            Box::new(CargoApplySuggestionsRmcpTool),
+        );
         tools.insert(CargoBuildRmcpTool::NAME, Box::new(CargoBuildRmcpTool));
         tools.insert(CargoCheckRmcpTool::NAME, Box::new(CargoCheckRmcpTool));
         tools.insert(CargoCleanRmcpTool::NAME, Box::new(CargoCleanRmcpTool));
         tools.insert(CargoClippyRmcpTool::NAME, Box::new(CargoClippyRmcpTool));
         tools.insert(CargoDocRmcpTool::NAME, Box::new(CargoDocRmcpTool));
+        tools.insert(CargoDocQueryRmcpTool::NAME, Box::new(CargoDocQueryRmcpTool));
         tools.insert(CargoExpandRmcpTool::NAME, Box::new(CargoExpandRmcpTool));
         tools.insert(CargoFmtRmcpTool::NAME, Box::new(CargoFmtRmcpTool));
         tools.insert(
@@ -62,6 +99,7 @@ impl Server {
         tools.insert(CargoListRmcpTool::NAME, Box::new(CargoListRmcpTool));
         tools.insert(CargoMetadataRmcpTool::NAME, Box::new(CargoMetadataRmcpTool));
         tools.insert(CargoNewRmcpTool::NAME, Box::new(CargoNewRmcpTool));
+        tools.insert(CargoOutdatedRmcpTool::NAME, Box::new(CargoOutdatedRmcpTool));
         tools.insert(CargoPackageRmcpTool::NAME, Box::new(CargoPackageRmcpTool));
         tools.insert(CargoRemoveRmcpTool::NAME, Box::new(CargoRemoveRmcpTool));
         tools.insert(CargoSearchRmcpTool::NAME, Box::new(CargoSearchRmcpTool));
@@ -98,6 +136,13 @@ impl Server {
             Box::new(CargoInstaUpdateSnapshotsRmcpTool),
         );
 
+        // Cargo-llvm-cov tools
+        tools.insert(CargoLlvmCovRmcpTool::NAME, Box::new(CargoLlvmCovRmcpTool));
+        tools.insert(
+            CargoLlvmCovInstallRmcpTool::NAME,
+            Box::new(CargoLlvmCovInstallRmcpTool),
+        );
+
         // Cargo-machete tools
         tools.insert(CargoMacheteRmcpTool::NAME, Box::new(CargoMacheteRmcpTool));
         tools.insert(
@@ -105,6 +150,49 @@ impl Server {
             Box::new(CargoMacheteInstallRmcpTool),
         );
 
+        // Cargo-nextest tools
+        tools.insert(
+            CargoNextestRunRmcpTool::NAME,
+            Box::new(CargoNextestRunRmcpTool),
+        );
+        tools.insert(
+            CargoNextestListRmcpTool::NAME,
+            Box::new(CargoNextestListRmcpTool),
+        );
+        tools.insert(
+            CargoNextestInstallRmcpTool::NAME,
+            Box::new(CargoNextestInstallRmcpTool),
+        );
+
+        // Cargo-semver-checks tools
+        tools.insert(
+            CargoSemverChecksRmcpTool::NAME,
+            Box::new(CargoSemverChecksRmcpTool),
+        );
+        tools.insert(
+            CargoSemverChecksInstallRmcpTool::NAME,
+            Box::new(CargoSemverChecksInstallRmcpTool),
+        );
+
+        // Rust-analyzer tools
+        tools.insert(
+            DocumentSymbolsRmcpTool::NAME,
+            Box::new(DocumentSymbolsRmcpTool),
+        );
+        tools.insert(
+            FindDefinitionRmcpTool::NAME,
+            Box::new(FindDefinitionRmcpTool),
+        );
+        tools.insert(
+            FindReferencesRmcpTool::NAME,
+            Box::new(FindReferencesRmcpTool),
+        );
+        tools.insert(HoverRmcpTool::NAME, Box::new(HoverRmcpTool));
+        tools.insert(
+            WorkspaceSymbolRmcpTool::NAME,
+            Box::new(WorkspaceSymbolRmcpTool),
+        );
+
         // Rustc tools
         tools.insert(RustcExplainRmcpTool::NAME, Box::new(RustcExplainRmcpTool));
 
@@ -116,6 +204,43 @@ impl Server {
         );
         tools.insert(RustupUpdateRmcpTool::NAME, Box::new(RustupUpdateRmcpTool));
 
+        // Standard library documentation
+        tools.insert(StdDocRmcpTool::NAME, Box::new(StdDocRmcpTool));
+
+        // Workspace tools
+        tools.insert(WorkspaceListRmcpTool::NAME, Box::new(WorkspaceListRmcpTool));
+
+        // Command output and history tools
+        tools.insert(
+            GetCommandOutputRmcpTool::NAME,
+            Box::new(GetCommandOutputRmcpTool),
+        );
+        tools.insert(
+            ListRecentInvocationsRmcpTool::NAME,
+            Box::new(ListRecentInvocationsRmcpTool),
+        );
+
+        for tool_name in config.tools.keys() {
+            if !tools.contains_key(tool_name.as_str()) {
+                tracing::warn!("Config sets defaults of unknown tool: {}", tool_name);
+            }
+        }
+
+        if let Some(enabled_tools) = &config.enabled_tools {
+            tracing::info!("Enabled tools: {}", enabled_tools.join(", "));
+            for tool_name in enabled_tools {
+                if !tools.contains_key(tool_name.as_str()) {
+                    tracing::warn!("Tool not found: {}", tool_name);
+                }
+            }
+            tools.retain(|name, _| enabled_tools.iter().any(|enabled| enabled == name));
+        }
+
+        if globals::is_read_only() {
+            tools.retain(|_, tool| tool.hints().available_read_only());
+        }
+
+        let disabled_tools = &config.disabled_tools;
         if !disabled_tools.is_empty() {
             tracing::info!("Disabled tools: {}", disabled_tools.join(", "));
             for tool_name in disabled_tools {
@@ -125,10 +250,40 @@ impl Server {
             }
         }
 
+        let tool_defaults = tools
+            .iter()
+            .map(|(name, tool)| (*name, config.tool_defaults(name, &tool.json_schema())))
+            .filter(|(_, defaults)| !defaults.is_empty())
+            .collect();
+        for tool_name in config.tool_confirmation.keys() {
+            if !tools.contains_key(tool_name.as_str()) {
+                tracing::warn!(
+                    "Config sets confirmation policy of unknown tool: {}",
+                    tool_name
+                );
+            }
+        }
+        let confirmation_policies = tools
+            .keys()
+            .map(|name| (*name, config.confirmation_policy(name)))
+            .collect();
+
         Self {
             ignore_recommendations,
-            detect_workspace,
-            tools,
+            workspaces: SessionWorkspaces::new(fixed_workspace.clone()),
+            fixed_workspace,
+            tools: Arc::new(tools),
+            prompts: Arc::new(prompts),
+            tool_defaults: Arc::new(tool_defaults),
+            confirmation_policies: Arc::new(confirmation_policies),
+        }
+    }
+
+    /// Copy of the server for a new client session, which detects its own workspaces
+    pub fn for_session(&self) -> Self {
+        Self {
+            workspaces: SessionWorkspaces::new(self.fixed_workspace.clone()),
+            ..self.clone()
         }
     }
 
@@ -138,7 +293,7 @@ impl Server {
 
         // Header
         output.push_str("## Rust MCP Server\n");
-        output.push_str(&format!("| 🟢 Tools ({}) | 🟢 Prompts (0) | 🟢 Resources (0) | <span style=\"opacity:0.6\">🔴 Logging</span> | <span style=\"opacity:0.6\">🔴 Completions</span> | <span style=\"opacity:0.6\">🔴 Experimental</span> |\n", self.tools.len()));
+        output.push_str(&format!("| 🟢 Tools ({}) | 🟢 Prompts ({}) | 🟢 Resources ({}) | <span style=\"opacity:0.6\">🔴 Logging</span> | <span style=\"opacity:0.6\">🔴 Completions</span> | <span style=\"opacity:0.6\">🔴 Experimental</span> |\n", self.tools.len(), self.prompts.len(), RESOURCE_KINDS.len()));
         output.push_str("| --- | --- | --- | --- | --- | --- |\n\n");
 
         // Tools section
@@ -175,6 +330,38 @@ impl Server {
             output.push('\n');
         }
 
+        // Prompts section
+        output.push_str(&format!("## 📝 Prompts ({})\n\n\n", self.prompts.len()));
+        for prompt in self.prompts.list() {
+            output.push_str(&format!("- **{}**\n", prompt.name));
+            if let Some(description) = &prompt.description {
+                output.push_str(&format!("  - {description}\n"));
+            }
+            if let Some(arguments) = &prompt.arguments {
+                output.push_str("  - **Arguments:**\n");
+                for argument in arguments {
+                    let required = if argument.required == Some(true) {
+                        " (required)"
+                    } else {
+                        ""
+                    };
+                    output.push_str(&format!(
+                        "      - <code>{}</code>{required} : {}<br />\n",
+                        argument.name,
+                        argument.description.as_deref().unwrap_or_default()
+                    ));
+                }
+            }
+            output.push('\n');
+        }
+
+        // Resources section
+        output.push_str(&format!("## 📄 Resources ({})\n\n\n", RESOURCE_KINDS.len()));
+        for (name, description) in RESOURCE_KINDS {
+            output.push_str(&format!("- **{name}**\n"));
+            output.push_str(&format!("  - {description}\n\n"));
+        }
+
         output.pop();
         output
     }
@@ -202,11 +389,17 @@ impl Server {
 impl rmcp::ServerHandler for Server {
     fn get_info(&self) -> ServerInfo {
         use rmcp::model::{
-            Implementation, InitializeResult, ProtocolVersion, ServerCapabilities, ToolsCapability,
+            Implementation, InitializeResult, PromptsCapability, ProtocolVersion,
+            ResourcesCapability, ServerCapabilities, ToolsCapability,
         };
 
         let mut capabilities = ServerCapabilities::default();
         capabilities.tools = Some(ToolsCapability { list_changed: None });
+        capabilities.prompts = Some(PromptsCapability { list_changed: None });
+        capabilities.resources = Some(ResourcesCapability {
+            subscribe: None,
+            list_changed: None,
+        });
 
         let mut server_info = Implementation::default();
         server_info.name = "Rust MCP Server".to_owned();
@@ -228,8 +421,15 @@ impl rmcp::ServerHandler for Server {
 
     async fn on_initialized(&self, context: NotificationContext<rmcp::RoleServer>) {
         tracing::info!("MCP client initialized");
-        if self.detect_workspace {
-            crate::workspace::detect_rust_workspace(context);
+        if self.fixed_workspace.is_none() {
+            crate::workspace::detect_rust_workspaces(context.peer, self.workspaces.clone());
+        }
+    }
+
+    async fn on_roots_list_changed(&self, context: NotificationContext<rmcp::RoleServer>) {
+        tracing::info!("MCP client roots changed");
+        if self.fixed_workspace.is_none() {
+            crate::workspace::detect_rust_workspaces(context.peer, self.workspaces.clone());
         }
     }
 
@@ -250,7 +450,18 @@ impl rmcp::ServerHandler for Server {
             tool_def.title = Some(tool.title().into());
             tool_def.description = Some(tool.description().trim().trim_matches('\n').into());
             tool_def.input_schema = schema;
+            tool_def.output_schema = tool.output_schema().map(Arc::new);
             tool_def.execution = Some(execution.clone());
+            tool_def.annotations = Some(if globals::is_read_only() {
+                // arguments that would write are rejected in read-only mode
+                ToolHints {
+                    open_world: tool.hints().open_world,
+                    ..ToolHints::read_only()
+                }
+                .to_annotations()
+            } else {
+                tool.hints().to_annotations()
+            });
             tools.push(tool_def);
         }
 
@@ -261,16 +472,76 @@ impl rmcp::ServerHandler for Server {
         })
     }
 
-    async fn call_tool(
+    async fn list_prompts(
         &self,
-        request: rmcp::model::CallToolRequestParams,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<rmcp::RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        Ok(ListPromptsResult::with_all_items(self.prompts.list()))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParams,
         _context: RequestContext<rmcp::RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        self.prompts.get(&request.name, request.arguments.as_ref())
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        context: RequestContext<rmcp::RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        let ctx = ToolContext::new(context.ct.clone()).with_workspaces(self.workspaces.clone());
+        resources::list_resources(&ctx)
+            .await
+            .map(ListResourcesResult::with_all_items)
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<rmcp::RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        Ok(ListResourceTemplatesResult::with_all_items(
+            resources::resource_templates(),
+        ))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        context: RequestContext<rmcp::RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let ctx = ToolContext::new(context.ct.clone()).with_workspaces(self.workspaces.clone());
+        resources::read_resource(&request.uri, &ctx).await
+    }
+
+    async fn call_tool(
+        &self,
+        mut request: rmcp::model::CallToolRequestParams,
+        context: RequestContext<rmcp::RoleServer>,
     ) -> Result<rmcp::model::CallToolResult, ErrorData> {
         let tool = self.tools.get(request.name.as_ref()).ok_or_else(|| {
             ErrorData::invalid_request(format!("Tool '{}' not found", request.name), None)
         })?;
 
-        tool.call_rmcp_tool(request)
+        if let Some(defaults) = self.tool_defaults.get(tool.name()) {
+            config::apply_defaults(&mut request.arguments, defaults);
+        }
+
+        let policy = self
+            .confirmation_policies
+            .get(tool.name())
+            .copied()
+            .unwrap_or_default();
+        let ctx = ToolContext::new(context.ct.clone())
+            .with_workspaces(self.workspaces.clone())
+            .with_progress(ProgressReporter::from_request(&context))
+            .with_confirmation(Confirmation::new(policy, context.peer.clone()));
+        tool.call_rmcp_tool(request, ctx)
+            .await
             .map(|r| r.into_rmcp_result(self.ignore_recommendations))
     }
 }
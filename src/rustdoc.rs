@@ -0,0 +1,1751 @@
+//! Queries on the JSON documentation rustdoc generates with `--output-format json`.
+//!
+//! The JSON is read as untyped values rather than with a fixed schema so that the small
+//! changes between format versions only degrade the rendering of the affected items.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt::Write as _,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::SystemTime,
+};
+
+use rmcp::ErrorData;
+use serde::Deserialize;
+use serde_json::{Value, value::RawValue};
+
+/// Number of parsed indices kept in memory, the JSON of a large crate takes a while to parse
+const MAX_LOADED_INDICES: usize = 4;
+
+/// Number of similar paths suggested when a path is not found
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Format versions the queries are known to work with: 34 renamed `import` items to `use`,
+/// later versions are still read in case their changes do not affect the queries
+const FORMAT_VERSIONS: RangeInclusive<u64> = 34..=61;
+
+static LOADED: Mutex<VecDeque<(PathBuf, SystemTime, Arc<RustdocIndex>)>> =
+    Mutex::new(VecDeque::new());
+
+/// Kinds of items in the order they are listed, with the heading of their group
+const ITEM_GROUPS: [(&str, &str); 14] = [
+    ("module", "Modules"),
+    ("macro", "Macros"),
+    ("proc_macro", "Macros"),
+    ("struct", "Structs"),
+    ("enum", "Enums"),
+    ("union", "Unions"),
+    ("trait", "Traits"),
+    ("trait_alias", "Trait aliases"),
+    ("function", "Functions"),
+    ("type_alias", "Type aliases"),
+    ("constant", "Constants"),
+    ("static", "Statics"),
+    ("primitive", "Primitives"),
+    ("variant", "Variants"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Query {
+    /// Declaration and documentation of an item
+    Item,
+    /// Public items of a module, or members of a type or trait
+    Items,
+    /// Trait implementations of a type, or implementors of a trait
+    Impls,
+}
+
+impl Query {
+    pub(crate) fn parse(query: &str) -> Result<Self, ErrorData> {
+        match query {
+            "item" => Ok(Self::Item),
+            "items" => Ok(Self::Items),
+            "impls" => Ok(Self::Impls),
+            other => Err(ErrorData::invalid_params(
+                format!("Unknown query `{other}`, expected \"item\", \"items\" or \"impls\""),
+                None,
+            )),
+        }
+    }
+}
+
+/// The fields of a rustdoc JSON file that are used, with the items left unparsed
+#[derive(Deserialize)]
+struct RawCrate {
+    root: Value,
+    #[serde(default)]
+    crate_version: Option<String>,
+    #[serde(default)]
+    format_version: Value,
+    #[serde(default)]
+    paths: Value,
+    index: HashMap<String, Box<RawValue>>,
+}
+
+/// Documentation of one crate with the public path of its items
+pub(crate) struct RustdocIndex {
+    /// Items by ID, parsed when first used since the JSON of a crate like `core` takes more than
+    /// ten times its size once parsed
+    items: HashMap<String, (Box<RawValue>, OnceLock<Value>)>,
+    /// Path and kind of the items of this crate and of the items it refers to in other crates
+    paths: Value,
+    crate_version: Option<String>,
+    crate_name: String,
+    /// Item ID by public path, every re-export of an item has its own entry
+    by_path: BTreeMap<String, String>,
+    /// Items that share their path with a module, such as the `str` primitive and the `std::str`
+    /// module, which stays in `by_path`
+    shadowed: BTreeMap<String, String>,
+    /// IDs of the items with a public path
+    public: HashSet<String>,
+    /// Paths looked at by [`Self::search`]: every item and the members of types and traits, with
+    /// their ID and the ID of the parent of members
+    names: Vec<(String, String, Option<String>)>,
+}
+
+/// An item found by [`RustdocIndex::search`]
+pub(crate) struct SearchHit {
+    /// Shortest public path of the item
+    pub(crate) path: String,
+    /// Path of the item in the module that defines it, if it differs from `path`
+    pub(crate) definition: Option<String>,
+    /// Kind of the item and first paragraph of its docs
+    pub(crate) description: String,
+}
+
+impl RustdocIndex {
+    /// Parses a rustdoc JSON file, reusing the result of a previous call while the file is unchanged
+    pub(crate) async fn load(file: PathBuf) -> Result<Arc<Self>, ErrorData> {
+        tokio::task::spawn_blocking(move || Self::load_blocking(&file))
+            .await
+            .map_err(|e| {
+                ErrorData::internal_error(format!("Failed to load rustdoc JSON: {e}"), None)
+            })?
+    }
+
+    fn load_blocking(file: &Path) -> Result<Arc<Self>, ErrorData> {
+        let modified = std::fs::metadata(file)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| {
+                ErrorData::internal_error(format!("Failed to read {}: {e}", file.display()), None)
+            })?;
+        {
+            let loaded = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((_, _, index)) = loaded
+                .iter()
+                .find(|(path, time, _)| path == file && *time == modified)
+            {
+                return Ok(index.clone());
+            }
+        }
+
+        let json = std::fs::read(file).map_err(|e| {
+            ErrorData::internal_error(format!("Failed to read {}: {e}", file.display()), None)
+        })?;
+        let krate = serde_json::from_slice(&json).map_err(|e| {
+            ErrorData::internal_error(
+                format!("Failed to parse rustdoc JSON {}: {e}", file.display()),
+                None,
+            )
+        })?;
+        drop(json);
+        let index = Arc::new(Self::new(krate)?);
+
+        let mut loaded = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+        loaded.retain(|(path, _, _)| path != file);
+        if loaded.len() == MAX_LOADED_INDICES {
+            loaded.pop_front();
+        }
+        loaded.push_back((file.to_path_buf(), modified, index.clone()));
+        Ok(index)
+    }
+
+    fn new(krate: RawCrate) -> Result<Self, ErrorData> {
+        let unsupported = |reason: &str| {
+            ErrorData::internal_error(
+                format!(
+                    "Unsupported rustdoc JSON format version {}: {reason}, supported versions are {} to {}",
+                    krate.format_version,
+                    FORMAT_VERSIONS.start(),
+                    FORMAT_VERSIONS.end()
+                ),
+                None,
+            )
+        };
+        match krate.format_version.as_u64() {
+            None => return Err(unsupported("not a rustdoc JSON file")),
+            Some(version) if version < *FORMAT_VERSIONS.start() => {
+                return Err(unsupported(
+                    "generated by an older toolchain, update the nightly toolchain",
+                ));
+            }
+            Some(version) if version > *FORMAT_VERSIONS.end() => {
+                tracing::warn!(
+                    "Reading rustdoc JSON format version {version}, newer than the supported versions"
+                );
+            }
+            Some(_) => {}
+        }
+
+        let root = id_key(&krate.root);
+        let crate_name = krate
+            .index
+            .get(&root)
+            .and_then(|root| serde_json::from_str::<Value>(root.get()).ok())
+            .and_then(|root| root["name"].as_str().map(str::to_owned))
+            .ok_or_else(|| unsupported("the root module was not found"))?;
+        let mut index = Self {
+            items: krate
+                .index
+                .into_iter()
+                .map(|(id, item)| (id, (item, OnceLock::new())))
+                .collect(),
+            paths: krate.paths,
+            crate_version: krate.crate_version,
+            crate_name,
+            by_path: BTreeMap::new(),
+            shadowed: BTreeMap::new(),
+            public: HashSet::new(),
+            names: Vec::new(),
+        };
+        index.collect_paths(root);
+        index.collect_names();
+        Ok(index)
+    }
+
+    /// Walks the public modules breadth first, following re-exports
+    fn collect_paths(&mut self, root: String) {
+        let mut paths = Vec::new();
+        let mut queue = VecDeque::from([(root.clone(), self.crate_name.clone())]);
+        let mut visited = HashSet::from([root.clone()]);
+        paths.push((self.crate_name.clone(), root));
+
+        while let Some((module, prefix)) = queue.pop_front() {
+            let Some(module) = self.peek(&module) else {
+                continue;
+            };
+            let Some(children) = module["inner"]["module"]["items"].as_array() else {
+                continue;
+            };
+            for child in children {
+                let Some(item) = self.peek(&id_key(child)) else {
+                    continue;
+                };
+                if item["visibility"] != "public" || is_hidden(&item) {
+                    continue;
+                }
+                let (name, target) = match item["inner"].get("use") {
+                    Some(import) => {
+                        let target = id_key(&import["id"]);
+                        if import["is_glob"] == true {
+                            match self.peek(&target).as_deref() {
+                                Some(glob)
+                                    if kind(glob) == "module" && visited.insert(target.clone()) =>
+                                {
+                                    queue.push_back((target, prefix.clone()));
+                                }
+                                Some(glob) if kind(glob) == "enum" => {
+                                    for variant in glob["inner"]["enum"]["variants"]
+                                        .as_array()
+                                        .into_iter()
+                                        .flatten()
+                                    {
+                                        let variant = id_key(variant);
+                                        if let Some(name) = self
+                                            .peek(&variant)
+                                            .and_then(|v| v["name"].as_str().map(str::to_owned))
+                                        {
+                                            paths.push((format!("{prefix}::{name}"), variant));
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        (import["name"].as_str(), target)
+                    }
+                    None => (item["name"].as_str(), id_key(child)),
+                };
+                let Some(name) = name else {
+                    continue;
+                };
+                let path = format!("{prefix}::{name}");
+                if self.is_module(&target)
+                    && self.items.contains_key(&target)
+                    && visited.insert(target.clone())
+                {
+                    queue.push_back((target.clone(), path.clone()));
+                }
+                paths.push((path, target));
+            }
+        }
+
+        for (path, id) in paths {
+            self.public.insert(id.clone());
+            let is_module = self.is_module(&id);
+            match self
+                .by_path
+                .get(&path)
+                .map(|existing| self.is_module(existing))
+            {
+                None => _ = self.by_path.insert(path, id),
+                Some(existing_is_module) if existing_is_module != is_module => {
+                    if is_module {
+                        let shadowed = self.by_path.insert(path.clone(), id);
+                        self.shadowed
+                            .extend(shadowed.map(|shadowed| (path, shadowed)));
+                    } else {
+                        self.shadowed.entry(path).or_insert(id);
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Collects the paths of the items and members [`Self::search`] matches
+    fn collect_names(&mut self) {
+        let mut names = Vec::new();
+        for (path, id) in self.by_path.iter().chain(&self.shadowed) {
+            let Some(item) = self.peek(id) else {
+                continue;
+            };
+            if *path != self.crate_name {
+                names.push((path.clone(), id.clone(), None));
+            }
+            // Variants and trait items are as visible as their parent
+            let mut members: Vec<(String, bool)> = match kind(&item) {
+                "enum" => ids(&item["inner"]["enum"]["variants"]),
+                "trait" => ids(&item["inner"]["trait"]["items"]),
+                _ => Vec::new(),
+            }
+            .into_iter()
+            .map(|member| (member, false))
+            .collect();
+            for imp in ids(&item["inner"][kind(&item)]["impls"]) {
+                let Some(imp) = self.peek(&imp) else {
+                    continue;
+                };
+                if imp["inner"]["impl"]["trait"].is_null() {
+                    members.extend(
+                        ids(&imp["inner"]["impl"]["items"])
+                            .into_iter()
+                            .map(|member| (member, true)),
+                    );
+                }
+            }
+            for (member, needs_pub) in members {
+                let Some(member_item) = self.peek(&member) else {
+                    continue;
+                };
+                if (needs_pub && member_item["visibility"] != "public") || is_hidden(&member_item) {
+                    continue;
+                }
+                if let Some(name) = member_item["name"].as_str() {
+                    names.push((format!("{path}::{name}"), member, Some(id.clone())));
+                }
+            }
+        }
+        self.names = names;
+    }
+
+    fn is_module(&self, id: &str) -> bool {
+        match self.peek(id) {
+            Some(item) => kind(&item) == "module",
+            None => self.paths[id]["kind"] == "module",
+        }
+    }
+
+    fn item(&self, id: &str) -> Option<&Value> {
+        let (json, item) = self.items.get(id)?;
+        Some(item.get_or_init(|| serde_json::from_str(json.get()).unwrap_or_default()))
+    }
+
+    /// The item `id` without keeping it parsed, for the walks over all the items
+    fn peek(&self, id: &str) -> Option<Cow<'_, Value>> {
+        let (json, item) = self.items.get(id)?;
+        Some(match item.get() {
+            Some(item) => Cow::Borrowed(item),
+            None => Cow::Owned(serde_json::from_str(json.get()).unwrap_or_default()),
+        })
+    }
+
+    /// Version of the documented crate, the toolchain version for the standard library
+    pub(crate) fn crate_version(&self) -> Option<&str> {
+        self.crate_version.as_deref()
+    }
+
+    /// Answers a query about the item at `path`, e.g. `tokio::sync::Mutex::lock`
+    pub(crate) fn query(&self, path: &str, query: Option<Query>) -> Result<String, ErrorData> {
+        let path = self.normalize_path(path);
+        let mut id = self.resolve(&path)?;
+        let mut note = String::new();
+        if let Some(shadowed) = self.shadowed.get(&path)
+            && (query != Some(Query::Items) || self.item(&id).is_none())
+        {
+            id = shadowed.clone();
+            note = format!("\n`{path}` is also a module, query \"items\" to list its items\n");
+        }
+        let Some(item) = self.item(&id) else {
+            return Err(self.external_item_error(&path, &id));
+        };
+        let query = query.unwrap_or(if kind(item) == "module" {
+            Query::Items
+        } else {
+            Query::Item
+        });
+        let text = match query {
+            Query::Item => self.render_item(&path, item),
+            Query::Items => self.render_items(&path, item),
+            Query::Impls => self.render_impls(&path, item)?,
+        };
+        Ok(text + &note)
+    }
+
+    /// Whether `path` names an item of this crate or one of its members
+    pub(crate) fn resolves(&self, path: &str) -> bool {
+        let path = self.normalize_path(path);
+        self.shadowed.contains_key(&path)
+            || self.resolve(&path).is_ok_and(|id| self.item(&id).is_some())
+    }
+
+    /// Path in another crate of the item `path` leads to through a re-export, e.g.
+    /// `core::option::Option::map` for `std::option::Option::map`
+    pub(crate) fn reexport_target(&self, path: &str) -> Option<String> {
+        let path = self.normalize_path(path);
+        let segments: Vec<&str> = path.split("::").collect();
+        for len in (1..=segments.len()).rev() {
+            let Some(id) = self.by_path.get(&segments[..len].join("::")) else {
+                continue;
+            };
+            if self.item(id).is_some() {
+                return None;
+            }
+            let mut target = self.canonical_path(id)?;
+            for segment in &segments[len..] {
+                _ = write!(target, "::{segment}");
+            }
+            return Some(target);
+        }
+        None
+    }
+
+    /// Public paths of the items re-exported from other crates, with their path in that crate
+    pub(crate) fn reexports(&self) -> impl Iterator<Item = (&str, String)> {
+        self.by_path.iter().filter_map(|(path, id)| {
+            if self.item(id).is_some() {
+                return None;
+            }
+            Some((path.as_str(), self.canonical_path(id)?))
+        })
+    }
+
+    /// Items, and members of types and traits, whose name matches `name` as ranked by
+    /// [`match_score`]
+    pub(crate) fn search(&self, name: &str) -> Vec<SearchHit> {
+        // Shortest path and parent of the matching items by ID
+        let mut hits: HashMap<&str, (&str, Option<&str>)> = HashMap::new();
+        for (path, id, parent) in &self.names {
+            if match_score(name, path).is_none() {
+                continue;
+            }
+            match hits.get_mut(id.as_str()) {
+                Some((shortest, _)) if shortest.len() <= path.len() => {}
+                Some(hit) => *hit = (path, parent.as_deref()),
+                None => _ = hits.insert(id, (path, parent.as_deref())),
+            }
+        }
+
+        hits.into_iter()
+            .filter_map(|(id, (path, parent))| {
+                let item = self.peek(id)?;
+                let definition = match parent {
+                    Some(parent) => self.canonical_path(parent).map(|parent| {
+                        format!("{parent}::{}", item["name"].as_str().unwrap_or_default())
+                    }),
+                    None => self.canonical_path(id),
+                };
+                Some(SearchHit {
+                    definition: definition.filter(|definition| definition != path),
+                    description: format!("({}){}", kind_name(&item), summary(&item)),
+                    path: path.to_owned(),
+                })
+            })
+            .collect()
+    }
+
+    /// Replaces a leading `crate` or hyphenated crate name with the crate name
+    fn normalize_path(&self, path: &str) -> String {
+        let path = path.trim().trim_start_matches("::");
+        let (first, rest) = path.split_once("::").unwrap_or((path, ""));
+        let first = if first == "crate" || first.replace('-', "_") == self.crate_name {
+            self.crate_name.as_str()
+        } else {
+            first
+        };
+        match (first, rest) {
+            (first, "") if first == self.crate_name => first.to_owned(),
+            (first, rest) if first == self.crate_name => format!("{first}::{rest}"),
+            // Paths that don't start with the crate name are relative to its root
+            _ => format!("{}::{path}", self.crate_name),
+        }
+    }
+
+    /// ID of the item at `path`, items that are not reachable through modules such as methods
+    /// and fields are looked up among the members of their parent
+    fn resolve(&self, path: &str) -> Result<String, ErrorData> {
+        if let Some(id) = self.by_path.get(path) {
+            return Ok(id.clone());
+        }
+
+        let segments: Vec<&str> = path.split("::").collect();
+        for parent_len in (1..segments.len()).rev() {
+            let parent = segments[..parent_len].join("::");
+            let parents: Vec<&String> = self
+                .by_path
+                .get(&parent)
+                .into_iter()
+                .chain(self.shadowed.get(&parent))
+                .collect();
+            if parents.is_empty() {
+                continue;
+            }
+            return parents
+                .into_iter()
+                .find_map(|parent| {
+                    segments[parent_len..]
+                        .iter()
+                        .try_fold(parent.clone(), |id, name| self.member(&id, name))
+                })
+                .ok_or_else(|| self.not_found_error(path));
+        }
+        Err(self.not_found_error(path))
+    }
+
+    /// Field, variant, method or associated item `name` of the type or trait `id`
+    fn member(&self, id: &str, name: &str) -> Option<String> {
+        let item = self.item(id)?;
+        let inner = &item["inner"][kind(item)];
+        let direct = match kind(item) {
+            "struct" | "variant" => struct_fields(inner),
+            "union" => ids(&inner["fields"]),
+            "enum" => ids(&inner["variants"]),
+            "trait" => ids(&inner["items"]),
+            _ => Vec::new(),
+        };
+        let named = |ids: Vec<String>| {
+            ids.into_iter()
+                .find(|id| self.item(id).is_some_and(|item| item["name"] == name))
+        };
+        named(direct).or_else(|| {
+            let (inherent, traits): (Vec<_>, Vec<_>) = self
+                .impls_of(item)
+                .into_iter()
+                .partition(|(_, imp)| imp["trait"].is_null());
+            inherent
+                .into_iter()
+                .chain(traits)
+                .find_map(|(_, imp)| named(ids(&imp["items"])))
+        })
+    }
+
+    /// Whether a trait is public, documentation generated with private items includes the impls
+    /// of private traits
+    fn is_public_trait(&self, trait_: &Value) -> bool {
+        let id = id_key(&trait_["id"]);
+        self.item(&id).is_none() || self.public.contains(&id)
+    }
+
+    fn impls_of<'a>(&'a self, item: &'a Value) -> Vec<(String, &'a Value)> {
+        ids(&item["inner"][kind(item)]["impls"])
+            .into_iter()
+            .filter_map(|id| {
+                let imp = &self.item(&id)?["inner"]["impl"];
+                (!imp.is_null()).then_some((id, imp))
+            })
+            .collect()
+    }
+
+    fn not_found_error(&self, path: &str) -> ErrorData {
+        let name = path.rsplit("::").next().unwrap_or(path);
+        let mut similar: Vec<((u8, usize), &str)> = self
+            .by_path
+            .keys()
+            .filter_map(|candidate| Some((match_score(name, candidate)?, candidate.as_str())))
+            .collect();
+        similar.sort();
+        let similar: Vec<&str> = similar
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, candidate)| candidate)
+            .collect();
+        let mut message = format!(
+            "`{path}` not found in the public API of `{}`",
+            self.crate_name
+        );
+        if !similar.is_empty() {
+            _ = write!(message, ", similar paths: {}", similar.join(", "));
+        }
+        ErrorData::invalid_params(message, None)
+    }
+
+    /// Path of an item in the module that defines it, also known for the items of other crates
+    fn canonical_path(&self, id: &str) -> Option<String> {
+        let segments = self.paths[id]["path"].as_array()?;
+        Some(
+            segments
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join("::"),
+        )
+    }
+
+    fn external_item_error(&self, path: &str, id: &str) -> ErrorData {
+        let canonical = self.canonical_path(id).unwrap_or_default();
+        ErrorData::invalid_params(
+            format!(
+                "`{path}` is a re-export of `{canonical}` from another crate, query its crate instead"
+            ),
+            None,
+        )
+    }
+
+    fn render_item(&self, path: &str, item: &Value) -> String {
+        let mut text = format!("{} {path}", kind_name(item));
+        if let Some(span) = item.get("span").filter(|span| !span.is_null()) {
+            _ = write!(
+                text,
+                " (defined at {}:{})",
+                span["filename"].as_str().unwrap_or_default(),
+                span["begin"][0]
+            );
+        }
+        _ = write!(text, "\n\n```rust\n{}\n```\n", self.declaration(item));
+        if let Some(stability) = stability(item) {
+            _ = writeln!(text, "\n{stability}");
+        }
+        if let Some(deprecation) = item.get("deprecation").filter(|d| !d.is_null()) {
+            _ = writeln!(
+                text,
+                "\nDeprecated{}{}",
+                deprecation["since"]
+                    .as_str()
+                    .map(|since| format!(" since {since}"))
+                    .unwrap_or_default(),
+                deprecation["note"]
+                    .as_str()
+                    .map(|note| format!(": {note}"))
+                    .unwrap_or_default()
+            );
+        }
+        if let Some(docs) = item["docs"].as_str() {
+            _ = writeln!(text, "\n{}", docs.trim());
+        }
+
+        match kind(item) {
+            "struct" | "enum" | "union" | "primitive" => {
+                let methods = self.render_inherent_items(item);
+                if !methods.is_empty() {
+                    _ = write!(text, "\nMethods and associated items:\n{methods}");
+                }
+                let traits: Vec<String> = self
+                    .impls_of(item)
+                    .into_iter()
+                    .filter(|(_, imp)| {
+                        !imp["trait"].is_null()
+                            && imp["blanket_impl"].is_null()
+                            && self.is_public_trait(&imp["trait"])
+                    })
+                    .map(|(_, imp)| self.path(&imp["trait"]))
+                    .collect();
+                if !traits.is_empty() {
+                    _ = writeln!(
+                        text,
+                        "\nImplements: {} (query \"impls\" for the details)",
+                        traits.join(", ")
+                    );
+                }
+            }
+            "trait" => {
+                let implementations = ids(&item["inner"]["trait"]["implementations"]).len();
+                _ = writeln!(
+                    text,
+                    "\n{implementations} implementation(s) in this crate (query \"impls\" to list them)"
+                );
+            }
+            _ => {}
+        }
+        text
+    }
+
+    /// One line per public member of an item, with the first line of its docs
+    fn render_items(&self, path: &str, item: &Value) -> String {
+        let inner = &item["inner"][kind(item)];
+        let mut text = String::new();
+        match kind(item) {
+            "module" => {
+                let prefix = format!("{path}::");
+                let mut groups: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+                for (child_path, id) in self.by_path.range(prefix.clone()..) {
+                    let Some(name) = child_path.strip_prefix(&prefix) else {
+                        break;
+                    };
+                    if name.contains("::") {
+                        continue;
+                    }
+                    let line = match self.item(id) {
+                        Some(child) => {
+                            let group = ITEM_GROUPS
+                                .iter()
+                                .position(|(kind_, _)| *kind_ == kind(child))
+                                .unwrap_or(ITEM_GROUPS.len());
+                            groups
+                                .entry(group)
+                                .or_default()
+                                .push(format!("- {name}{}", summary(child)));
+                            continue;
+                        }
+                        None => format!(
+                            "- {name} (re-export of `{}`)",
+                            self.canonical_path(id).unwrap_or_default()
+                        ),
+                    };
+                    groups.entry(ITEM_GROUPS.len()).or_default().push(line);
+                }
+                for (group, lines) in groups {
+                    let heading = ITEM_GROUPS
+                        .get(group)
+                        .map_or("Re-exports", |(_, heading)| heading);
+                    _ = writeln!(text, "{heading}:\n{}\n", lines.join("\n"));
+                }
+            }
+            "struct" | "union" | "variant" => {
+                let mut fields = match kind(item) {
+                    "union" => ids(&inner["fields"]),
+                    _ => struct_fields(inner),
+                };
+                // Fields of variants are public without a visibility
+                if kind(item) != "variant" {
+                    fields.retain(|field| {
+                        self.item(field)
+                            .is_some_and(|field| field["visibility"] == "public")
+                    });
+                }
+                self.push_members(&mut text, "Fields", &fields);
+                _ = write!(text, "{}", self.render_inherent_items(item));
+            }
+            "enum" => {
+                self.push_members(&mut text, "Variants", &ids(&inner["variants"]));
+                _ = write!(text, "{}", self.render_inherent_items(item));
+            }
+            "trait" => self.push_members(&mut text, "Items", &ids(&inner["items"])),
+            "primitive" => _ = write!(text, "{}", self.render_inherent_items(item)),
+            _ => {
+                return format!(
+                    "{} {path} has no members, query \"item\" for its declaration",
+                    kind_name(item)
+                );
+            }
+        }
+        if text.is_empty() {
+            format!("{} {path} has no public items", kind_name(item))
+        } else {
+            text.trim_end().to_owned() + "\n"
+        }
+    }
+
+    fn push_members(&self, text: &mut String, heading: &str, members: &[String]) {
+        let lines: Vec<String> = members
+            .iter()
+            .filter_map(|id| self.item(id))
+            .filter(|member| member["name"].is_string())
+            .map(|member| format!("- {}{}", self.member_decl(member), summary(member)))
+            .collect();
+        if !lines.is_empty() {
+            _ = writeln!(text, "{heading}:\n{}\n", lines.join("\n"));
+        }
+    }
+
+    /// Public items of the inherent impls of a type, one per line
+    fn render_inherent_items(&self, item: &Value) -> String {
+        let mut text = String::new();
+        for (_, imp) in self.impls_of(item) {
+            if !imp["trait"].is_null() {
+                continue;
+            }
+            for id in ids(&imp["items"]) {
+                if let Some(member) = self
+                    .item(&id)
+                    .filter(|member| member["visibility"] == "public" && !is_hidden(member))
+                {
+                    _ = writeln!(text, "- `{}`{}", self.declaration(member), summary(member));
+                }
+            }
+        }
+        text
+    }
+
+    fn render_impls(&self, path: &str, item: &Value) -> Result<String, ErrorData> {
+        let mut text = String::new();
+        match kind(item) {
+            "struct" | "enum" | "union" | "primitive" => {
+                let mut auto = Vec::new();
+                let mut blanket = Vec::new();
+                for (_, imp) in self.impls_of(item) {
+                    if imp["trait"].is_null() || !self.is_public_trait(&imp["trait"]) {
+                        continue;
+                    }
+                    if imp["is_synthetic"] == true {
+                        auto.push(self.path(&imp["trait"]));
+                    } else if !imp["blanket_impl"].is_null() {
+                        blanket.push(self.path(&imp["trait"]));
+                    } else {
+                        _ = writeln!(text, "- `{}`", self.impl_header(imp));
+                    }
+                }
+                if text.is_empty() {
+                    text = format!("No trait implementations for {path}\n");
+                } else {
+                    text = format!("Trait implementations for {path}:\n{text}");
+                }
+                if !auto.is_empty() {
+                    _ = writeln!(text, "\nAuto traits: {}", auto.join(", "));
+                }
+                if !blanket.is_empty() {
+                    _ = writeln!(text, "\nBlanket implementations: {}", blanket.join(", "));
+                }
+            }
+            "trait" => {
+                let implementations = ids(&item["inner"]["trait"]["implementations"]);
+                _ = writeln!(
+                    text,
+                    "{} implementation(s) of {path} in this crate:",
+                    implementations.len()
+                );
+                for id in implementations {
+                    if let Some(imp) = self.item(&id).map(|imp| &imp["inner"]["impl"]) {
+                        _ = writeln!(text, "- `{}`", self.impl_header(imp));
+                    }
+                }
+            }
+            _ => {
+                return Err(ErrorData::invalid_params(
+                    format!(
+                        "{} {path} has no implementations, query \"impls\" on a type or a trait",
+                        kind_name(item)
+                    ),
+                    None,
+                ));
+            }
+        }
+        Ok(text)
+    }
+
+    /// Declaration of an item as it would be written in Rust, without the bodies
+    fn declaration(&self, item: &Value) -> String {
+        let name = item["name"].as_str().unwrap_or_default();
+        let vis = visibility(&item["visibility"]);
+        let inner = &item["inner"][kind(item)];
+        match kind(item) {
+            "function" => self.function(&vis, name, inner),
+            "struct" => {
+                let generics = &inner["generics"];
+                let head = format!("{vis}struct {name}{}", self.generic_params(generics));
+                let where_clause = self.where_clause(generics);
+                match &inner["kind"] {
+                    Value::Object(kind_) if kind_.contains_key("tuple") => {
+                        let fields: Vec<String> = kind_["tuple"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .map(|field| match self.item(&id_key(field)) {
+                                Some(field) if field["visibility"] == "public" => format!(
+                                    "{}{}",
+                                    visibility(&field["visibility"]),
+                                    self.ty(&field["inner"]["struct_field"])
+                                ),
+                                _ => "_".to_owned(),
+                            })
+                            .collect();
+                        format!("{head}({}){where_clause};", fields.join(", "))
+                    }
+                    Value::Object(_) => {
+                        let mut fields = String::new();
+                        let mut stripped = inner["kind"]["plain"]["has_stripped_fields"] == true;
+                        for field in struct_fields(inner) {
+                            match self.item(&field) {
+                                Some(field) if field["visibility"] == "public" => {
+                                    _ = writeln!(fields, "    {},", self.member_decl(field));
+                                }
+                                _ => stripped = true,
+                            }
+                        }
+                        if stripped {
+                            fields.push_str("    /* private fields */\n");
+                        }
+                        format!("{head}{where_clause} {{\n{fields}}}")
+                    }
+                    _ => format!("{head}{where_clause};"),
+                }
+            }
+            "union" => {
+                let mut fields = String::new();
+                for field in ids(&inner["fields"]) {
+                    if let Some(field) = self.item(&field) {
+                        _ = writeln!(fields, "    {},", self.member_decl(field));
+                    }
+                }
+                format!(
+                    "{vis}union {name}{}{} {{\n{fields}}}",
+                    self.generic_params(&inner["generics"]),
+                    self.where_clause(&inner["generics"])
+                )
+            }
+            "enum" => {
+                let mut variants = String::new();
+                for variant in ids(&inner["variants"]) {
+                    if let Some(variant) = self.item(&variant) {
+                        _ = writeln!(variants, "    {},", self.member_decl(variant));
+                    }
+                }
+                if inner["has_stripped_variants"] == true {
+                    variants.push_str("    // some variants omitted\n");
+                }
+                format!(
+                    "{vis}enum {name}{}{} {{\n{variants}}}",
+                    self.generic_params(&inner["generics"]),
+                    self.where_clause(&inner["generics"])
+                )
+            }
+            "trait" => {
+                let mut head = vis.clone();
+                if inner["is_unsafe"] == true {
+                    head.push_str("unsafe ");
+                }
+                if inner["is_auto"] == true {
+                    head.push_str("auto ");
+                }
+                let bounds = self.bounds(&inner["bounds"]);
+                let supertraits = if bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {bounds}")
+                };
+                let mut items = String::new();
+                for member in ids(&inner["items"]) {
+                    if let Some(member) = self.item(&member) {
+                        _ = writeln!(items, "    {}", self.member_decl(member));
+                    }
+                }
+                format!(
+                    "{head}trait {name}{}{supertraits}{} {{\n{items}}}",
+                    self.generic_params(&inner["generics"]),
+                    self.where_clause(&inner["generics"])
+                )
+            }
+            "type_alias" => format!(
+                "{vis}type {name}{}{} = {};",
+                self.generic_params(&inner["generics"]),
+                self.where_clause(&inner["generics"]),
+                self.ty(&inner["type"])
+            ),
+            "constant" => format!(
+                "{vis}const {name}: {} = {};",
+                self.ty(&inner["type"]),
+                inner["const"]["expr"].as_str().unwrap_or("_")
+            ),
+            "static" => format!(
+                "{vis}static {}{name}: {};",
+                if inner["is_mutable"] == true {
+                    "mut "
+                } else {
+                    ""
+                },
+                self.ty(&inner["type"])
+            ),
+            "macro" => inner.as_str().unwrap_or_default().to_owned(),
+            "module" => format!("{vis}mod {name}"),
+            "impl" => self.impl_header(inner),
+            "use" => format!("{vis}use {};", inner["source"].as_str().unwrap_or_default()),
+            "struct_field" | "variant" | "assoc_type" | "assoc_const" => self.member_decl(item),
+            // Primitives, extern crates and proc macros
+            _ => format!("{} {name}", kind_name(item)),
+        }
+    }
+
+    /// Short declaration of a field, variant or associated item
+    fn member_decl(&self, item: &Value) -> String {
+        let name = item["name"].as_str().unwrap_or_default();
+        let inner = &item["inner"][kind(item)];
+        match kind(item) {
+            "struct_field" => format!(
+                "{}{name}: {}",
+                visibility(&item["visibility"]),
+                self.ty(inner)
+            ),
+            "variant" => match &inner["kind"] {
+                Value::Object(kind_) if kind_.contains_key("tuple") => {
+                    let fields: Vec<String> = kind_["tuple"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|field| {
+                            self.item(&id_key(field)).map_or("_".to_owned(), |field| {
+                                self.ty(&field["inner"]["struct_field"])
+                            })
+                        })
+                        .collect();
+                    format!("{name}({})", fields.join(", "))
+                }
+                Value::Object(kind_) if kind_.contains_key("struct") => {
+                    let fields: Vec<String> = ids(&kind_["struct"]["fields"])
+                        .iter()
+                        .filter_map(|field| self.item(field))
+                        .map(|field| self.member_decl(field))
+                        .collect();
+                    format!("{name} {{ {} }}", fields.join(", "))
+                }
+                _ => match inner["discriminant"]["expr"].as_str() {
+                    Some(discriminant) => format!("{name} = {discriminant}"),
+                    None => name.to_owned(),
+                },
+            },
+            "function" => {
+                let decl = self.function("", name, inner);
+                if inner["has_body"] == true {
+                    format!("{decl} {{ ... }}")
+                } else {
+                    format!("{decl};")
+                }
+            }
+            "assoc_type" => {
+                let bounds = self.bounds(&inner["bounds"]);
+                let mut decl = format!("type {name}{}", self.generic_params(&inner["generics"]));
+                if !bounds.is_empty() {
+                    _ = write!(decl, ": {bounds}");
+                }
+                if !inner["type"].is_null() {
+                    _ = write!(decl, " = {}", self.ty(&inner["type"]));
+                }
+                decl + ";"
+            }
+            "assoc_const" => {
+                let mut decl = format!("const {name}: {}", self.ty(&inner["type"]));
+                if let Some(value) = inner["value"].as_str() {
+                    _ = write!(decl, " = {value}");
+                }
+                decl + ";"
+            }
+            _ => self.declaration(item),
+        }
+    }
+
+    fn function(&self, vis: &str, name: &str, function: &Value) -> String {
+        let header = &function["header"];
+        let mut decl = vis.to_owned();
+        for (flag, keyword) in [
+            ("is_const", "const "),
+            ("is_async", "async "),
+            ("is_unsafe", "unsafe "),
+        ] {
+            if header[flag] == true {
+                decl.push_str(keyword);
+            }
+        }
+        decl.push_str(&abi(&header["abi"]));
+        let inputs: Vec<String> = function["sig"]["inputs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|input| {
+                let name = input[0].as_str().unwrap_or("_");
+                let ty = &input[1];
+                if name != "self" {
+                    return format!("{name}: {}", self.ty(ty));
+                }
+                match (ty.get("generic"), ty.get("borrowed_ref")) {
+                    (Some(generic), _) if generic == "Self" => "self".to_owned(),
+                    (_, Some(reference)) if reference["type"]["generic"] == "Self" => format!(
+                        "&{}{}self",
+                        reference["lifetime"]
+                            .as_str()
+                            .map(|lifetime| format!("{lifetime} "))
+                            .unwrap_or_default(),
+                        if reference["is_mutable"] == true {
+                            "mut "
+                        } else {
+                            ""
+                        }
+                    ),
+                    _ => format!("self: {}", self.ty(ty)),
+                }
+            })
+            .collect();
+        _ = write!(
+            decl,
+            "fn {name}{}({})",
+            self.generic_params(&function["generics"]),
+            inputs.join(", ")
+        );
+        let output = &function["sig"]["output"];
+        if !output.is_null() {
+            _ = write!(decl, " -> {}", self.ty(output));
+        }
+        decl + &self.where_clause(&function["generics"])
+    }
+
+    fn impl_header(&self, imp: &Value) -> String {
+        let mut header = String::new();
+        if imp["is_unsafe"] == true {
+            header.push_str("unsafe ");
+        }
+        _ = write!(header, "impl{} ", self.generic_params(&imp["generics"]));
+        if !imp["trait"].is_null() {
+            if imp["is_negative"] == true {
+                header.push('!');
+            }
+            _ = write!(header, "{} for ", self.path(&imp["trait"]));
+        }
+        header + &self.ty(&imp["for"]) + &self.where_clause(&imp["generics"])
+    }
+
+    /// `<'a, T: Bound, const N: usize>`, empty without parameters
+    fn generic_params(&self, generics: &Value) -> String {
+        let params: Vec<String> = generics["params"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|param| param["kind"]["type"]["is_synthetic"] != true)
+            .map(|param| self.generic_param(param))
+            .collect();
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        }
+    }
+
+    fn generic_param(&self, param: &Value) -> String {
+        let name = param["name"].as_str().unwrap_or_default();
+        let kind_ = &param["kind"];
+        if let Some(lifetime) = kind_.get("lifetime") {
+            let outlives: Vec<&str> = lifetime["outlives"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            if outlives.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{name}: {}", outlives.join(" + "))
+            }
+        } else if let Some(ty) = kind_.get("type") {
+            let bounds = self.bounds(&ty["bounds"]);
+            let mut param = name.to_owned();
+            if !bounds.is_empty() {
+                _ = write!(param, ": {bounds}");
+            }
+            if !ty["default"].is_null() {
+                _ = write!(param, " = {}", self.ty(&ty["default"]));
+            }
+            param
+        } else if let Some(constant) = kind_.get("const") {
+            format!("const {name}: {}", self.ty(&constant["type"]))
+        } else {
+            name.to_owned()
+        }
+    }
+
+    /// ` where T: Bound`, empty without predicates
+    fn where_clause(&self, generics: &Value) -> String {
+        let predicates: Vec<String> = generics["where_predicates"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|predicate| {
+                if let Some(bound) = predicate.get("bound_predicate") {
+                    Some(format!(
+                        "{}{}: {}",
+                        self.higher_ranked(&bound["generic_params"]),
+                        self.ty(&bound["type"]),
+                        self.bounds(&bound["bounds"])
+                    ))
+                } else if let Some(lifetime) = predicate.get("lifetime_predicate") {
+                    let outlives: Vec<&str> = lifetime["outlives"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .collect();
+                    Some(format!(
+                        "{}: {}",
+                        lifetime["lifetime"].as_str().unwrap_or_default(),
+                        outlives.join(" + ")
+                    ))
+                } else {
+                    predicate
+                        .get("eq_predicate")
+                        .map(|eq| format!("{} == {}", self.ty(&eq["lhs"]), self.term(&eq["rhs"])))
+                }
+            })
+            .collect();
+        if predicates.is_empty() {
+            String::new()
+        } else {
+            format!(" where {}", predicates.join(", "))
+        }
+    }
+
+    fn higher_ranked(&self, params: &Value) -> String {
+        let params: Vec<String> = params
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|param| self.generic_param(param))
+            .collect();
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("for<{}> ", params.join(", "))
+        }
+    }
+
+    fn bounds(&self, bounds: &Value) -> String {
+        bounds
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|bound| {
+                if let Some(trait_bound) = bound.get("trait_bound") {
+                    let modifier = match trait_bound["modifier"].as_str() {
+                        Some("maybe") => "?",
+                        Some("maybe_const") => "~const ",
+                        _ => "",
+                    };
+                    format!(
+                        "{}{modifier}{}",
+                        self.higher_ranked(&trait_bound["generic_params"]),
+                        self.path(&trait_bound["trait"])
+                    )
+                } else if let Some(lifetime) = bound["outlives"].as_str() {
+                    lifetime.to_owned()
+                } else if let Some(args) = bound["use"].as_array() {
+                    let args: Vec<String> = args
+                        .iter()
+                        .map(|arg| match arg {
+                            Value::String(arg) => arg.clone(),
+                            arg => arg
+                                .as_object()
+                                .and_then(|arg| arg.values().next())
+                                .and_then(Value::as_str)
+                                .unwrap_or_default()
+                                .to_owned(),
+                        })
+                        .collect();
+                    format!("use<{}>", args.join(", "))
+                } else {
+                    "_".to_owned()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    /// A path to a type or trait with its generic arguments
+    fn path(&self, path: &Value) -> String {
+        // Older format versions call the field `name`
+        let name = path["path"]
+            .as_str()
+            .or_else(|| path["name"].as_str())
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                // Paths to private items are left empty, fall back to the item name
+                let id = id_key(&path["id"]);
+                self.paths[&id]["path"]
+                    .as_array()
+                    .and_then(|segments| segments.last())
+                    .or_else(|| self.item(&id)?.get("name"))
+                    .and_then(Value::as_str)
+            })
+            .unwrap_or_default();
+        format!("{name}{}", self.generic_args(&path["args"]))
+    }
+
+    fn generic_args(&self, args: &Value) -> String {
+        if let Some(angle) = args.get("angle_bracketed") {
+            let mut parts: Vec<String> = angle["args"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|arg| match arg {
+                    Value::String(_) => "_".to_owned(),
+                    arg => {
+                        if let Some(lifetime) = arg["lifetime"].as_str() {
+                            lifetime.to_owned()
+                        } else if let Some(ty) = arg.get("type") {
+                            self.ty(ty)
+                        } else {
+                            arg["const"]["expr"].as_str().unwrap_or("_").to_owned()
+                        }
+                    }
+                })
+                .collect();
+            parts.extend(
+                angle["constraints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|constraint| {
+                        let name = format!(
+                            "{}{}",
+                            constraint["name"].as_str().unwrap_or_default(),
+                            self.generic_args(&constraint["args"])
+                        );
+                        let binding = &constraint["binding"];
+                        if let Some(equality) = binding.get("equality") {
+                            format!("{name} = {}", self.term(equality))
+                        } else {
+                            format!("{name}: {}", self.bounds(&binding["constraint"]))
+                        }
+                    }),
+            );
+            if parts.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", parts.join(", "))
+            }
+        } else if let Some(parenthesized) = args.get("parenthesized") {
+            let inputs: Vec<String> = parenthesized["inputs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|input| self.ty(input))
+                .collect();
+            let output = &parenthesized["output"];
+            if output.is_null() {
+                format!("({})", inputs.join(", "))
+            } else {
+                format!("({}) -> {}", inputs.join(", "), self.ty(output))
+            }
+        } else {
+            String::new()
+        }
+    }
+
+    fn term(&self, term: &Value) -> String {
+        match (term.get("type"), term.get("constant")) {
+            (Some(ty), _) => self.ty(ty),
+            (_, Some(constant)) => constant["expr"].as_str().unwrap_or("_").to_owned(),
+            _ => "_".to_owned(),
+        }
+    }
+
+    fn ty(&self, ty: &Value) -> String {
+        let Some((variant, value)) = ty.as_object().and_then(|ty| ty.iter().next()) else {
+            // `infer` is the only variant without fields
+            return "_".to_owned();
+        };
+        match variant.as_str() {
+            "resolved_path" => self.path(value),
+            "generic" | "primitive" => value.as_str().unwrap_or_default().to_owned(),
+            "tuple" => {
+                let types: Vec<String> = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|ty| self.ty(ty))
+                    .collect();
+                match types.as_slice() {
+                    [single] => format!("({single},)"),
+                    types => format!("({})", types.join(", ")),
+                }
+            }
+            "slice" => format!("[{}]", self.ty(value)),
+            "array" => format!(
+                "[{}; {}]",
+                self.ty(&value["type"]),
+                value["len"].as_str().unwrap_or("_")
+            ),
+            "pat" => self.ty(&value["type"]),
+            "impl_trait" => format!("impl {}", self.bounds(value)),
+            "dyn_trait" => {
+                let mut bounds: Vec<String> = value["traits"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|poly| {
+                        format!(
+                            "{}{}",
+                            self.higher_ranked(&poly["generic_params"]),
+                            self.path(&poly["trait"])
+                        )
+                    })
+                    .collect();
+                if let Some(lifetime) = value["lifetime"].as_str() {
+                    bounds.push(lifetime.to_owned());
+                }
+                format!("dyn {}", bounds.join(" + "))
+            }
+            "raw_pointer" => format!(
+                "*{} {}",
+                if value["is_mutable"] == true {
+                    "mut"
+                } else {
+                    "const"
+                },
+                self.ty(&value["type"])
+            ),
+            "borrowed_ref" => format!(
+                "&{}{}{}",
+                value["lifetime"]
+                    .as_str()
+                    .map(|lifetime| format!("{lifetime} "))
+                    .unwrap_or_default(),
+                if value["is_mutable"] == true {
+                    "mut "
+                } else {
+                    ""
+                },
+                self.ty(&value["type"])
+            ),
+            "qualified_path" => {
+                let name = value["name"].as_str().unwrap_or_default();
+                let args = self.generic_args(&value["args"]);
+                let self_type = self.ty(&value["self_type"]);
+                if value["trait"].is_null() {
+                    format!("{self_type}::{name}{args}")
+                } else {
+                    format!(
+                        "<{self_type} as {}>::{name}{args}",
+                        self.path(&value["trait"])
+                    )
+                }
+            }
+            "function_pointer" => {
+                let inputs: Vec<String> = value["sig"]["inputs"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|input| self.ty(&input[1]))
+                    .collect();
+                let mut pointer = format!(
+                    "{}{}{}fn({})",
+                    self.higher_ranked(&value["generic_params"]),
+                    if value["header"]["is_unsafe"] == true {
+                        "unsafe "
+                    } else {
+                        ""
+                    },
+                    abi(&value["header"]["abi"]),
+                    inputs.join(", ")
+                );
+                let output = &value["sig"]["output"];
+                if !output.is_null() {
+                    _ = write!(pointer, " -> {}", self.ty(output));
+                }
+                pointer
+            }
+            _ => "_".to_owned(),
+        }
+    }
+}
+
+/// Index key of an item ID, IDs were strings in older format versions and are numbers now
+fn id_key(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
+
+fn ids(list: &Value) -> Vec<String> {
+    list.as_array().into_iter().flatten().map(id_key).collect()
+}
+
+fn struct_fields(inner: &Value) -> Vec<String> {
+    let kind_ = &inner["kind"];
+    match (kind_.get("plain"), kind_.get("struct"), kind_.get("tuple")) {
+        (Some(plain), _, _) => ids(&plain["fields"]),
+        (_, Some(fields), _) => ids(&fields["fields"]),
+        (_, _, Some(fields)) => fields
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|field| !field.is_null())
+            .map(id_key)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Variant name of the `inner` enum of an item, e.g. `struct` or `function`
+fn kind(item: &Value) -> &str {
+    match &item["inner"] {
+        Value::Object(inner) => inner.keys().next().map_or("", String::as_str),
+        Value::String(inner) => inner,
+        _ => "",
+    }
+}
+
+fn kind_name(item: &Value) -> String {
+    kind(item).replace('_', " ")
+}
+
+/// First paragraph of the docs of an item on a single line, prefixed with `: `, and a mark on
+/// unstable items
+fn summary(item: &Value) -> String {
+    let unstable = if item["stability"]["level"] == "unstable" {
+        " (unstable)"
+    } else {
+        ""
+    };
+    let paragraph: Vec<&str> = item["docs"]
+        .as_str()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+        .collect();
+    if paragraph.is_empty() {
+        unstable.to_owned()
+    } else {
+        format!("{unstable}: {}", paragraph.join(" "))
+    }
+}
+
+/// Stability of an item, only recorded in the documentation of the standard library
+fn stability(item: &Value) -> Option<String> {
+    let describe = |stability: &Value| match &stability["level"] {
+        Value::Object(level) => level
+            .get("stable")
+            .and_then(|stable| stable["since"].as_str())
+            .map(|since| format!("stable since Rust {since}")),
+        Value::String(level) if level == "unstable" => Some(format!(
+            "unstable, requires `#![feature({})]`",
+            stability["feature"].as_str().unwrap_or_default()
+        )),
+        _ => None,
+    };
+    let mut text = describe(&item["stability"])?;
+    if let Some(const_stability) = describe(&item["const_stability"]) {
+        _ = write!(text, ", const {const_stability}");
+    }
+    Some(text[..1].to_uppercase() + &text[1..])
+}
+
+/// Whether an item is marked `#[doc(hidden)]`, only documented with `--document-hidden-items`
+fn is_hidden(item: &Value) -> bool {
+    item["attrs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|attr| attr.as_str().or_else(|| attr["other"].as_str()) == Some("#[doc(hidden)]"))
+}
+
+/// Ranks how well the name at the end of `path` matches `query`, lower is better: exact match,
+/// prefix, substring and then subsequence ignoring case, shorter paths first
+pub(crate) fn match_score(query: &str, path: &str) -> Option<(u8, usize)> {
+    let name = path.rsplit("::").next().unwrap_or(path).to_lowercase();
+    let query = query.to_lowercase();
+    let tier = if name == query {
+        0
+    } else if name.starts_with(&query) {
+        1
+    } else if name.contains(&query) {
+        2
+    } else {
+        let mut chars = name.chars();
+        if !query.chars().all(|c| chars.any(|n| n == c)) {
+            return None;
+        }
+        3
+    };
+    Some((tier, path.len()))
+}
+
+fn visibility(visibility: &Value) -> String {
+    match visibility {
+        Value::String(visibility) if visibility == "public" => "pub ".to_owned(),
+        Value::String(visibility) if visibility == "crate" => "pub(crate) ".to_owned(),
+        Value::Object(restricted) => format!(
+            "pub(in {}) ",
+            restricted
+                .get("restricted")
+                .and_then(|restricted| restricted["path"].as_str())
+                .unwrap_or_default()
+        ),
+        _ => String::new(),
+    }
+}
+
+fn abi(abi: &Value) -> String {
+    match abi {
+        Value::String(abi) if abi == "Rust" => String::new(),
+        Value::String(abi) => format!("extern \"{abi}\" "),
+        Value::Object(abi) => match abi.keys().next() {
+            Some(name) => format!("extern \"{name}\" "),
+            None => String::new(),
+        },
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A crate with a private `mutex` module re-exported from `sync`:
+    ///
+    /// ```ignore
+    /// pub mod sync {
+    ///     mod mutex {
+    ///         /// An async mutex
+    ///         pub struct Mutex<T: ?Sized> { .. }
+    ///         impl<T: ?Sized> Mutex<T> {
+    ///             /// Locks the mutex
+    ///             pub async fn lock(&self) -> MutexGuard<'_, T> { .. }
+    ///         }
+    ///         impl<T: Default> Default for Mutex<T> { .. }
+    ///     }
+    ///     pub use mutex::Mutex;
+    /// }
+    /// ```
+    fn sample() -> RustdocIndex {
+        let generic_t = json!({
+            "name": "T",
+            "kind": { "type": {
+                "bounds": [{ "trait_bound": { "trait": { "path": "Sized", "id": 90, "args": null }, "generic_params": [], "modifier": "maybe" } }],
+                "default": null,
+                "is_synthetic": false,
+            } },
+        });
+        let krate = json!({
+            "root": 0,
+            "format_version": 57,
+            "crate_version": "1.0.0",
+            "paths": { "90": { "crate_id": 1, "path": ["core", "marker", "Sized"], "kind": "trait" } },
+            "index": {
+                "0": { "name": "demo", "visibility": "public", "docs": null, "inner": { "module": { "items": [1] } } },
+                "1": { "name": "sync", "visibility": "public", "docs": "Synchronization primitives", "inner": { "module": { "items": [2, 3] } } },
+                "2": { "name": "mutex", "visibility": "default", "docs": null, "inner": { "module": { "items": [4] } } },
+                "3": { "name": null, "visibility": "public", "docs": null, "inner": { "use": { "source": "mutex::Mutex", "name": "Mutex", "id": 4, "is_glob": false } } },
+                "4": {
+                    "name": "Mutex", "visibility": "public", "docs": "An async mutex\n\nMore details.",
+                    "inner": { "struct": {
+                        "kind": { "plain": { "fields": [], "has_stripped_fields": true } },
+                        "generics": { "params": [generic_t], "where_predicates": [] },
+                        "impls": [5, 7],
+                    } },
+                },
+                "5": {
+                    "name": null, "visibility": "default", "docs": null,
+                    "inner": { "impl": {
+                        "is_unsafe": false, "generics": { "params": [generic_t], "where_predicates": [] },
+                        "trait": null, "for": { "resolved_path": { "path": "Mutex", "id": 4, "args": { "angle_bracketed": { "args": [{ "type": { "generic": "T" } }], "constraints": [] } } } },
+                        "items": [6], "is_negative": false, "is_synthetic": false, "blanket_impl": null,
+                    } },
+                },
+                "6": {
+                    "name": "lock", "visibility": "public", "docs": "Locks the mutex",
+                    "inner": { "function": {
+                        "sig": {
+                            "inputs": [["self", { "borrowed_ref": { "lifetime": null, "is_mutable": false, "type": { "generic": "Self" } } }]],
+                            "output": { "resolved_path": { "path": "MutexGuard", "id": 8, "args": { "angle_bracketed": {
+                                "args": [{ "lifetime": "'_" }, { "type": { "generic": "T" } }], "constraints": [],
+                            } } } },
+                            "is_c_variadic": false,
+                        },
+                        "generics": { "params": [], "where_predicates": [] },
+                        "header": { "is_const": false, "is_unsafe": false, "is_async": true, "abi": "Rust" },
+                        "has_body": true,
+                    } },
+                },
+                "7": {
+                    "name": null, "visibility": "default", "docs": null,
+                    "inner": { "impl": {
+                        "is_unsafe": false,
+                        "generics": { "params": [{ "name": "T", "kind": { "type": {
+                            "bounds": [{ "trait_bound": { "trait": { "path": "Default", "id": 91, "args": null }, "generic_params": [], "modifier": "none" } }],
+                            "default": null, "is_synthetic": false,
+                        } } }], "where_predicates": [] },
+                        "trait": { "path": "Default", "id": 91, "args": null },
+                        "for": { "resolved_path": { "path": "Mutex", "id": 4, "args": { "angle_bracketed": { "args": [{ "type": { "generic": "T" } }], "constraints": [] } } } },
+                        "items": [], "is_negative": false, "is_synthetic": false, "blanket_impl": null,
+                    } },
+                },
+            },
+        });
+        RustdocIndex::new(serde_json::from_value(krate).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_format_version() {
+        let krate = |format_version: Value| {
+            serde_json::from_value::<RawCrate>(json!({
+                "root": 0,
+                "format_version": format_version,
+                "index": { "0": { "name": "demo", "visibility": "public", "inner": { "module": { "items": [] } } } },
+            }))
+            .unwrap()
+        };
+        let error = |format_version| {
+            RustdocIndex::new(krate(format_version))
+                .err()
+                .unwrap()
+                .message
+                .into_owned()
+        };
+
+        assert!(RustdocIndex::new(krate(json!(FORMAT_VERSIONS.end()))).is_ok());
+        assert!(RustdocIndex::new(krate(json!(FORMAT_VERSIONS.end() + 1))).is_ok());
+        assert_eq!(
+            error(json!(28)),
+            "Unsupported rustdoc JSON format version 28: generated by an older toolchain, update the nightly toolchain, supported versions are 34 to 61"
+        );
+        assert!(error(Value::Null).contains("not a rustdoc JSON file"));
+    }
+
+    #[test]
+    fn test_query() {
+        let index = sample();
+        assert_eq!(
+            index.query("demo::sync", None).unwrap(),
+            "Structs:\n- Mutex: An async mutex\n"
+        );
+        assert_eq!(
+            index.query("demo::sync::Mutex::lock", None).unwrap(),
+            "function demo::sync::Mutex::lock\n\n```rust\npub async fn lock(&self) -> MutexGuard<'_, T>\n```\n\nLocks the mutex\n"
+        );
+        assert_eq!(
+            index.query("sync::Mutex", Some(Query::Items)).unwrap(),
+            "- `pub async fn lock(&self) -> MutexGuard<'_, T>`: Locks the mutex\n"
+        );
+        assert_eq!(
+            index
+                .query("demo::sync::Mutex", Some(Query::Impls))
+                .unwrap(),
+            "Trait implementations for demo::sync::Mutex:\n- `impl<T: Default> Default for Mutex<T>`\n"
+        );
+        assert!(
+            index
+                .query("demo::sync::Mutex", None)
+                .unwrap()
+                .starts_with("struct demo::sync::Mutex\n\n```rust\npub struct Mutex<T: ?Sized> {\n    /* private fields */\n}\n```\n\nAn async mutex")
+        );
+
+        let error = index.query("demo::mutex::Mutex", None).unwrap_err();
+        assert_eq!(
+            error.message,
+            "`demo::mutex::Mutex` not found in the public API of `demo`, similar paths: demo::sync::Mutex"
+        );
+    }
+}
@@ -0,0 +1,714 @@
+use std::fmt::Write as _;
+
+use rmcp::model::{AnnotateAble, Annotations, RawContent, RawTextContent, Role};
+
+use crate::{
+    Response,
+    command::{Output, Stderr, Stdout},
+    meta::Meta,
+};
+
+/// Test binary arguments that make libtest print JSON events, only accepted by nightly toolchains
+pub(crate) const LIBTEST_JSON_ARGS: &[&str] = &[
+    "-Z",
+    "unstable-options",
+    "--format",
+    "json",
+    "--report-time",
+];
+
+/// Cargo status lines that are dropped from stderr when the client asked for quiet output
+const CARGO_STATUSES: &[&str] = &[
+    "Compiling",
+    "Finished",
+    "Running",
+    "Doc-tests",
+    "Downloading",
+    "Downloaded",
+    "Blocking",
+    "Locking",
+    "Updating",
+];
+
+/// Results of `cargo test`, grouped by test binary
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct TestReport {
+    /// Number of passed tests in all test binaries
+    passed: usize,
+    /// Number of failed tests in all test binaries
+    failed: usize,
+    /// Number of ignored tests in all test binaries
+    ignored: usize,
+    /// One entry per test binary: unit tests, integration tests and doc tests
+    suites: Vec<TestSuite>,
+}
+
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct TestSuite {
+    /// Test binary, e.g. "unittests src/lib.rs", "tests/api.rs" or "Doc-tests my_crate"
+    name: String,
+    /// Number of passed tests
+    passed: usize,
+    /// Number of failed tests
+    failed: usize,
+    /// Number of ignored tests
+    ignored: usize,
+    /// Number of tests that didn't match the name filter
+    filtered_out: usize,
+    /// Time it took to run the binary, missing if it didn't finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_secs: Option<f64>,
+    /// Results of the individual tests in the order they finished
+    tests: Vec<TestResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct TestResult {
+    /// Full test name, e.g. "tests::parses_empty_input" or "src/lib.rs - add (line 3)"
+    name: String,
+    status: TestStatus,
+    /// Time it took to run the test, only reported with nightly toolchains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_secs: Option<f64>,
+    /// Panic message and location if the test panicked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    panic: Option<String>,
+    /// Output captured while the test ran, reported for failed tests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stdout: Option<String>,
+    /// Reason given in `#[ignore = "..."]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ignore_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TestStatus {
+    Ok,
+    Failed,
+    Ignored,
+}
+
+impl TestResult {
+    fn new(name: impl Into<String>, status: TestStatus) -> Self {
+        TestResult {
+            name: name.into(),
+            status,
+            duration_secs: None,
+            panic: None,
+            stdout: None,
+            ignore_reason: None,
+        }
+    }
+
+    fn set_stdout(&mut self, stdout: &str) {
+        let stdout = stdout.trim_end();
+        if stdout.is_empty() {
+            return;
+        }
+        self.panic = extract_panic(stdout);
+        self.stdout = Some(stdout.to_owned());
+    }
+}
+
+/// Finds the `thread '...' panicked at ...` message in the captured output, without the backtrace
+fn extract_panic(stdout: &str) -> Option<String> {
+    let start = stdout
+        .lines()
+        .position(|line| line.starts_with("thread '") && line.contains(" panicked at "))?;
+    let panic: Vec<_> = stdout
+        .lines()
+        .skip(start)
+        .take_while(|line| !line.starts_with("note: ") && !line.starts_with("stack backtrace:"))
+        .collect();
+    Some(panic.join("\n").trim_end().to_owned())
+}
+
+/// Event printed by libtest with `--format json`
+#[derive(serde::Deserialize)]
+struct LibtestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    event: String,
+    name: Option<String>,
+    #[serde(default)]
+    passed: usize,
+    #[serde(default)]
+    failed: usize,
+    #[serde(default)]
+    ignored: usize,
+    #[serde(default)]
+    filtered_out: usize,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+    message: Option<String>,
+}
+
+/// Where the text parser is within the output of a test binary
+enum Section {
+    Results,
+    /// `failures:` or `successes:` with the captured output of the named test
+    CapturedOutput(Option<String>, String),
+    /// The list of failed test names printed after their output
+    FailureList,
+}
+
+impl TestReport {
+    /// Parses the libtest output out of stdout, naming the test binaries after cargo's
+    /// `Running` lines in stderr.
+    ///
+    /// Lines that are not test results (e.g. printed by tests with `--nocapture`) are left in stdout.
+    pub(crate) fn take_from(output: &mut Output, strip_cargo_status: bool) -> Self {
+        let mut report = TestReport::default();
+        let stderr = output.stderr.take().map(|s| s.0).unwrap_or_default();
+        let mut suite_names = stderr.lines().filter_map(|line| {
+            let line = line.trim();
+            if let Some(binary) = line.strip_prefix("Running ") {
+                // "unittests src/lib.rs (target/debug/deps/foo-0123456789abcdef)"
+                Some(binary.rsplit_once(" (").map_or(binary, |(name, _)| name))
+            } else if line.starts_with("Doc-tests ") {
+                Some(line)
+            } else {
+                None
+            }
+        });
+
+        let stdout = output.stdout.take().map(|s| s.0).unwrap_or_default();
+        let mut other_lines = Vec::new();
+        let mut section = Section::Results;
+        let mut pending: Option<String> = None;
+        for line in stdout.lines() {
+            if line.starts_with('{')
+                && let Ok(event) = serde_json::from_str::<LibtestEvent>(line)
+            {
+                report.push_event(event, &mut suite_names);
+                continue;
+            }
+
+            if line.starts_with("running ") && (line.ends_with(" test") || line.ends_with(" tests"))
+            {
+                report.start_suite(&mut suite_names);
+                section = Section::Results;
+                continue;
+            }
+            if let Some(summary) = line.strip_prefix("test result: ") {
+                report.finish_suite_from_text(summary, &mut section);
+                continue;
+            }
+
+            match &mut section {
+                Section::Results => {
+                    if let Some(test) = line.strip_prefix("test ") {
+                        match test.rsplit_once(" ...") {
+                            Some((name, result)) if !result.trim().is_empty() => {
+                                report.push_text_result(name, result.trim());
+                            }
+                            Some((name, _)) => pending = Some(name.to_owned()),
+                            None => other_lines.push(line),
+                        }
+                    } else if let Some(name) = pending.take_if(|_| parse_status(line).is_some()) {
+                        report.push_text_result(&name, line);
+                    } else if line == "failures:" || line == "successes:" {
+                        section = Section::CapturedOutput(None, String::new());
+                    } else if !line.is_empty() {
+                        other_lines.push(line);
+                    }
+                }
+                Section::CapturedOutput(current, captured) => {
+                    if let Some(name) = line
+                        .strip_prefix("---- ")
+                        .and_then(|l| l.strip_suffix(" stdout ----"))
+                    {
+                        report.attach_output(current.take(), captured);
+                        *current = Some(name.to_owned());
+                    } else if line == "failures:" || line == "successes:" {
+                        report.attach_output(current.take(), captured);
+                        section = Section::FailureList;
+                    } else if current.is_some() {
+                        captured.push_str(line);
+                        captured.push('\n');
+                    }
+                }
+                Section::FailureList => {
+                    // only needed when libtest printed dots instead of the results
+                    if let Some(name) = line.strip_prefix("    ") {
+                        report.ensure_failed(name);
+                    }
+                }
+            }
+        }
+        if let Section::CapturedOutput(current, captured) = &mut section {
+            report.attach_output(current.take(), captured);
+        }
+
+        if !other_lines.is_empty() {
+            output.stdout = Some(Stdout(other_lines.join("\n")));
+        }
+        let stderr = if strip_cargo_status {
+            stderr
+                .lines()
+                .filter(|line| {
+                    let line = line.trim_start();
+                    !CARGO_STATUSES.iter().any(|status| {
+                        line.strip_prefix(status)
+                            .is_some_and(|rest| rest.starts_with(' '))
+                    })
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            stderr
+        };
+        if !stderr.trim().is_empty() {
+            output.stderr = Some(Stderr(stderr));
+        }
+        report
+    }
+
+    /// Starts the results of the next test binary, its name is filled in later if cargo didn't print it
+    fn start_suite<'a>(&mut self, names: &mut impl Iterator<Item = &'a str>) {
+        self.suites.push(TestSuite {
+            name: names.next().map(str::to_owned).unwrap_or_default(),
+            ..TestSuite::default()
+        });
+    }
+
+    fn current_suite(&mut self) -> &mut TestSuite {
+        if self.suites.is_empty() {
+            self.start_suite(&mut std::iter::empty());
+        }
+        self.suites.last_mut().expect("suite was just added")
+    }
+
+    fn push_event<'a>(&mut self, event: LibtestEvent, names: &mut impl Iterator<Item = &'a str>) {
+        match (event.kind.as_str(), event.event.as_str()) {
+            ("suite", "started") => self.start_suite(names),
+            ("suite", "ok" | "failed") => {
+                let suite = self.current_suite();
+                suite.passed = event.passed;
+                suite.failed = event.failed;
+                suite.ignored = event.ignored;
+                suite.filtered_out = event.filtered_out;
+                suite.duration_secs = event.exec_time;
+            }
+            ("test", status) => {
+                let status = match status {
+                    "ok" => TestStatus::Ok,
+                    "failed" | "timeout" => TestStatus::Failed,
+                    "ignored" => TestStatus::Ignored,
+                    _ => return,
+                };
+                let mut name = event.name.unwrap_or_default();
+                // nextest names its tests `<binary-id>$<test name>`
+                if let Some((binary_id, test_name)) = name.split_once('$') {
+                    let suite = self.current_suite();
+                    if suite.name.is_empty() {
+                        suite.name = binary_id.to_owned();
+                    }
+                    name = test_name.to_owned();
+                }
+                let mut result = TestResult::new(name, status);
+                result.duration_secs = event.exec_time;
+                if let Some(stdout) = &event.stdout {
+                    result.set_stdout(stdout);
+                }
+                if status == TestStatus::Ignored {
+                    result.ignore_reason = event.message;
+                }
+                self.current_suite().tests.push(result);
+            }
+            _ => {}
+        }
+    }
+
+    fn push_text_result(&mut self, name: &str, result: &str) {
+        let Some(status) = parse_status(result) else {
+            return;
+        };
+        let mut test = TestResult::new(name, status);
+        if status == TestStatus::Ignored {
+            test.ignore_reason = result.strip_prefix("ignored, ").map(str::to_owned);
+        }
+        self.current_suite().tests.push(test);
+    }
+
+    fn attach_output(&mut self, name: Option<String>, captured: &mut String) {
+        let captured = std::mem::take(captured);
+        let Some(name) = name else {
+            return;
+        };
+        self.ensure_failed(&name);
+        if let Some(test) = self
+            .current_suite()
+            .tests
+            .iter_mut()
+            .find(|test| test.name == name)
+        {
+            test.set_stdout(&captured);
+        }
+    }
+
+    /// Adds a failed test that only shows up in the failure list
+    fn ensure_failed(&mut self, name: &str) {
+        let suite = self.current_suite();
+        if !suite.tests.iter().any(|test| test.name == name) {
+            suite.tests.push(TestResult::new(name, TestStatus::Failed));
+        }
+    }
+
+    /// Parses `ok. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.02s`
+    fn finish_suite_from_text(&mut self, summary: &str, section: &mut Section) {
+        if let Section::CapturedOutput(current, captured) = section {
+            self.attach_output(current.take(), captured);
+        }
+        *section = Section::Results;
+
+        let suite = self.current_suite();
+        let counts = summary
+            .split_once(". ")
+            .map_or(summary, |(_, counts)| counts);
+        for part in counts.split("; ") {
+            if let Some(secs) = part
+                .strip_prefix("finished in ")
+                .and_then(|s| s.strip_suffix('s'))
+            {
+                suite.duration_secs = secs.parse().ok();
+                continue;
+            }
+            let Some((count, what)) = part.split_once(' ') else {
+                continue;
+            };
+            let Ok(count) = count.parse() else {
+                continue;
+            };
+            match what {
+                "passed" => suite.passed = count,
+                "failed" => suite.failed = count,
+                "ignored" => suite.ignored = count,
+                "filtered out" => suite.filtered_out = count,
+                _ => {}
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        for (i, suite) in self.suites.iter_mut().enumerate() {
+            if suite.name.is_empty() {
+                suite.name = format!("test binary #{}", i + 1);
+            }
+        }
+        self.passed = self.suites.iter().map(|s| s.passed).sum();
+        self.failed = self.suites.iter().map(|s| s.failed).sum();
+        self.ignored = self.suites.iter().map(|s| s.ignored).sum();
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.suites.is_empty()
+    }
+
+    /// Compact text rendering with the failed tests first
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::new();
+        for suite in &self.suites {
+            for test in suite
+                .tests
+                .iter()
+                .filter(|t| t.status == TestStatus::Failed)
+            {
+                let _ = writeln!(out, "FAILED {} ({})", test.name, suite.name);
+                if let Some(details) = test.panic.as_ref().or(test.stdout.as_ref()) {
+                    for line in details.lines() {
+                        let _ = writeln!(out, "  {line}");
+                    }
+                }
+            }
+        }
+
+        for suite in &self.suites {
+            let _ = write!(
+                out,
+                "{}: {} passed, {} failed, {} ignored",
+                suite.name, suite.passed, suite.failed, suite.ignored
+            );
+            if suite.filtered_out > 0 {
+                let _ = write!(out, ", {} filtered out", suite.filtered_out);
+            }
+            match suite.duration_secs {
+                Some(secs) => {
+                    let _ = writeln!(out, " ({secs:.2}s)");
+                }
+                None => out.push_str(" (did not finish)\n"),
+            }
+        }
+        let _ = write!(
+            out,
+            "Total: {} passed, {} failed, {} ignored",
+            self.passed, self.failed, self.ignored
+        );
+        out
+    }
+
+    /// Adds the report to the response both as structured content and as text
+    pub(crate) fn attach_to(mut self, response: &mut Response) {
+        self.finish();
+        if !self.is_empty() {
+            let content = RawContent::Text(RawTextContent {
+                text: self.render(),
+                meta: Some(Meta::new().with_description("test results").into()),
+            });
+            let mut annotations = Annotations::default();
+            annotations.audience = Some(vec![Role::User, Role::Assistant]);
+            annotations.priority = Some(1.);
+            response.add_content(content.annotate(annotations));
+        }
+        response.set_structured_content(&self);
+    }
+}
+
+fn parse_status(result: &str) -> Option<TestStatus> {
+    match result {
+        "ok" => Some(TestStatus::Ok),
+        "FAILED" => Some(TestStatus::Failed),
+        r if r == "ignored" || r.starts_with("ignored, ") => Some(TestStatus::Ignored),
+        r if r.starts_with("bench:") => Some(TestStatus::Ok),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::{CommandLine, ExitStatus};
+
+    use super::*;
+
+    const STDERR: &str = "   Compiling tproj v0.1.0 (/tmp/tproj)
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.52s
+     Running unittests src/lib.rs (target/debug/deps/tproj-e8bb00aca4fa22b6)
+error: test failed, to rerun pass `--lib`
+     Running tests/it.rs (target/debug/deps/it-271324b1dc75c57a)
+   Doc-tests tproj
+error: doctest failed, to rerun pass `--doc`";
+
+    const PRETTY_STDOUT: &str = "
+running 3 tests
+test tests::fails ... FAILED
+test tests::ignored ... ignored, slow
+test tests::passes ... ok
+
+failures:
+
+---- tests::fails stdout ----
+some output
+
+thread 'tests::fails' (19981) panicked at src/lib.rs:13:43:
+assertion `left == right` failed: math is broken
+  left: 3
+ right: 4
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::fails
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.02s
+
+
+running 1 test
+test integ ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+
+running 1 test
+test src/lib.rs - add (line 3) ... FAILED
+
+failures:
+
+---- src/lib.rs - add (line 3) stdout ----
+Test executable failed (exit status: 101).
+
+stderr:
+
+thread 'main' (20008) panicked at /tmp/rustdoctest5vM9gY/doctest_bundle_2024.rs:6:1:
+assertion `left == right` failed
+  left: 2
+ right: 3
+stack backtrace:
+   0: __rustc::rust_begin_unwind
+
+
+
+failures:
+    src/lib.rs - add (line 3)
+
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.02s
+
+all doctests ran in 0.31s; merged doctests compilation took 0.29s";
+
+    const JSON_STDOUT: &str = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::fails" }
+{ "type": "test", "name": "tests::fails", "event": "failed", "exec_time": 0.000050078, "stdout": "some output\n\nthread 'tests::fails' (20099) panicked at src/lib.rs:13:43:\nassertion `left == right` failed: math is broken\n  left: 3\n right: 4\nnote: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n" }
+{ "type": "test", "event": "started", "name": "tests::ignored" }
+{ "type": "test", "name": "tests::ignored", "event": "ignored", "message": "slow" }
+{ "type": "test", "event": "started", "name": "tests::passes" }
+{ "type": "test", "name": "tests::passes", "event": "ok", "exec_time": 0.000000318 }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.000351295 }
+{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "event": "started", "name": "integ" }
+{ "type": "test", "name": "integ", "event": "ok", "exec_time": 0.000000623 }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.000178785 }
+{ "type": "report", "total_time": 0.312603686, "compilation_time": 0.293079146 }"#;
+
+    fn output(stdout: &str) -> Output {
+        Output {
+            tool_name: "cargo-test".into(),
+            cmd_line: CommandLine("cargo test".into()),
+            stdout: Some(Stdout(stdout.into())),
+            stderr: Some(Stderr(STDERR.into())),
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
+        }
+    }
+
+    #[test]
+    fn test_parse_pretty_output() {
+        let mut output = output(PRETTY_STDOUT);
+        let mut report = TestReport::take_from(&mut output, true);
+        report.finish();
+
+        assert_eq!((report.passed, report.failed, report.ignored), (2, 2, 1));
+        let names: Vec<_> = report.suites.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["unittests src/lib.rs", "tests/it.rs", "Doc-tests tproj"]
+        );
+
+        let unit = &report.suites[0];
+        assert_eq!(unit.duration_secs, Some(0.02));
+        assert_eq!(unit.tests.len(), 3);
+        assert_eq!(unit.tests[1].ignore_reason.as_deref(), Some("slow"));
+        assert_eq!(
+            unit.tests[0].panic.as_deref(),
+            Some(
+                "thread 'tests::fails' (19981) panicked at src/lib.rs:13:43:\nassertion `left == right` failed: math is broken\n  left: 3\n right: 4"
+            )
+        );
+        assert!(
+            unit.tests[0]
+                .stdout
+                .as_deref()
+                .unwrap()
+                .starts_with("some output")
+        );
+
+        let doc_test = &report.suites[2].tests[0];
+        assert_eq!(doc_test.status, TestStatus::Failed);
+        assert!(doc_test.panic.as_deref().unwrap().ends_with(" right: 3"));
+
+        assert_eq!(
+            output.stdout.map(|s| s.0).as_deref(),
+            Some("all doctests ran in 0.31s; merged doctests compilation took 0.29s")
+        );
+        assert_eq!(
+            output.stderr.map(|s| s.0).as_deref(),
+            Some(
+                "error: test failed, to rerun pass `--lib`\nerror: doctest failed, to rerun pass `--doc`"
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_json_output() {
+        let mut output = output(JSON_STDOUT);
+        let mut report = TestReport::take_from(&mut output, false);
+        report.finish();
+
+        assert!(output.stdout.is_none());
+
+        assert_eq!((report.passed, report.failed, report.ignored), (2, 1, 1));
+        let unit = &report.suites[0];
+        assert_eq!(unit.name, "unittests src/lib.rs");
+        assert_eq!(unit.tests[0].duration_secs, Some(0.000050078));
+        assert_eq!(unit.tests[1].ignore_reason.as_deref(), Some("slow"));
+        assert!(
+            unit.tests[0]
+                .panic
+                .as_deref()
+                .unwrap()
+                .ends_with(" right: 4")
+        );
+        assert_eq!(
+            report.suites[1].tests,
+            [TestResult {
+                duration_secs: Some(0.000000623),
+                ..TestResult::new("integ", TestStatus::Ok)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_nextest_json_output() {
+        let stdout = r#"{"type":"suite","event":"started","test_count":2}
+{"type":"test","event":"started","name":"tproj::it$integ"}
+{"type":"test","event":"ok","name":"tproj::it$integ","exec_time":0.002}
+{"type":"test","event":"started","name":"tproj::it$flaky"}
+{"type":"test","event":"failed","name":"tproj::it$flaky","exec_time":0.003,"stdout":"thread 'flaky' panicked at tests/it.rs:9:5:\nexplicit panic\n"}
+{"type":"suite","event":"failed","passed":1,"failed":1,"ignored":0,"measured":0,"filtered_out":0,"exec_time":0.005}"#;
+        let mut output = output(stdout);
+        output.stderr = None;
+        let mut report = TestReport::take_from(&mut output, false);
+        report.finish();
+
+        let suite = &report.suites[0];
+        assert_eq!(suite.name, "tproj::it");
+        assert_eq!(suite.tests[0].name, "integ");
+        assert_eq!(
+            suite.tests[1].panic.as_deref(),
+            Some("thread 'flaky' panicked at tests/it.rs:9:5:\nexplicit panic")
+        );
+        assert_eq!((report.passed, report.failed), (1, 1));
+    }
+
+    #[test]
+    fn test_render_failed_first() {
+        let mut report = TestReport::take_from(&mut output(PRETTY_STDOUT), true);
+        report.finish();
+
+        assert_eq!(
+            report.render(),
+            "FAILED tests::fails (unittests src/lib.rs)
+  thread 'tests::fails' (19981) panicked at src/lib.rs:13:43:
+  assertion `left == right` failed: math is broken
+    left: 3
+   right: 4
+FAILED src/lib.rs - add (line 3) (Doc-tests tproj)
+  thread 'main' (20008) panicked at /tmp/rustdoctest5vM9gY/doctest_bundle_2024.rs:6:1:
+  assertion `left == right` failed
+    left: 2
+   right: 3
+unittests src/lib.rs: 1 passed, 1 failed, 1 ignored (0.02s)
+tests/it.rs: 1 passed, 0 failed, 0 ignored (0.00s)
+Doc-tests tproj: 0 passed, 1 failed, 0 ignored (0.02s)
+Total: 2 passed, 2 failed, 1 ignored"
+        );
+    }
+
+    #[test]
+    fn test_parse_interrupted_output() {
+        // `--nocapture` output between the test name and its result, then a timeout
+        let stdout = "running 2 tests\ntest slow ... \nprinted by the test\nok\ntest hangs ... ";
+        let mut output = output(stdout);
+        let mut report = TestReport::take_from(&mut output, true);
+        report.finish();
+
+        assert_eq!(
+            report.suites[0].tests,
+            [TestResult::new("slow", TestStatus::Ok)]
+        );
+        assert_eq!(report.suites[0].duration_secs, None);
+        assert_eq!(
+            output.stdout.map(|s| s.0).as_deref(),
+            Some("printed by the test")
+        );
+    }
+}
@@ -1,8 +1,216 @@
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    time::Duration,
+};
+
 use rmcp::ErrorData;
-use rmcp::model::CallToolRequestParams;
+use rmcp::model::{CallToolRequestParams, JsonObject, ToolAnnotations};
 use schemars::JsonSchema;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    Response,
+    audit::{self, CallStart, Invocation},
+    command::TIMEOUT_SECS_PARAM,
+    confirmation::Confirmation,
+    globals,
+    output_store::MAX_OUTPUT_BYTES_PARAM,
+    progress::ProgressReporter,
+    workspace::{SessionWorkspaces, WORKSPACE_ROOT_PARAM},
+};
+
+pub(crate) type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Per-call state shared with the commands spawned by a tool
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ToolContext {
+    ct: CancellationToken,
+    timeout: Option<Duration>,
+    progress: Option<ProgressReporter>,
+    confirmation: Option<Confirmation>,
+    workspace_root: Option<PathBuf>,
+    workspaces: SessionWorkspaces,
+    max_output_bytes: Option<usize>,
+}
+
+impl ToolContext {
+    pub(crate) fn new(ct: CancellationToken) -> Self {
+        Self {
+            ct,
+            timeout: globals::get_default_timeout(),
+            progress: None,
+            confirmation: None,
+            workspace_root: None,
+            workspaces: SessionWorkspaces::default(),
+            max_output_bytes: globals::get_max_output_bytes().filter(|max| *max > 0),
+        }
+    }
+
+    pub(crate) fn with_progress(mut self, progress: Option<ProgressReporter>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub(crate) fn with_confirmation(mut self, confirmation: Confirmation) -> Self {
+        self.confirmation = Some(confirmation);
+        self
+    }
+
+    /// Uses the workspaces of the client session, starting in its default workspace root
+    pub(crate) fn with_workspaces(mut self, workspaces: SessionWorkspaces) -> Self {
+        self.workspace_root = workspaces.default_root();
+        self.workspaces = workspaces;
+        self
+    }
+
+    /// Overrides the default workspace root with the one requested by the client
+    pub(crate) fn with_workspace_root(mut self, workspace_root: PathBuf) -> Self {
+        self.workspace_root = Some(workspace_root);
+        self
+    }
+
+    /// Overrides the server default timeout with the one requested by the client.
+    /// `Some(0)` disables the timeout.
+    pub(crate) fn with_timeout_secs(mut self, timeout_secs: Option<u64>) -> Self {
+        match timeout_secs {
+            Some(0) => self.timeout = None,
+            Some(secs) => self.timeout = Some(Duration::from_secs(secs)),
+            None => {}
+        }
+        self
+    }
+
+    /// Overrides the server maximum output size with the one requested by the client.
+    /// `Some(0)` disables the limit.
+    pub(crate) fn with_max_output_bytes(mut self, max_output_bytes: Option<usize>) -> Self {
+        match max_output_bytes {
+            Some(0) => self.max_output_bytes = None,
+            Some(max) => self.max_output_bytes = Some(max),
+            None => {}
+        }
+        self
+    }
+
+    /// Size above which the middle of each output stream is elided from the result
+    pub(crate) fn max_output_bytes(&self) -> Option<usize> {
+        self.max_output_bytes
+    }
+
+    /// Maximum time a spawned command may run before it is killed
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
 
-use crate::Response;
+    /// Set if the client asked for progress notifications
+    pub(crate) fn progress(&self) -> Option<&ProgressReporter> {
+        self.progress.as_ref()
+    }
+
+    /// Directory the commands run in, the current directory if `None`
+    pub(crate) fn workspace_root(&self) -> Option<&Path> {
+        self.workspace_root.as_deref()
+    }
+
+    /// Workspaces of the client session the call belongs to
+    pub(crate) fn workspaces(&self) -> &SessionWorkspaces {
+        &self.workspaces
+    }
+
+    /// Set if the user may have to confirm the commands before they run
+    pub(crate) fn confirmation(&self) -> Option<&Confirmation> {
+        self.confirmation.as_ref()
+    }
+
+    /// Copy of the context for the helper commands a tool runs before it changes files itself,
+    /// which then asks for confirmation with [`Confirmation::confirm_changes`]
+    pub(crate) fn without_confirmation(&self) -> Self {
+        Self {
+            confirmation: None,
+            ..self.clone()
+        }
+    }
+
+    /// Token that is cancelled when the client cancels the request
+    pub(crate) fn cancellation_token(&self) -> &CancellationToken {
+        &self.ct
+    }
+}
+
+/// Side effects of a tool, published as MCP tool annotations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ToolHints {
+    /// Does not modify the workspace, the lockfile or the installed toolchains
+    pub(crate) read_only: bool,
+    /// May overwrite or delete existing data
+    pub(crate) destructive: bool,
+    /// Calling it again with the same arguments has no additional effect
+    pub(crate) idempotent: bool,
+    /// Talks to registries or other services outside of the machine
+    pub(crate) open_world: bool,
+    /// Only writes when an argument asks for it, the other calls stay available in read-only mode
+    pub(crate) writes_on_request: bool,
+}
+
+impl ToolHints {
+    pub(crate) const fn read_only() -> Self {
+        Self {
+            read_only: true,
+            destructive: false,
+            idempotent: true,
+            open_world: false,
+            writes_on_request: false,
+        }
+    }
+
+    pub(crate) const fn writes() -> Self {
+        Self {
+            read_only: false,
+            destructive: false,
+            idempotent: false,
+            open_world: false,
+            writes_on_request: false,
+        }
+    }
+
+    pub(crate) const fn destructive(mut self) -> Self {
+        self.destructive = true;
+        self
+    }
+
+    pub(crate) const fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    pub(crate) const fn open_world(mut self) -> Self {
+        self.open_world = true;
+        self
+    }
+
+    pub(crate) const fn on_request(mut self) -> Self {
+        self.writes_on_request = true;
+        self
+    }
+
+    /// Whether the tool is listed in read-only mode
+    pub(crate) fn available_read_only(&self) -> bool {
+        self.read_only || self.writes_on_request
+    }
+
+    pub(crate) fn to_annotations(self) -> ToolAnnotations {
+        let annotations = ToolAnnotations::new()
+            .read_only(self.read_only)
+            .idempotent(self.idempotent)
+            .open_world(self.open_world);
+        if self.read_only {
+            annotations
+        } else {
+            annotations.destructive(self.destructive)
+        }
+    }
+}
 
 /// Dyn compatible Tool trait
 pub(crate) trait DynTool {
@@ -10,7 +218,13 @@ pub(crate) trait DynTool {
     fn title(&self) -> &'static str;
     fn description(&self) -> &'static str;
     fn json_schema(&self) -> serde_json::Map<String, serde_json::Value>;
-    fn call_rmcp_tool(&self, request: CallToolRequestParams) -> Result<Response, ErrorData>;
+    fn output_schema(&self) -> Option<serde_json::Map<String, serde_json::Value>>;
+    fn hints(&self) -> ToolHints;
+    fn call_rmcp_tool(
+        &self,
+        request: CallToolRequestParams,
+        ctx: ToolContext,
+    ) -> BoxFuture<'_, Result<Response, ErrorData>>;
 }
 
 /// Actual trait that all tools must implement
@@ -18,14 +232,31 @@ pub(crate) trait Tool {
     const NAME: &'static str;
     const TITLE: &'static str;
     const DESCRIPTION: &'static str;
-    type RequestArgs: serde::de::DeserializeOwned + schemars::JsonSchema;
+    type RequestArgs: serde::de::DeserializeOwned + schemars::JsonSchema + Send;
+
+    /// Side effects of the tool with the arguments that change the most
+    const HINTS: ToolHints;
+
+    /// Side effects of a call with these arguments, tools that only write when asked to override it
+    fn request_hints(_request: &Self::RequestArgs) -> ToolHints {
+        Self::HINTS
+    }
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<Response, ErrorData>;
+    /// Schema of the `structuredContent` returned by the tool, `None` if it returns text only
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        None
+    }
+
+    fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> impl Future<Output = Result<Response, ErrorData>> + Send;
 }
 
 impl<T> DynTool for T
 where
-    T: Tool,
+    T: Tool + Sync,
 {
     fn name(&self) -> &'static str {
         T::NAME
@@ -40,23 +271,165 @@ where
     }
 
     fn json_schema(&self) -> serde_json::Map<String, serde_json::Value> {
-        json_schema_impl::<T::RequestArgs>()
+        let mut schema = json_schema_impl::<T::RequestArgs>();
+        if let Some(serde_json::Value::Object(properties)) = schema.get_mut("properties") {
+            properties.insert(
+                WORKSPACE_ROOT_PARAM.to_owned(),
+                serde_json::json!({
+                    "type": "string",
+                    "description": "Workspace to run in: the name or path of a workspace listed by workspace-list, \
+                        or another directory containing a Cargo.toml. Defaults to the server workspace root."
+                }),
+            );
+            properties.insert(
+                MAX_OUTPUT_BYTES_PARAM.to_owned(),
+                serde_json::json!({
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Maximum size in bytes of each output stream, the middle of longer streams is elided \
+                        and can be read with get-command-output. Defaults to the server `--max-output-bytes`, `0` disables the limit."
+                }),
+            );
+            properties.insert(
+                TIMEOUT_SECS_PARAM.to_owned(),
+                serde_json::json!({
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Maximum time in seconds each command is allowed to run. When exceeded, the process tree is \
+                        killed and the partial output is returned. Defaults to the server `--default-timeout`, `0` disables the limit."
+                }),
+            );
+        }
+        schema
     }
 
-    fn call_rmcp_tool(&self, request: CallToolRequestParams) -> Result<Response, ErrorData> {
-        let Some(args) = request.arguments else {
-            return Err(ErrorData::invalid_params("Missing tool arguments", None));
-        };
+    fn output_schema(&self) -> Option<serde_json::Map<String, serde_json::Value>> {
+        T::output_schema()
+    }
+
+    fn hints(&self) -> ToolHints {
+        T::HINTS
+    }
 
-        let args: T::RequestArgs = serde_json::from_value(args.into()).map_err(|e| {
-            ErrorData::invalid_params(format!("Failed to parse tool arguments: {e}"), None)
-        })?;
+    fn call_rmcp_tool(
+        &self,
+        request: CallToolRequestParams,
+        ctx: ToolContext,
+    ) -> BoxFuture<'_, Result<Response, ErrorData>> {
+        Box::pin(async move {
+            let Some(mut args) = request.arguments else {
+                return Err(ErrorData::invalid_params("Missing tool arguments", None));
+            };
 
-        self.call_rmcp_tool(args)
+            let start = CallStart::now();
+            let arguments = args.clone();
+            let (result, working_directory) = match call_context(&mut args, ctx) {
+                Ok(ctx) => {
+                    let working_directory = ctx
+                        .workspace_root()
+                        .map(Path::to_path_buf)
+                        .or_else(|| std::env::current_dir().ok());
+                    (call_with_context(self, args, ctx).await, working_directory)
+                }
+                Err(e) => (Err(e), None),
+            };
+            audit::record(Invocation::new(
+                T::NAME,
+                arguments,
+                working_directory,
+                &start,
+                &result,
+            ));
+            result
+        })
     }
 }
 
-fn json_schema_impl<T: JsonSchema>() -> serde_json::Map<String, serde_json::Value> {
+/// Applies the parameters every tool accepts to the call context
+fn call_context(args: &mut JsonObject, mut ctx: ToolContext) -> Result<ToolContext, ErrorData> {
+    match args.remove(WORKSPACE_ROOT_PARAM) {
+        None | Some(serde_json::Value::Null) => {}
+        Some(serde_json::Value::String(workspace)) => {
+            let root = ctx.workspaces().resolve(&workspace)?;
+            ctx = ctx.with_workspace_root(root);
+        }
+        Some(_) => {
+            return Err(ErrorData::invalid_params(
+                format!("`{WORKSPACE_ROOT_PARAM}` must be a string"),
+                None,
+            ));
+        }
+    }
+
+    match args.remove(MAX_OUTPUT_BYTES_PARAM) {
+        None | Some(serde_json::Value::Null) => {}
+        Some(value) => {
+            let max_output_bytes = value.as_u64().and_then(|max| usize::try_from(max).ok());
+            if max_output_bytes.is_none() {
+                return Err(ErrorData::invalid_params(
+                    format!("`{MAX_OUTPUT_BYTES_PARAM}` must be a non-negative integer"),
+                    None,
+                ));
+            }
+            ctx = ctx.with_max_output_bytes(max_output_bytes);
+        }
+    }
+
+    match args.remove(TIMEOUT_SECS_PARAM) {
+        None | Some(serde_json::Value::Null) => {}
+        Some(value) => {
+            let Some(timeout_secs) = value.as_u64() else {
+                return Err(ErrorData::invalid_params(
+                    format!("`{TIMEOUT_SECS_PARAM}` must be a non-negative integer"),
+                    None,
+                ));
+            };
+            ctx = ctx.with_timeout_secs(Some(timeout_secs));
+        }
+    }
+
+    Ok(ctx)
+}
+
+async fn call_with_context<T: Tool + Sync>(
+    tool: &T,
+    args: JsonObject,
+    mut ctx: ToolContext,
+) -> Result<Response, ErrorData> {
+    if globals::is_read_only()
+        && args.get("locking_mode").and_then(|mode| mode.as_str()) == Some("unlocked")
+    {
+        return Err(ErrorData::invalid_params(
+            "`locking_mode: unlocked` may update Cargo.lock and is not allowed in read-only mode",
+            None,
+        ));
+    }
+
+    let args: T::RequestArgs = serde_json::from_value(args.into()).map_err(|e| {
+        ErrorData::invalid_params(format!("Failed to parse tool arguments: {e}"), None)
+    })?;
+
+    let hints = T::request_hints(&args);
+    if globals::is_read_only() && !hints.read_only {
+        return Err(ErrorData::invalid_params(
+            format!(
+                "These arguments make `{}` modify the workspace, which is not allowed in read-only mode",
+                T::NAME
+            ),
+            None,
+        ));
+    }
+
+    if let Some(confirmation) = &mut ctx.confirmation {
+        confirmation.set_destructive(hints.destructive);
+    }
+    let max_output_bytes = ctx.max_output_bytes();
+    let mut response = Tool::call_rmcp_tool(tool, args, ctx).await?;
+    response.keep_output(max_output_bytes);
+    Ok(response)
+}
+
+pub(crate) fn json_schema_impl<T: JsonSchema>() -> serde_json::Map<String, serde_json::Value> {
     use schemars::schema_for;
     use serde_json::Value;
 
@@ -90,7 +463,9 @@ fn json_schema_impl<T: JsonSchema>() -> serde_json::Map<String, serde_json::Valu
 
 #[cfg(test)]
 mod tests {
-    use super::json_schema_impl;
+    use std::time::Duration;
+
+    use super::{ToolContext, call_context, json_schema_impl};
     use insta::assert_debug_snapshot;
 
     #[test]
@@ -173,4 +548,30 @@ mod tests {
             panic!("Expected value property to be an object");
         }
     }
+
+    #[test]
+    fn test_call_context_strips_generic_parameters() {
+        let serde_json::Value::Object(mut args) = serde_json::json!({
+            "package": "app",
+            "timeout_secs": 30,
+            "max_output_bytes": 0,
+        }) else {
+            unreachable!()
+        };
+        let ctx = call_context(&mut args, ToolContext::default()).unwrap();
+        assert_eq!(ctx.timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(ctx.max_output_bytes(), None);
+        assert_eq!(args.keys().collect::<Vec<_>>(), ["package"]);
+
+        let serde_json::Value::Object(mut args) = serde_json::json!({ "timeout_secs": 0 }) else {
+            unreachable!()
+        };
+        let ctx = ToolContext::default().with_timeout_secs(Some(60));
+        assert_eq!(call_context(&mut args, ctx).unwrap().timeout(), None);
+
+        let serde_json::Value::Object(mut args) = serde_json::json!({ "timeout_secs": -1 }) else {
+            unreachable!()
+        };
+        assert!(call_context(&mut args, ToolContext::default()).is_err());
+    }
 }
@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::{
-    Response, Tool, execute_command,
+    Response, Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{
         PackageWithVersion, deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
@@ -231,9 +231,16 @@ impl Tool for CargoAddRmcpTool {
     const TITLE: &'static str = "Add Rust dependency";
     const DESCRIPTION: &'static str = "Adds a dependency to a Rust project using cargo add.";
     type RequestArgs = CargoAddRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes().idempotent().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -354,9 +361,16 @@ impl Tool for CargoRemoveRmcpTool {
     const TITLE: &'static str = "Remove Rust dependency";
     const DESCRIPTION: &'static str = "Remove dependencies from a Cargo.toml manifest file.";
     type RequestArgs = CargoRemoveRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes().destructive().idempotent();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -454,6 +468,11 @@ mod tests {
       "description": "Path to Cargo.toml",
       "type": "string"
     },
+    "max_output_bytes": {
+      "description": "Maximum size in bytes of each output stream, the middle of longer streams is elided and can be read with get-command-output. Defaults to the server `--max-output-bytes`, `0` disables the limit.",
+      "minimum": 0,
+      "type": "integer"
+    },
     "no_default_features": {
       "default": null,
       "description": "Disable the default features",
@@ -507,6 +526,11 @@ mod tests {
       "description": "Package to modify, must be specified",
       "type": "string"
     },
+    "timeout_secs": {
+      "description": "Maximum time in seconds each command is allowed to run. When exceeded, the process tree is killed and the partial output is returned. Defaults to the server `--default-timeout`, `0` disables the limit.",
+      "minimum": 0,
+      "type": "integer"
+    },
     "toolchain": {
       "default": null,
       "description": "The toolchain to use, e.g., \"stable\" or \"nightly\".",
@@ -516,6 +540,10 @@ mod tests {
       "default": null,
       "description": "Optional version specification",
       "type": "string"
+    },
+    "workspace_root": {
+      "description": "Workspace to run in: the name or path of a workspace listed by workspace-list, or another directory containing a Cargo.toml. Defaults to the server workspace root.",
+      "type": "string"
     }
   },
   "required": [
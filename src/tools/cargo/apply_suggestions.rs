@@ -0,0 +1,501 @@
+use std::{
+    collections::BTreeMap,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    Response, Tool, ToolContext, ToolHints,
+    command::execute_command,
+    diagnostics::{Diagnostic, DiagnosticsReport, MESSAGE_FORMAT_JSON, glob_match},
+    serde_utils::{deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags},
+    tools::cargo::CargoCheckRmcpTool,
+};
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, Annotations, RawContent, Role},
+};
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct CargoApplySuggestionsRequest {
+    /// The toolchain to use, e.g., "stable" or "nightly".
+    #[serde(default, deserialize_with = "deserialize_string")]
+    toolchain: Option<String>,
+
+    /// Collect suggestions with `cargo clippy` (default) or only from the compiler with `cargo check`
+    #[serde(default)]
+    clippy: Option<bool>,
+
+    /// Only apply suggestions of diagnostics with these error codes or lint names, e.g. "unused_imports" or "clippy::*"
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    codes: Option<Vec<String>>,
+
+    /// Only apply suggestions of diagnostics in files matching these glob patterns, e.g. "src/parser/**"
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    files: Option<Vec<String>>,
+
+    /// Only apply suggestions of the diagnostics with these ids, as reported by cargo-check, cargo-build or cargo-clippy
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    ids: Option<Vec<String>>,
+
+    /// Also apply suggestions the compiler marks as `MaybeIncorrect`. They may not compile or may change behavior.
+    #[serde(default)]
+    include_maybe_incorrect: Option<bool>,
+
+    /// Only return the diff without changing any file
+    #[serde(default)]
+    dry_run: Option<bool>,
+
+    /// Package(s) to check
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    package: Option<Vec<String>>,
+
+    /// Check all packages in the workspace
+    #[serde(default)]
+    workspace: Option<bool>,
+
+    /// Check all targets (lib, bins, examples, tests, benches)
+    #[serde(default)]
+    all_targets: Option<bool>,
+
+    /// Space or comma separated list of features to activate
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    features: Option<Vec<String>>,
+
+    /// Activate all available features
+    #[serde(default)]
+    all_features: Option<bool>,
+
+    /// Do not activate the default feature
+    #[serde(default)]
+    no_default_features: Option<bool>,
+
+    /// Locking mode for dependency resolution.
+    ///
+    /// Valid options:
+    /// - "locked" (default): Assert that `Cargo.lock` will remain unchanged
+    /// - "unlocked": Allow `Cargo.lock` to be updated
+    /// - "offline": Run without accessing the network
+    /// - "frozen": Equivalent to specifying both --locked and --offline
+    #[serde(default, deserialize_with = "deserialize_string")]
+    locking_mode: Option<String>,
+}
+
+impl CargoApplySuggestionsRequest {
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        if let Some(toolchain) = &self.toolchain {
+            cmd.arg(format!("+{toolchain}"));
+        }
+        cmd.arg(if self.clippy.unwrap_or(true) {
+            "clippy"
+        } else {
+            "check"
+        });
+
+        if let Some(packages) = &self.package {
+            for package in packages {
+                cmd.arg("--package").arg(package);
+            }
+        }
+
+        if self.workspace.unwrap_or(false) {
+            cmd.arg("--workspace");
+        }
+
+        if self.all_targets.unwrap_or(false) {
+            cmd.arg("--all-targets");
+        }
+
+        if let Some(features) = &self.features {
+            cmd.arg("--features").arg(features.join(","));
+        }
+
+        if self.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+
+        if self.no_default_features.unwrap_or(false) {
+            cmd.arg("--no-default-features");
+        }
+
+        let locking_flags = locking_mode_to_cli_flags(self.locking_mode.as_deref(), "locked")?;
+        cmd.args(locking_flags);
+
+        cmd.arg("--quiet").arg(MESSAGE_FORMAT_JSON);
+        Ok(cmd)
+    }
+
+    fn build_locate_project_cmd(&self) -> Command {
+        let mut cmd = Command::new("cargo");
+        cmd.args(["locate-project", "--workspace", "--message-format", "plain"]);
+        cmd
+    }
+
+    fn selects(&self, diagnostic: &Diagnostic) -> bool {
+        if let Some(ids) = &self.ids
+            && !ids.iter().any(|id| id == diagnostic.id())
+        {
+            return false;
+        }
+        if let Some(codes) = &self.codes {
+            let code = diagnostic.code().unwrap_or_default();
+            if !codes.iter().any(|pattern| glob_match(pattern, code)) {
+                return false;
+            }
+        }
+        if let Some(files) = &self.files {
+            let Some(file) = diagnostic.file() else {
+                return false;
+            };
+            let file = file.replace('\\', "/");
+            if !files.iter().any(|pattern| glob_match(pattern, &file)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn applicabilities(&self) -> &'static [&'static str] {
+        if self.include_maybe_incorrect.unwrap_or(false) {
+            &["MachineApplicable", "MaybeIncorrect"]
+        } else {
+            &["MachineApplicable"]
+        }
+    }
+}
+
+/// Replacements to make in every file, sorted by position and without overlaps
+#[derive(Debug, Default)]
+struct EditPlan<'a> {
+    files: BTreeMap<&'a str, Vec<(Range<usize>, &'a str)>>,
+    applied: usize,
+    conflicting: usize,
+}
+
+impl<'a> EditPlan<'a> {
+    /// Picks the first applicable suggestion of every diagnostic, skipping the ones
+    /// that touch text already changed by a previous suggestion
+    fn new(
+        diagnostics: impl IntoIterator<Item = &'a Diagnostic>,
+        applicabilities: &[&str],
+    ) -> Self {
+        let mut plan = EditPlan::default();
+        for diagnostic in diagnostics {
+            let suggestions = diagnostic.suggestions();
+            let Some(group) = suggestions
+                .iter()
+                .map(|suggestion| suggestion.group())
+                .find(|group| {
+                    suggestions
+                        .iter()
+                        .filter(|suggestion| suggestion.group() == *group)
+                        .all(|suggestion| {
+                            suggestion
+                                .applicability()
+                                .is_some_and(|a| applicabilities.contains(&a))
+                        })
+                })
+            else {
+                continue;
+            };
+            let edits: Vec<_> = suggestions
+                .iter()
+                .filter(|suggestion| suggestion.group() == group)
+                .collect();
+
+            let conflicts = edits.iter().enumerate().any(|(i, edit)| {
+                let accepted = plan.files.get(edit.file()).into_iter().flatten();
+                let siblings = edits[..i].iter().filter(|s| s.file() == edit.file());
+                accepted
+                    .map(|(range, _)| range.clone())
+                    .chain(siblings.map(|s| s.range()))
+                    .any(|range| overlaps(&range, &edit.range()))
+            });
+            if conflicts {
+                plan.conflicting += 1;
+                continue;
+            }
+
+            for edit in edits {
+                let file_edits = plan.files.entry(edit.file()).or_default();
+                let pos = file_edits.partition_point(|(range, _)| range.start < edit.range().start);
+                file_edits.insert(pos, (edit.range(), edit.replacement()));
+            }
+            plan.applied += 1;
+        }
+        plan
+    }
+}
+
+/// Two insertions at the same position conflict too, their order would be arbitrary
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start == b.start || (a.start < b.end && b.start < a.end)
+}
+
+/// Applies sorted, non-overlapping replacements to the file content
+fn apply_edits(content: &str, edits: &[(Range<usize>, &str)]) -> Option<String> {
+    let mut result = content.to_owned();
+    for (range, replacement) in edits.iter().rev() {
+        if range.end > result.len()
+            || !result.is_char_boundary(range.start)
+            || !result.is_char_boundary(range.end)
+        {
+            return None;
+        }
+        result.replace_range(range.clone(), replacement);
+    }
+    Some(result)
+}
+
+/// Resolves a path reported by rustc, refusing anything outside the workspace
+fn resolve_path(root: &Path, file: &str) -> Option<PathBuf> {
+    let path = Path::new(file);
+    if path.components().any(|c| c == Component::ParentDir) {
+        return None;
+    }
+    let path = root.join(path);
+    path.starts_with(root).then_some(path)
+}
+
+pub struct CargoApplySuggestionsRmcpTool;
+
+impl Tool for CargoApplySuggestionsRmcpTool {
+    const NAME: &'static str = "cargo-apply-suggestions";
+    const TITLE: &'static str = "Apply compiler suggestions";
+    const DESCRIPTION: &'static str = "Applies machine-applicable suggestions from the compiler and Clippy to the source code and returns a unified diff of the changes. \
+        Can be narrowed down to diagnostic codes, files or diagnostic ids reported by cargo-check, cargo-build and cargo-clippy. Use `dry_run` to preview the diff.";
+    type RequestArgs = CargoApplySuggestionsRequest;
+    const HINTS: ToolHints = ToolHints::writes().destructive().on_request();
+
+    fn request_hints(request: &Self::RequestArgs) -> ToolHints {
+        if request.dry_run.unwrap_or(false) {
+            ToolHints::read_only()
+        } else {
+            Self::HINTS
+        }
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        // Locating the workspace and collecting the suggestions only read it, the user confirms
+        // the changes themselves before any file is written
+        let helper_ctx = ctx.without_confirmation();
+
+        let mut locate =
+            execute_command(request.build_locate_project_cmd(), Self::NAME, &helper_ctx).await?;
+        let root = match locate.stdout.take() {
+            Some(manifest) if locate.success() => Path::new(manifest.0.trim())
+                .parent()
+                .map(Path::to_path_buf)
+                .ok_or_else(|| {
+                    ErrorData::internal_error(
+                        format!("Unexpected workspace manifest path: {}", manifest.0),
+                        None,
+                    )
+                })?,
+            _ => return Ok(locate.into()),
+        };
+
+        let mut output = execute_command(request.build_cmd()?, Self::NAME, &helper_ctx).await?;
+        let report = DiagnosticsReport::take_from(&mut output);
+        let selected: Vec<_> = report
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| request.selects(diagnostic))
+            .collect();
+        let plan = EditPlan::new(selected.iter().copied(), request.applicabilities());
+
+        let dry_run = request.dry_run.unwrap_or(false);
+        let mut diff = String::new();
+        let mut changes = Vec::new();
+        let mut skipped_files = Vec::new();
+        for (file, edits) in &plan.files {
+            let Some(path) = resolve_path(&root, file) else {
+                skipped_files.push(format!("{file} (outside of the workspace)"));
+                continue;
+            };
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    skipped_files.push(format!("{file} ({e})"));
+                    continue;
+                }
+            };
+            let Some(new_content) = apply_edits(&content, edits) else {
+                skipped_files.push(format!("{file} (changed since it was compiled)"));
+                continue;
+            };
+            diff.push_str(
+                &similar::TextDiff::from_lines(&content, &new_content)
+                    .unified_diff()
+                    .header(&format!("a/{file}"), &format!("b/{file}"))
+                    .to_string(),
+            );
+            changes.push((file, path, new_content));
+        }
+
+        let mut changed_files = changes.len();
+        if !dry_run && !changes.is_empty() {
+            if let Some(confirmation) = ctx.confirmation() {
+                let action = format!(
+                    "applying {} suggestion(s) to {changed_files} file(s)",
+                    plan.applied
+                );
+                confirmation
+                    .confirm_changes(Self::NAME, &action, &diff, ctx.cancellation_token())
+                    .await?;
+            }
+            for (file, path, new_content) in &changes {
+                if let Err(e) = std::fs::write(path, new_content) {
+                    skipped_files.push(format!("{file} ({e})"));
+                    changed_files -= 1;
+                }
+            }
+        }
+
+        let mut summary = if dry_run {
+            format!(
+                "Dry run: {} suggestion(s) from {} selected diagnostic(s) would change {changed_files} file(s)",
+                plan.applied,
+                selected.len()
+            )
+        } else {
+            format!(
+                "Applied {} suggestion(s) from {} selected diagnostic(s) to {changed_files} file(s)",
+                plan.applied,
+                selected.len()
+            )
+        };
+        if plan.conflicting > 0 {
+            summary.push_str(&format!(
+                "\nSkipped {} suggestion(s) overlapping with other suggestions, run the tool again to apply them",
+                plan.conflicting
+            ));
+        }
+        if !skipped_files.is_empty() {
+            summary.push_str(&format!("\nSkipped files: {}", skipped_files.join(", ")));
+        }
+
+        let mut response: Response = output.into();
+        let mut annotations = Annotations::default();
+        annotations.audience = Some(vec![Role::User, Role::Assistant]);
+        annotations.priority = Some(1.);
+        response.add_content(RawContent::text(summary).annotate(annotations.clone()));
+        if !diff.is_empty() {
+            response.add_content(RawContent::text(diff).annotate(annotations));
+        }
+        if !dry_run && changed_files > 0 {
+            response.add_recommendation(format!(
+                "Run #{} to verify the changes",
+                CargoCheckRmcpTool::NAME
+            ));
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::{CommandLine, ExitStatus, Output, Stdout};
+
+    use super::*;
+
+    /// `let mut x = 5;` with an unused variable and `use std::fmt;` with an unused import
+    const STDOUT: &str = r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused variable: `x`","code":{"code":"unused_variables"},"spans":[{"file_name":"src/main.rs","byte_start":24,"byte_end":25,"line_start":2,"line_end":2,"column_start":13,"column_end":14,"is_primary":true,"label":null,"suggested_replacement":null,"suggestion_applicability":null}],"children":[{"level":"help","message":"if this is intentional, prefix it with an underscore","code":null,"spans":[{"file_name":"src/main.rs","byte_start":24,"byte_end":25,"line_start":2,"line_end":2,"column_start":13,"column_end":14,"is_primary":true,"label":null,"suggested_replacement":"_x","suggestion_applicability":"MachineApplicable"}],"children":[]}]}}
+{"reason":"compiler-message","message":{"level":"warning","message":"unused import: `std::fmt`","code":{"code":"unused_imports"},"spans":[{"file_name":"src/lib.rs","byte_start":4,"byte_end":12,"line_start":1,"line_end":1,"column_start":5,"column_end":13,"is_primary":true,"label":null,"suggested_replacement":null,"suggestion_applicability":null}],"children":[{"level":"help","message":"remove the whole `use` item","code":null,"spans":[{"file_name":"src/lib.rs","byte_start":0,"byte_end":14,"line_start":1,"line_end":2,"column_start":1,"column_end":1,"is_primary":true,"label":null,"suggested_replacement":"","suggestion_applicability":"MachineApplicable"}],"children":[]}]}}
+{"reason":"compiler-message","message":{"level":"warning","message":"variable does not need to be mutable","code":{"code":"unused_mut"},"spans":[{"file_name":"src/main.rs","byte_start":20,"byte_end":25,"line_start":2,"line_end":2,"column_start":9,"column_end":14,"is_primary":true,"label":null,"suggested_replacement":null,"suggestion_applicability":null}],"children":[{"level":"help","message":"remove this `mut`","code":null,"spans":[{"file_name":"src/main.rs","byte_start":20,"byte_end":24,"line_start":2,"line_end":2,"column_start":9,"column_end":13,"is_primary":true,"label":null,"suggested_replacement":"","suggestion_applicability":"MaybeIncorrect"}],"children":[]}]}}"#;
+
+    fn report() -> DiagnosticsReport {
+        let mut output = Output {
+            tool_name: CargoApplySuggestionsRmcpTool::NAME.into(),
+            cmd_line: CommandLine("cargo clippy".into()),
+            stdout: Some(Stdout(STDOUT.into())),
+            stderr: None,
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
+        };
+        DiagnosticsReport::take_from(&mut output)
+    }
+
+    fn request(json: serde_json::Value) -> CargoApplySuggestionsRequest {
+        serde_json::from_value(json).expect("valid request")
+    }
+
+    #[test]
+    fn test_machine_applicable_only() {
+        let report = report();
+        let request = request(serde_json::json!({}));
+        let plan = EditPlan::new(report.diagnostics(), request.applicabilities());
+
+        assert_eq!(plan.applied, 2);
+        assert_eq!(plan.files["src/main.rs"], [(24..25, "_x")]);
+        assert_eq!(plan.files["src/lib.rs"], [(0..14, "")]);
+    }
+
+    #[test]
+    fn test_select_by_code_and_id() {
+        let report = report();
+        let by_code = request(serde_json::json!({ "codes": ["unused_imports"] }));
+        let selected: Vec<_> = report
+            .diagnostics()
+            .iter()
+            .filter(|d| by_code.selects(d))
+            .collect();
+        let plan = EditPlan::new(selected, by_code.applicabilities());
+        assert_eq!(
+            plan.files.keys().copied().collect::<Vec<_>>(),
+            ["src/lib.rs"]
+        );
+
+        let id = report.diagnostics()[0].id();
+        let by_id = request(serde_json::json!({ "ids": id, "files": "src/*.rs" }));
+        assert!(by_id.selects(&report.diagnostics()[0]));
+        assert!(!by_id.selects(&report.diagnostics()[1]));
+    }
+
+    #[test]
+    fn test_overlapping_suggestions_are_skipped() {
+        let report = report();
+        let request = request(serde_json::json!({ "include_maybe_incorrect": true }));
+        let plan = EditPlan::new(report.diagnostics(), request.applicabilities());
+
+        // removing `mut ` (20..24) doesn't overlap renaming `x` (24..25)
+        assert_eq!(plan.applied, 3);
+        assert_eq!(plan.conflicting, 0);
+        assert_eq!(
+            apply_edits(
+                "fn main() {\n    let mut x = 5;\n}\n",
+                &plan.files["src/main.rs"]
+            ),
+            Some("fn main() {\n    let _x = 5;\n}\n".to_owned())
+        );
+
+        let duplicated: Vec<_> = report
+            .diagnostics()
+            .iter()
+            .chain(report.diagnostics())
+            .collect();
+        let plan = EditPlan::new(duplicated, request.applicabilities());
+        assert_eq!(plan.applied, 3);
+        assert_eq!(plan.conflicting, 3);
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let root = Path::new("/workspace");
+        assert_eq!(
+            resolve_path(root, "src/main.rs"),
+            Some(PathBuf::from("/workspace/src/main.rs"))
+        );
+        assert_eq!(resolve_path(root, "../other/src/lib.rs"), None);
+        assert_eq!(
+            resolve_path(root, "/home/user/.cargo/registry/src/foo/lib.rs"),
+            None
+        );
+        assert_eq!(apply_edits("short", &[(3..10, "")]), None);
+    }
+}
@@ -1,11 +1,14 @@
 use std::process::Command;
 
 use crate::{
-    Response, Tool, execute_command,
+    Response, Tool, ToolContext, ToolHints,
+    diagnostics::{DiagnosticsFilter, DiagnosticsReport, MESSAGE_FORMAT_JSON},
+    execute_command,
     serde_utils::{
         deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
     },
+    tool::json_schema_impl,
     tools::cargo::CargoCheckRmcpTool,
 };
 use rmcp::ErrorData;
@@ -138,6 +141,10 @@ pub struct CargoBuildRequest {
     /// Treat warnings as errors
     #[serde(default)]
     warnings_as_errors: Option<bool>,
+
+    /// Filters applied to the reported diagnostics
+    #[serde(flatten)]
+    diagnostics_filter: DiagnosticsFilter,
 }
 
 impl CargoBuildRequest {
@@ -266,6 +273,8 @@ impl CargoBuildRequest {
         // Output options
         let output_flags = output_verbosity_to_cli_flags(self.output_verbosity.as_deref())?;
         cmd.args(output_flags);
+        // Diagnostics are parsed into structured content
+        cmd.arg(MESSAGE_FORMAT_JSON);
 
         if self.warnings_as_errors.unwrap_or(false) {
             cmd.env("RUSTFLAGS", "-D warnings");
@@ -282,14 +291,27 @@ impl Tool for CargoBuildRmcpTool {
     const DESCRIPTION: &'static str =
         "Builds a Rust project using Cargo. Usually, run without any additional arguments.";
     type RequestArgs = CargoBuildRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<DiagnosticsReport>())
+    }
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        request.diagnostics_filter.validate()?;
         let cmd = request.build_cmd()?;
         let start_time = std::time::Instant::now();
-        let output = execute_command(cmd, Self::NAME)?;
+        let mut output = execute_command(cmd, Self::NAME, &ctx).await?;
         let duration = start_time.elapsed();
 
+        let mut report = DiagnosticsReport::take_from(&mut output);
+        report.filter(&request.diagnostics_filter)?;
         let mut response: Response = output.into();
+        report.attach_to(&mut response);
         if duration.as_secs() >= 60 {
             response.add_recommendation(format!(
                 "Consider using #{} tool for faster feedback",
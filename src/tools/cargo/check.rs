@@ -1,11 +1,14 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints,
+    diagnostics::{DiagnosticsFilter, DiagnosticsReport, MESSAGE_FORMAT_JSON},
+    execute_command,
     serde_utils::{
         deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
     },
+    tool::json_schema_impl,
 };
 use rmcp::ErrorData;
 
@@ -137,6 +140,10 @@ pub struct CargoCheckRequest {
     /// Treat warnings as errors
     #[serde(default)]
     warnings_as_errors: Option<bool>,
+
+    /// Filters applied to the reported diagnostics
+    #[serde(flatten)]
+    diagnostics_filter: DiagnosticsFilter,
 }
 
 impl CargoCheckRequest {
@@ -265,6 +272,8 @@ impl CargoCheckRequest {
         // Output options
         let output_flags = output_verbosity_to_cli_flags(self.output_verbosity.as_deref())?;
         cmd.args(output_flags);
+        // Diagnostics are parsed into structured content
+        cmd.arg(MESSAGE_FORMAT_JSON);
 
         if self.warnings_as_errors.unwrap_or(false) {
             cmd.env("RUSTFLAGS", "-D warnings");
@@ -280,9 +289,25 @@ impl Tool for CargoCheckRmcpTool {
     const TITLE: &'static str = "cargo check";
     const DESCRIPTION: &'static str = "Checks a Rust package and all of its dependencies for errors. Usually, run without any additional arguments.";
     type RequestArgs = CargoCheckRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<DiagnosticsReport>())
+    }
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        request.diagnostics_filter.validate()?;
         let cmd = request.build_cmd()?;
-        execute_command(cmd, Self::NAME).map(Into::into)
+        let mut output = execute_command(cmd, Self::NAME, &ctx).await?;
+
+        let mut report = DiagnosticsReport::take_from(&mut output);
+        report.filter(&request.diagnostics_filter)?;
+        let mut response: crate::Response = output.into();
+        report.attach_to(&mut response);
+        Ok(response)
     }
 }
@@ -1,13 +1,15 @@
 use std::process::Command;
 
 use crate::{
-    Tool,
+    Tool, ToolContext, ToolHints,
     command::execute_command,
+    diagnostics::{DiagnosticsFilter, DiagnosticsReport, MESSAGE_FORMAT_JSON},
     serde_utils::{
         deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
     },
-    tools::cargo::CargoFmtRmcpTool,
+    tool::json_schema_impl,
+    tools::cargo::{CargoApplySuggestionsRmcpTool, CargoFmtRmcpTool},
 };
 use rmcp::ErrorData;
 
@@ -139,6 +141,10 @@ pub struct CargoClippyRequest {
     /// Treat warnings as errors
     #[serde(default)]
     warnings_as_errors: Option<bool>,
+
+    /// Filters applied to the reported diagnostics
+    #[serde(flatten)]
+    diagnostics_filter: DiagnosticsFilter,
 }
 impl CargoClippyRequest {
     pub fn build_cmd(&self) -> Result<Command, ErrorData> {
@@ -267,6 +273,8 @@ impl CargoClippyRequest {
         // Output options
         let output_flags = output_verbosity_to_cli_flags(self.output_verbosity.as_deref())?;
         cmd.args(output_flags);
+        // Diagnostics are parsed into structured content
+        cmd.arg(MESSAGE_FORMAT_JSON);
 
         if self.warnings_as_errors.unwrap_or(false) {
             cmd.env("RUSTFLAGS", "-D warnings");
@@ -284,19 +292,40 @@ impl Tool for CargoClippyRmcpTool {
     const DESCRIPTION: &'static str =
         "Checks a Rust package to catch common mistakes and improve code quality using Clippy";
     type RequestArgs = CargoClippyRequest;
+    const HINTS: ToolHints = ToolHints::writes().destructive().on_request();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    fn request_hints(request: &Self::RequestArgs) -> ToolHints {
+        if request.fix.unwrap_or(false) {
+            Self::HINTS
+        } else {
+            ToolHints::read_only()
+        }
+    }
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<DiagnosticsReport>())
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        request.diagnostics_filter.validate()?;
         let cmd = request.build_cmd()?;
-        let output = execute_command(cmd, Self::NAME)?;
+        let mut output = execute_command(cmd, Self::NAME, &ctx).await?;
 
-        let add_fix_recommendation = !request.fix.unwrap_or(false) && output.stderr.is_some();
+        let mut report = DiagnosticsReport::take_from(&mut output);
+        report.filter(&request.diagnostics_filter)?;
+        let add_fix_recommendation = !request.fix.unwrap_or(false) && !report.is_empty();
         let add_fmt_recommendation = request.fix.unwrap_or(false);
         let mut response: crate::Response = output.into();
+        report.attach_to(&mut response);
 
         if add_fix_recommendation {
             response.add_recommendation(format!(
-                "Run #{} with the `fix` and `allow_dirty` options to automatically fix the issues",
-                Self::NAME
+                "Run #{} to automatically fix the issues that have machine-applicable suggestions",
+                CargoApplySuggestionsRmcpTool::NAME
             ));
         }
 
@@ -365,4 +394,18 @@ mod tests {
         assert_eq!(tool.all_features, None);
         assert_eq!(tool.allow_dirty, None);
     }
+
+    #[test]
+    fn test_request_hints() {
+        let request: CargoClippyRequest = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(
+            CargoClippyRmcpTool::request_hints(&request),
+            ToolHints::read_only()
+        );
+
+        let request: CargoClippyRequest = serde_json::from_value(json!({"fix": true})).unwrap();
+        let hints = CargoClippyRmcpTool::request_hints(&request);
+        assert!(!hints.read_only && hints.destructive);
+        assert!(CargoClippyRmcpTool::HINTS.available_read_only());
+    }
 }
@@ -4,12 +4,11 @@ use std::process::Command;
 use rmcp::{ErrorData, model::RawContent};
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{
         deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
     },
-    tools::get_workspace_root,
 };
 
 #[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
@@ -274,7 +273,7 @@ impl CargoDocRequest {
         Ok(cmd)
     }
 
-    fn get_doc_path(&self) -> Option<String> {
+    fn get_doc_path(&self, workspace_root: Option<&Path>) -> Option<String> {
         let base_dir = self.target_dir.as_deref().unwrap_or("target");
 
         // Get the base documentation directory
@@ -285,7 +284,7 @@ impl CargoDocRequest {
         };
 
         // Get the absolute path using workspace root
-        let absolute_doc_dir = if let Some(workspace_root) = get_workspace_root() {
+        let absolute_doc_dir = if let Some(workspace_root) = workspace_root {
             workspace_root.join(&doc_dir)
         } else {
             Path::new(&doc_dir).to_path_buf()
@@ -343,13 +342,18 @@ impl Tool for CargoDocRmcpTool {
     const TITLE: &'static str = "Build Rust documentation";
     const DESCRIPTION: &'static str = "Build documentation for a Rust package using Cargo. Recommended to use with no_deps and specific package for faster builds. Returns path to generated documentation index.";
     type RequestArgs = CargoDocRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
         use rmcp::model::{AnnotateAble, Annotations, Role};
 
         let cmd = request.build_cmd()?;
         let start_time = std::time::Instant::now();
-        let output = execute_command(cmd, Self::NAME)?;
+        let output = execute_command(cmd, Self::NAME, &ctx).await?;
         let duration = start_time.elapsed();
 
         if !output.success() {
@@ -357,7 +361,7 @@ impl Tool for CargoDocRmcpTool {
         }
 
         let mut response: crate::Response = output.into();
-        let doc_path = request.get_doc_path();
+        let doc_path = request.get_doc_path(ctx.workspace_root());
         let doc_info = if let Some(doc_path) = doc_path {
             format!(
                 "Documentation generated successfully!\nDocumentation file: {doc_path}\nOpen this file in your browser to view the docs"
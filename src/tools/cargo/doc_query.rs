@@ -0,0 +1,327 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
+
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, RawContent},
+};
+
+use crate::{
+    Response, Tool, ToolContext, ToolHints,
+    diagnostics::stable_hash,
+    execute_command,
+    rustdoc::{Query, RustdocIndex},
+    serde_utils::{deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags},
+    tools::cargo::{CargoMetadata, workspace_info::Package},
+};
+
+/// Directory of the build directory the rustdoc JSON is generated and cached in
+const RUSTDOC_JSON_DIR: &str = "rustdoc-json";
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct CargoDocQueryRequest {
+    /// Path of the item, starting with the crate name (e.g. "tokio::sync::Mutex::lock" or "serde::de"). A crate name alone lists the items of its root module.
+    path: String,
+
+    /// Package to document, a workspace member or a dependency, as a package spec (e.g. "tokio" or "tokio@1.45.1").
+    /// Defaults to the package whose library is named like the first segment of `path`.
+    #[serde(default, deserialize_with = "deserialize_string")]
+    package: Option<String>,
+
+    /// What to return.
+    ///
+    /// Valid options:
+    /// - "item": declaration and documentation of the item (default, except for modules)
+    /// - "items": public items of a module (default for modules), or the members of a type or a trait
+    /// - "impls": trait implementations of a type, or the implementations of a trait
+    #[serde(default, deserialize_with = "deserialize_string")]
+    query: Option<String>,
+
+    /// Space or comma separated list of features to activate, workspace members only.
+    /// Dependencies are documented with the features the workspace enables.
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    features: Option<Vec<String>>,
+
+    /// Activate all available features, workspace members only
+    #[serde(default)]
+    all_features: Option<bool>,
+
+    /// Do not activate the `default` feature, workspace members only
+    #[serde(default)]
+    no_default_features: Option<bool>,
+
+    /// Nightly toolchain used to generate the JSON, which is an unstable rustdoc output. Defaults to "nightly".
+    #[serde(default, deserialize_with = "deserialize_string")]
+    toolchain: Option<String>,
+
+    /// Locking mode for dependency resolution.
+    ///
+    /// Valid options:
+    /// - "locked" (default): Assert that `Cargo.lock` will remain unchanged
+    /// - "unlocked": Allow `Cargo.lock` to be updated
+    /// - "offline": Run without accessing the network
+    /// - "frozen": Equivalent to specifying both --locked and --offline
+    #[serde(default, deserialize_with = "deserialize_string")]
+    locking_mode: Option<String>,
+}
+
+impl CargoDocQueryRequest {
+    fn toolchain(&self) -> &str {
+        self.toolchain.as_deref().unwrap_or("nightly")
+    }
+
+    fn build_cmd(&self, package: &Package, target_dir: &Path) -> Result<Command, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        cmd.arg(format!("+{}", self.toolchain()))
+            .arg("doc")
+            .arg("--no-deps")
+            .arg("--lib")
+            .arg("--package")
+            .arg(format!("{}@{}", package.name, package.version))
+            .arg("--target-dir")
+            .arg(target_dir);
+        cmd.env("RUSTDOCFLAGS", "-Z unstable-options --output-format json");
+
+        if let Some(features) = &self.features {
+            cmd.arg("--features").arg(features.join(","));
+        }
+        if self.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+        if self.no_default_features.unwrap_or(false) {
+            cmd.arg("--no-default-features");
+        }
+
+        let locking_flags = locking_mode_to_cli_flags(self.locking_mode.as_deref(), "locked")?;
+        cmd.args(locking_flags);
+        cmd.arg("--quiet");
+        Ok(cmd)
+    }
+
+    /// Name of the cached JSON: the package version, and a hash of everything else the
+    /// documentation depends on
+    fn cache_file_name(&self, package: &Package, resolved_features: &[String]) -> String {
+        let flag = |flag: Option<bool>| if flag.unwrap_or(false) { "1" } else { "0" };
+        let hash = stable_hash(&[
+            self.toolchain(),
+            &resolved_features.join(","),
+            &self
+                .features
+                .iter()
+                .flatten()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(","),
+            flag(self.all_features),
+            flag(self.no_default_features),
+        ]);
+        format!("{}-{}-{hash}.json", package.name, package.version)
+    }
+}
+
+/// The package `spec` refers to, by package or library name with an optional `@version`
+fn find_package<'a>(metadata: &'a CargoMetadata, spec: &str) -> Result<&'a Package, ErrorData> {
+    let (name, version) = spec
+        .split_once('@')
+        .map_or((spec, None), |(name, version)| (name, Some(version)));
+    let lib_name = name.replace('-', "_");
+    let mut candidates: Vec<&Package> = metadata
+        .packages
+        .iter()
+        .filter(|package| {
+            package.name == name || library_name(package).is_some_and(|library| library == lib_name)
+        })
+        .collect();
+    let all_versions: Vec<&str> = candidates.iter().map(|p| p.version.as_str()).collect();
+    if let Some(version) = version {
+        candidates.retain(|package| package.version == version);
+    }
+
+    match candidates.as_slice() {
+        [package] => Ok(package),
+        [] if all_versions.is_empty() => Err(ErrorData::invalid_params(
+            format!("Package `{name}` is not in the dependency graph of the workspace"),
+            None,
+        )),
+        [] => Err(ErrorData::invalid_params(
+            format!(
+                "Package `{spec}` is not in the dependency graph, available versions: {}",
+                all_versions.join(", ")
+            ),
+            None,
+        )),
+        candidates => candidates
+            .iter()
+            .find(|package| metadata.workspace_members.contains(&package.id))
+            .copied()
+            .ok_or_else(|| {
+                ErrorData::invalid_params(
+                    format!(
+                        "Several versions of `{name}` are in the dependency graph, pass `package` as one of: {}",
+                        all_versions
+                            .iter()
+                            .map(|version| format!("{name}@{version}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    None,
+                )
+            }),
+    }
+}
+
+/// Crate name of the library target of a package
+fn library_name(package: &Package) -> Option<String> {
+    package
+        .targets
+        .iter()
+        .find(|target| {
+            target
+                .kind
+                .iter()
+                .any(|kind| kind.ends_with("lib") || kind == "proc-macro")
+        })
+        .map(|target| target.name.replace('-', "_"))
+}
+
+/// Modification time of the most recently changed file of a local package, skipping the build
+/// directory and hidden directories
+fn newest_source_change(dir: &Path) -> Option<SystemTime> {
+    let mut newest = None;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let name = entry.file_name();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let modified = if file_type.is_dir() {
+            if name == "target" || name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+            newest_source_change(&entry.path())
+        } else {
+            entry.metadata().and_then(|m| m.modified()).ok()
+        };
+        newest = newest.max(modified);
+    }
+    newest
+}
+
+/// Whether the cached JSON is up to date, the sources of registry and git dependencies never change
+fn is_fresh(cached: &Path, package: &Package) -> bool {
+    let Ok(cached_at) = std::fs::metadata(cached).and_then(|m| m.modified()) else {
+        return false;
+    };
+    if package.source.is_some() {
+        return true;
+    }
+    let package_dir = Path::new(&package.manifest_path)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    newest_source_change(&package_dir).is_none_or(|changed| changed <= cached_at)
+}
+
+pub struct CargoDocQueryRmcpTool;
+
+impl Tool for CargoDocQueryRmcpTool {
+    const NAME: &'static str = "cargo-doc-query";
+    const TITLE: &'static str = "Query crate documentation";
+    const DESCRIPTION: &'static str = "Looks up the API of a workspace crate or a dependency in its rustdoc JSON: the public items of a module, the signature and docs of an item such as `tokio::sync::Mutex::lock`, or the trait implementations of a type. Builds the JSON with a nightly toolchain on first use and caches it in the target directory per crate version and features.";
+    type RequestArgs = CargoDocQueryRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let query = request.query.as_deref().map(Query::parse).transpose()?;
+
+        let metadata = CargoMetadata::load_with_dependencies(&ctx).await?;
+        let spec = match &request.package {
+            Some(package) => package.as_str(),
+            None => request.path.split("::").next().unwrap_or_default(),
+        };
+        let package = find_package(&metadata, spec)?;
+        let library = library_name(package).ok_or_else(|| {
+            ErrorData::invalid_params(
+                format!("Package `{}` has no library to document", package.name),
+                None,
+            )
+        })?;
+        let resolved_features = metadata
+            .resolve
+            .iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .find(|node| node.id == package.id)
+            .map(|node| node.features.clone())
+            .unwrap_or_default();
+
+        let target_dir: PathBuf = metadata.target_directory.join(RUSTDOC_JSON_DIR);
+        let cached = target_dir.join(request.cache_file_name(package, &resolved_features));
+        if !is_fresh(&cached, package) {
+            let cmd = request.build_cmd(package, &target_dir)?;
+            let output = execute_command(cmd, Self::NAME, &ctx).await?;
+            if !output.success() {
+                return Ok(output.into());
+            }
+            let built = target_dir.join("doc").join(format!("{library}.json"));
+            std::fs::copy(&built, &cached).map_err(|e| {
+                ErrorData::internal_error(
+                    format!(
+                        "Failed to cache {} as {}: {e}",
+                        built.display(),
+                        cached.display()
+                    ),
+                    None,
+                )
+            })?;
+        }
+
+        let text = RustdocIndex::load(cached)
+            .await?
+            .query(&request.path, query)?;
+
+        let mut response = Response::without_output();
+        response.add_content(RawContent::text(text).no_annotation());
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_package() {
+        let metadata: CargoMetadata = serde_json::from_value(serde_json::json!({
+            "packages": [
+                { "id": "app", "name": "my-app", "version": "0.1.0", "manifest_path": "/ws/Cargo.toml",
+                  "targets": [{ "name": "my_app", "kind": ["lib"] }] },
+                { "id": "syn1", "name": "syn", "version": "1.0.109", "source": "registry", "manifest_path": "/r/syn1/Cargo.toml",
+                  "targets": [{ "name": "syn", "kind": ["lib"] }] },
+                { "id": "syn2", "name": "syn", "version": "2.0.100", "source": "registry", "manifest_path": "/r/syn2/Cargo.toml",
+                  "targets": [{ "name": "syn", "kind": ["lib"] }] },
+            ],
+            "workspace_members": ["app"],
+            "workspace_root": "/ws",
+            "target_directory": "/ws/target",
+        }))
+        .unwrap();
+
+        assert_eq!(find_package(&metadata, "my_app").unwrap().id, "app");
+        assert_eq!(find_package(&metadata, "syn@2.0.100").unwrap().id, "syn2");
+        assert_eq!(
+            find_package(&metadata, "syn").unwrap_err().message,
+            "Several versions of `syn` are in the dependency graph, pass `package` as one of: syn@1.0.109, syn@2.0.100"
+        );
+        assert_eq!(
+            find_package(&metadata, "syn@3.0.0").unwrap_err().message,
+            "Package `syn@3.0.0` is not in the dependency graph, available versions: 1.0.109, 2.0.100"
+        );
+        assert!(find_package(&metadata, "tokio").is_err());
+    }
+}
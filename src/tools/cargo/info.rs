@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{
         PackageWithVersion, deserialize_string, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
@@ -86,10 +86,15 @@ impl Tool for CargoInfoRmcpTool {
     const TITLE: &'static str = "cargo info";
     const DESCRIPTION: &'static str = "Display information about a package. Information includes package description, list of available features, etc. Equivalent to 'cargo info <SPEC>'.";
     type RequestArgs = CargoInfoRequest;
+    const HINTS: ToolHints = ToolHints::read_only().open_world();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
         let cmd = request.build_cmd()?;
-        execute_command(cmd, Self::NAME).map(Into::into)
+        execute_command(cmd, Self::NAME, &ctx).await.map(Into::into)
     }
 }
 #[cfg(test)]
@@ -118,6 +123,11 @@ mod tests {
       "description": "Locking mode for dependency resolution.\n\nValid options:\n- \"locked\" (default): Assert that `Cargo.lock` will remain unchanged\n- \"unlocked\": Allow `Cargo.lock` to be updated\n- \"offline\": Run without accessing the network\n- \"frozen\": Equivalent to specifying both --locked and --offline",
       "type": "string"
     },
+    "max_output_bytes": {
+      "description": "Maximum size in bytes of each output stream, the middle of longer streams is elided and can be read with get-command-output. Defaults to the server `--max-output-bytes`, `0` disables the limit.",
+      "minimum": 0,
+      "type": "integer"
+    },
     "output_verbosity": {
       "default": null,
       "description": "Output verbosity level.\n\nValid options:\n- \"quiet\" (default): Show only the essential command output\n- \"normal\": Show standard output (no additional flags)\n- \"verbose\": Show detailed output including build information",
@@ -132,10 +142,19 @@ mod tests {
       "description": "Registry to search packages in",
       "type": "string"
     },
+    "timeout_secs": {
+      "description": "Maximum time in seconds each command is allowed to run. When exceeded, the process tree is killed and the partial output is returned. Defaults to the server `--default-timeout`, `0` disables the limit.",
+      "minimum": 0,
+      "type": "integer"
+    },
     "version": {
       "default": null,
       "description": "Optional version specification",
       "type": "string"
+    },
+    "workspace_root": {
+      "description": "Workspace to run in: the name or path of a workspace listed by workspace-list, or another directory containing a Cargo.toml. Defaults to the server workspace root.",
+      "type": "string"
     }
   },
   "required": [
@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{
         deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
@@ -131,10 +131,15 @@ impl Tool for CargoMetadataRmcpTool {
     const TITLE: &'static str = "cargo metadata";
     const DESCRIPTION: &'static str = "Outputs a listing of a project's resolved dependencies and metadata in machine-readable format (JSON).";
     type RequestArgs = CargoMetadataRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
         let cmd = request.build_cmd()?;
-        let mut response: crate::Response = execute_command(cmd, Self::NAME)?.into();
+        let mut response: crate::Response = execute_command(cmd, Self::NAME, &ctx).await?.into();
 
         if !request.no_deps.unwrap_or(false) {
             response.add_recommendation(
@@ -1,10 +1,13 @@
 mod add_remove;
+mod apply_suggestions;
 mod build;
 mod check;
 mod clippy;
 mod doc;
+mod doc_query;
 mod info;
 mod metadata;
+mod outdated;
 mod package;
 mod search;
 mod test;
@@ -13,23 +16,30 @@ mod update;
 mod workspace_info;
 
 pub use add_remove::{CargoAddRmcpTool, CargoRemoveRmcpTool};
+pub use apply_suggestions::CargoApplySuggestionsRmcpTool;
 pub use build::CargoBuildRmcpTool;
 pub use check::CargoCheckRmcpTool;
 pub use clippy::CargoClippyRmcpTool;
 pub use doc::CargoDocRmcpTool;
+pub use doc_query::CargoDocQueryRmcpTool;
 pub use info::CargoInfoRmcpTool;
 pub use metadata::CargoMetadataRmcpTool;
+pub use outdated::CargoOutdatedRmcpTool;
 pub use package::CargoPackageRmcpTool;
 pub use search::CargoSearchRmcpTool;
 pub use test::CargoTestRmcpTool;
 pub use tree::CargoTreeRmcpTool;
 pub use update::CargoUpdateRmcpTool;
+pub(crate) use workspace_info::CargoMetadata;
 pub use workspace_info::CargoWorkspaceInfoRmcpTool;
 
-use std::process::Command;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{
         deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
@@ -38,6 +48,13 @@ use crate::{
 };
 use rmcp::ErrorData;
 
+/// Directory where cargo keeps the registry caches and installed binaries
+pub(crate) fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")))
+}
+
 #[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
 pub struct CargoGenerateLockfileRequest {
     /// Path to Cargo.toml
@@ -108,9 +125,16 @@ impl Tool for CargoGenerateLockfileRmcpTool {
     const TITLE: &'static str = "Generate Cargo.lock";
     const DESCRIPTION: &'static str = "Generates or updates the Cargo.lock file for a Rust project. Usually, run without any additional arguments.";
     type RequestArgs = CargoGenerateLockfileRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes().destructive().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -246,9 +270,16 @@ impl Tool for CargoCleanRmcpTool {
     const TITLE: &'static str = "Clean Cargo artifacts";
     const DESCRIPTION: &'static str = "Cleans the target directory for a Rust project using Cargo. By default, it cleans the entire workspace.";
     type RequestArgs = CargoCleanRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes().destructive().idempotent();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -337,9 +368,22 @@ impl Tool for CargoFmtRmcpTool {
     const DESCRIPTION: &'static str =
         "Formats Rust code using rustfmt. Usually, run without any additional arguments.";
     type RequestArgs = CargoFmtRequest;
+    const HINTS: ToolHints = ToolHints::writes().idempotent().on_request();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        let output = execute_command(request.build_cmd()?, Self::NAME)?;
+    fn request_hints(request: &Self::RequestArgs) -> ToolHints {
+        if request.check {
+            ToolHints::read_only()
+        } else {
+            Self::HINTS
+        }
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        let output = execute_command(request.build_cmd()?, Self::NAME, &ctx).await?;
         let failed = !output.success();
         let mut response: crate::Response = output.into();
 
@@ -463,9 +507,16 @@ impl Tool for CargoNewRmcpTool {
     const TITLE: &'static str = "Create new Rust project";
     const DESCRIPTION: &'static str = "Create a new cargo package at <path>. Creates a new Rust project with the specified name and template.";
     type RequestArgs = CargoNewRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -487,8 +538,15 @@ impl Tool for CargoListRmcpTool {
     const TITLE: &'static str = "List cargo commands";
     const DESCRIPTION: &'static str = "Lists installed cargo commands using 'cargo --list'.";
     type RequestArgs = CargoListRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
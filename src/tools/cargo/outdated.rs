@@ -0,0 +1,478 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, Annotations, RawContent, Role},
+};
+use semver::{Version, VersionReq};
+
+use super::{CargoMetadata, cargo_home};
+use crate::{
+    Response, Tool, ToolContext, ToolHints, execute_command,
+    serde_utils::{deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags},
+    tool::json_schema_impl,
+};
+
+/// Source id of crates.io in `cargo metadata`, whose index is cached under `index.crates.io-*`
+const CRATES_IO_SOURCE: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct CargoOutdatedRequest {
+    /// The toolchain to use, e.g., "stable" or "nightly".
+    #[serde(default, deserialize_with = "deserialize_string")]
+    toolchain: Option<String>,
+
+    /// Workspace member(s) whose dependencies are reported. Defaults to all members.
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    package: Option<Vec<String>>,
+
+    /// Also list the dependencies that are up to date
+    #[serde(default)]
+    all: Option<bool>,
+
+    /// Path to Cargo.toml
+    #[serde(default, deserialize_with = "deserialize_string")]
+    manifest_path: Option<String>,
+
+    /// Locking mode for dependency resolution.
+    ///
+    /// Valid options:
+    /// - "locked" (default): Assert that `Cargo.lock` will remain unchanged
+    /// - "unlocked": Allow `Cargo.lock` to be updated
+    /// - "offline": Run without accessing the network, versions are read from the local registry index as last fetched
+    /// - "frozen": Equivalent to specifying both --locked and --offline
+    #[serde(default, deserialize_with = "deserialize_string")]
+    locking_mode: Option<String>,
+}
+
+impl CargoOutdatedRequest {
+    fn cargo(&self, subcommand: &str) -> Result<Command, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        if let Some(toolchain) = &self.toolchain {
+            cmd.arg(format!("+{toolchain}"));
+        }
+        cmd.arg(subcommand);
+
+        if let Some(manifest_path) = &self.manifest_path {
+            cmd.arg("--manifest-path").arg(manifest_path);
+        }
+
+        cmd.args(locking_mode_to_cli_flags(
+            self.locking_mode.as_deref(),
+            "locked",
+        )?);
+        Ok(cmd)
+    }
+
+    /// `cargo metadata` with the resolved dependencies of the workspace
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = self.cargo("metadata")?;
+        cmd.arg("--format-version").arg("1");
+        Ok(cmd)
+    }
+
+    /// `cargo update --dry-run`, which fetches the registry index entries of every dependency
+    /// without touching `Cargo.lock`. `None` when the network must not be used.
+    fn build_refresh_cmd(&self) -> Result<Option<Command>, ErrorData> {
+        if matches!(self.locking_mode.as_deref(), Some("offline" | "frozen")) {
+            return Ok(None);
+        }
+        let mut cmd = self.cargo("update")?;
+        cmd.arg("--dry-run");
+        Ok(Some(cmd))
+    }
+}
+
+/// Direct dependencies of the workspace members with their newer versions
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct OutdatedReport {
+    /// Number of registry dependencies checked
+    checked: usize,
+    dependencies: Vec<OutdatedDependency>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct OutdatedDependency {
+    /// Workspace member declaring the dependency
+    package: String,
+    name: String,
+    /// "normal", "dev" or "build"
+    kind: String,
+    /// Version requirement in the manifest, e.g. "^1.2"
+    requirement: String,
+    /// Version in `Cargo.lock`, missing if the dependency isn't resolved, e.g. an optional dependency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locked: Option<String>,
+    /// Newest version matching the requirement, which `cargo-update` can update to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest_compatible: Option<String>,
+    /// Newest published version, a breaking update if it doesn't match the requirement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest: Option<String>,
+}
+
+impl OutdatedDependency {
+    fn is_outdated(&self) -> bool {
+        self.latest.is_some()
+            && (self.latest != self.latest_compatible || self.latest_compatible != self.locked)
+    }
+}
+
+impl OutdatedReport {
+    fn new(metadata: &CargoMetadata, members: Option<&[String]>, index: &RegistryIndex) -> Self {
+        let packages: HashMap<&str, _> = metadata
+            .packages
+            .iter()
+            .map(|package| (package.id.as_str(), package))
+            .collect();
+        let nodes: HashMap<&str, _> = metadata
+            .resolve
+            .iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .map(|node| (node.id.as_str(), node))
+            .collect();
+
+        let mut report = OutdatedReport::default();
+        for member in &metadata.workspace_members {
+            let Some(package) = packages.get(member.as_str()) else {
+                continue;
+            };
+            if members.is_some_and(|members| !members.contains(&package.name)) {
+                continue;
+            }
+            // Versions the dependencies of the member resolved to
+            let resolved: Vec<(&str, Version)> = nodes
+                .get(member.as_str())
+                .into_iter()
+                .flat_map(|node| &node.deps)
+                .filter_map(|dep| packages.get(dep.pkg.as_str()))
+                .filter_map(|dep| Some((dep.name.as_str(), Version::parse(&dep.version).ok()?)))
+                .collect();
+
+            for dependency in &package.dependencies {
+                let Some(source) = dependency.source.as_deref().filter(|source| {
+                    source.starts_with("registry+") || source.starts_with("sparse+")
+                }) else {
+                    continue;
+                };
+                let Ok(req) = VersionReq::parse(&dependency.req) else {
+                    continue;
+                };
+                let locked = resolved
+                    .iter()
+                    .find(|(name, version)| *name == dependency.name && req.matches(version))
+                    .map(|(_, version)| version);
+                let versions = index.versions(source, &dependency.name);
+                let stable = |version: &&Version| {
+                    version.pre.is_empty() || locked.is_some_and(|locked| !locked.pre.is_empty())
+                };
+
+                report.checked += 1;
+                report.dependencies.push(OutdatedDependency {
+                    package: package.name.clone(),
+                    name: dependency.name.clone(),
+                    kind: dependency
+                        .kind
+                        .clone()
+                        .unwrap_or_else(|| "normal".to_owned()),
+                    requirement: dependency.req.clone(),
+                    locked: locked.map(Version::to_string),
+                    latest_compatible: versions
+                        .iter()
+                        .filter(|version| req.matches(version))
+                        .max()
+                        .map(Version::to_string),
+                    latest: versions.iter().filter(stable).max().map(Version::to_string),
+                });
+            }
+        }
+        report
+    }
+
+    fn render(&self) -> String {
+        let outdated = self
+            .dependencies
+            .iter()
+            .filter(|dependency| dependency.is_outdated())
+            .count();
+        let mut out = format!(
+            "{outdated} of {} direct registry dependencies are outdated",
+            self.checked
+        );
+        let mut package = None;
+        for dependency in &self.dependencies {
+            if package != Some(&dependency.package) {
+                package = Some(&dependency.package);
+                let _ = write!(out, "\n{}:", dependency.package);
+            }
+            let kind = match dependency.kind.as_str() {
+                "normal" => String::new(),
+                kind => format!(" ({kind})"),
+            };
+            let version = |version: &Option<String>| version.as_deref().unwrap_or("-").to_owned();
+            let _ = write!(
+                out,
+                "\n- {} {}{kind}: locked {}, compatible {}, latest {}",
+                dependency.name,
+                dependency.requirement,
+                version(&dependency.locked),
+                version(&dependency.latest_compatible),
+                version(&dependency.latest),
+            );
+            if dependency.latest.is_some() && dependency.latest != dependency.latest_compatible {
+                out.push_str(" (breaking)");
+            }
+        }
+        out
+    }
+}
+
+/// Crate versions cached by cargo in `$CARGO_HOME/registry/index/*/.cache`
+struct RegistryIndex {
+    /// One directory per registry, e.g. `index.crates.io-1949cf8c6b5b557f`
+    dirs: Vec<PathBuf>,
+}
+
+impl RegistryIndex {
+    fn new(cargo_home: &Path) -> Self {
+        let dirs = std::fs::read_dir(cargo_home.join("registry").join("index"))
+            .map(|dirs| dirs.flatten().map(|dir| dir.path()).collect())
+            .unwrap_or_default();
+        RegistryIndex { dirs }
+    }
+
+    /// Published, not yanked versions of a crate, empty if the index of its registry was never fetched
+    fn versions(&self, source: &str, name: &str) -> Vec<Version> {
+        let url = source.split_once('+').map_or(source, |(_, url)| url);
+        let host = url
+            .split_once("://")
+            .map_or(url, |(_, rest)| rest)
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        // crates.io is fetched through the sparse protocol by default
+        let hosts = if source == CRATES_IO_SOURCE {
+            vec!["index.crates.io", host]
+        } else {
+            vec![host]
+        };
+
+        let name = name.to_lowercase();
+        self.dirs
+            .iter()
+            .filter(|dir| {
+                dir.file_name().is_some_and(|dir| {
+                    let dir = dir.to_string_lossy();
+                    hosts
+                        .iter()
+                        .any(|host| dir.starts_with(&format!("{host}-")))
+                })
+            })
+            .find_map(|dir| std::fs::read(dir.join(".cache").join(index_path(&name))).ok())
+            .map(|cache| parse_index_cache(&cache))
+            .unwrap_or_default()
+    }
+}
+
+/// Path of a crate in a registry index, e.g. `se/rd/serde` or `3/s/syn`
+fn index_path(name: &str) -> PathBuf {
+    match name.len() {
+        1 => Path::new("1").join(name),
+        2 => Path::new("2").join(name),
+        3 => Path::new("3").join(&name[..1]).join(name),
+        _ => Path::new(&name[..2]).join(&name[2..4]).join(name),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Reads the index cache file of a crate: a header followed by NUL separated pairs of a version
+/// and its index entry as a JSON line
+fn parse_index_cache(cache: &[u8]) -> Vec<Version> {
+    cache
+        .split(|byte| *byte == 0)
+        .filter(|chunk| chunk.first() == Some(&b'{'))
+        .filter_map(|chunk| serde_json::from_slice::<IndexEntry>(chunk).ok())
+        .filter(|entry| !entry.yanked)
+        .filter_map(|entry| Version::parse(&entry.vers).ok())
+        .collect()
+}
+
+pub struct CargoOutdatedRmcpTool;
+
+impl Tool for CargoOutdatedRmcpTool {
+    const NAME: &'static str = "cargo-outdated";
+    const TITLE: &'static str = "List outdated dependencies";
+    const DESCRIPTION: &'static str = "Lists the direct registry dependencies of the workspace members that have newer versions, with their version requirement, the locked version, the newest version matching the requirement and the newest version overall. \
+        Versions beyond the latest compatible one are breaking updates that need the requirement in Cargo.toml changed, the others can be applied with cargo-update. \
+        Reads the local registry index after refreshing it, use the \"offline\" locking mode to skip the refresh.";
+    type RequestArgs = CargoOutdatedRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<OutdatedReport>())
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let mut notes = Vec::new();
+        if let Some(cmd) = request.build_refresh_cmd()? {
+            let output = execute_command(cmd, Self::NAME, &ctx).await?;
+            if !output.success() {
+                let stderr = output.stderr.map(|s| s.0).unwrap_or_default();
+                notes.push(format!(
+                    "Failed to refresh the registry index, the versions may be outdated: {}",
+                    stderr.trim().lines().last().unwrap_or_default()
+                ));
+            }
+        }
+
+        let metadata = CargoMetadata::run(request.build_cmd()?, &ctx).await?;
+        let Some(cargo_home) = cargo_home() else {
+            return Err(ErrorData::internal_error(
+                "Cannot locate the cargo home directory, set CARGO_HOME",
+                None,
+            ));
+        };
+        let mut report = OutdatedReport::new(
+            &metadata,
+            request.package.as_deref(),
+            &RegistryIndex::new(&cargo_home),
+        );
+        if !request.all.unwrap_or(false) {
+            report.dependencies.retain(OutdatedDependency::is_outdated);
+        }
+
+        let mut text = report.render();
+        for note in notes {
+            let _ = write!(text, "\n\n{note}");
+        }
+        let mut response = Response::without_output();
+        let mut annotations = Annotations::default();
+        annotations.audience = Some(vec![Role::User, Role::Assistant]);
+        annotations.priority = Some(1.);
+        response.add_content(RawContent::text(text).annotate(annotations));
+        response.set_structured_content(&report);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_entry(version: &str, yanked: bool) -> String {
+        format!(
+            r#"{version}{}{{"name": "serde", "vers": "{version}", "deps": [], "yanked": {yanked}}}{}"#,
+            '\0', '\0'
+        )
+    }
+
+    #[test]
+    fn test_index_path() {
+        assert_eq!(index_path("a"), Path::new("1/a"));
+        assert_eq!(index_path("cc"), Path::new("2/cc"));
+        assert_eq!(index_path("syn"), Path::new("3/s/syn"));
+        assert_eq!(index_path("serde"), Path::new("se/rd/serde"));
+    }
+
+    #[test]
+    fn test_outdated_report() {
+        let cargo_home = std::env::temp_dir().join("rust-mcp-server-outdated-test");
+        let cache = cargo_home.join("registry/index/index.crates.io-1949cf8c6b5b557f/.cache/se/rd");
+        std::fs::create_dir_all(&cache).unwrap();
+        let mut serde = b"\x03\x02\0\0\0Unknown\0".to_vec();
+        for (version, yanked) in [
+            ("1.0.100", false),
+            ("1.0.105", false),
+            ("1.0.106", true),
+            ("2.0.0-rc.1", false),
+            ("2.0.0", false),
+        ] {
+            serde.extend(index_entry(version, yanked).bytes());
+        }
+        std::fs::write(cache.join("serde"), serde).unwrap();
+
+        let metadata: CargoMetadata = serde_json::from_value(serde_json::json!({
+            "packages": [
+                {
+                    "id": "path+file:///ws#0.1.0",
+                    "name": "my-crate",
+                    "version": "0.1.0",
+                    "manifest_path": "/ws/Cargo.toml",
+                    "targets": [],
+                    "dependencies": [
+                        { "name": "serde", "req": "^1.0", "source": CRATES_IO_SOURCE },
+                        { "name": "serde_json", "req": "^1", "kind": "dev", "source": CRATES_IO_SOURCE },
+                        { "name": "helper", "req": "*" },
+                    ],
+                },
+                {
+                    "id": "registry+https://github.com/rust-lang/crates.io-index#serde@1.0.100",
+                    "name": "serde",
+                    "version": "1.0.100",
+                    "manifest_path": "/registry/serde-1.0.100/Cargo.toml",
+                    "targets": [],
+                },
+            ],
+            "workspace_members": ["path+file:///ws#0.1.0"],
+            "workspace_root": "/ws",
+            "target_directory": "/ws/target",
+            "resolve": {
+                "nodes": [{
+                    "id": "path+file:///ws#0.1.0",
+                    "deps": [{ "pkg": "registry+https://github.com/rust-lang/crates.io-index#serde@1.0.100" }],
+                }],
+            },
+        }))
+        .unwrap();
+
+        let mut report = OutdatedReport::new(&metadata, None, &RegistryIndex::new(&cargo_home));
+        assert_eq!(report.checked, 2);
+        assert_eq!(
+            report.dependencies[0],
+            OutdatedDependency {
+                package: "my-crate".into(),
+                name: "serde".into(),
+                kind: "normal".into(),
+                requirement: "^1.0".into(),
+                locked: Some("1.0.100".into()),
+                latest_compatible: Some("1.0.105".into()),
+                latest: Some("2.0.0".into()),
+            }
+        );
+        // Not in the local index
+        assert!(!report.dependencies[1].is_outdated());
+
+        report.dependencies.retain(OutdatedDependency::is_outdated);
+        assert_eq!(
+            report.render(),
+            "1 of 2 direct registry dependencies are outdated\nmy-crate:\n- serde ^1.0: locked 1.0.100, compatible 1.0.105, latest 2.0.0 (breaking)"
+        );
+    }
+
+    #[test]
+    fn test_offline_skips_refresh() {
+        let request: CargoOutdatedRequest = serde_json::from_value(serde_json::json!({
+            "locking_mode": "offline",
+        }))
+        .unwrap();
+        assert!(request.build_refresh_cmd().unwrap().is_none());
+        let cmd = request.build_cmd().unwrap();
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(args, ["metadata", "--offline", "--format-version", "1"]);
+    }
+}
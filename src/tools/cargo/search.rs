@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{deserialize_string, output_verbosity_to_cli_flags},
     tools::Registry,
 };
@@ -49,9 +49,14 @@ impl Tool for CargoSearchRmcpTool {
     const TITLE: &'static str = "cargo search";
     const DESCRIPTION: &'static str = "Search packages in the registry. Default registry is crates.io. Equivalent to 'cargo search <code>QUERY</code>'.";
     type RequestArgs = CargoSearchRequest;
+    const HINTS: ToolHints = ToolHints::read_only().open_world();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
         let cmd = request.build_cmd()?;
-        execute_command(cmd, Self::NAME).map(Into::into)
+        execute_command(cmd, Self::NAME, &ctx).await.map(Into::into)
     }
 }
@@ -1,11 +1,15 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints,
+    command::Output,
+    execute_command,
     serde_utils::{
         deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
     },
+    test_report::{LIBTEST_JSON_ARGS, TestReport},
+    tool::json_schema_impl,
 };
 use rmcp::ErrorData;
 
@@ -19,7 +23,10 @@ pub struct CargoTestRequest {
     #[serde(default, deserialize_with = "deserialize_string")]
     testname: Option<String>,
 
-    /// Arguments for the test binary (after --)
+    /// Arguments for the test binary (after --).
+    ///
+    /// On nightly toolchains libtest is asked for JSON output unless these contain `--format`,
+    /// the tests run again with text output if a test target with a custom harness rejects it.
     #[serde(default, deserialize_with = "deserialize_string_vec")]
     test_args: Option<Vec<String>>,
 
@@ -147,6 +154,9 @@ pub struct CargoTestRequest {
     /// - "quiet" (default): Show only the essential command output
     /// - "normal": Show standard output (no additional flags)
     /// - "verbose": Show detailed output including build information
+    ///
+    /// Cargo always runs with at least "normal" verbosity so that every test binary and test result
+    /// can be reported, in quiet mode its status lines are removed from the output afterwards.
     #[serde(default, deserialize_with = "deserialize_string")]
     output_verbosity: Option<String>,
 }
@@ -303,6 +313,64 @@ impl CargoTestRequest {
 
         Ok(cmd)
     }
+
+    /// Whether libtest can be asked for JSON output, which is unstable and needs a nightly toolchain
+    async fn libtest_json_supported(&self, ctx: &ToolContext) -> bool {
+        if self
+            .test_args
+            .iter()
+            .flatten()
+            .any(|arg| arg.starts_with("--format"))
+        {
+            return false;
+        }
+
+        let mut cmd = Command::new("rustc");
+        if let Some(toolchain) = &self.toolchain {
+            cmd.arg(format!("+{toolchain}"));
+        }
+        cmd.arg("--version");
+        match execute_command(cmd, CargoTestRmcpTool::NAME, &ctx.without_confirmation()).await {
+            Ok(output) => {
+                output.success()
+                    && output
+                        .stdout
+                        .is_some_and(|stdout| stdout.0.contains("-nightly"))
+            }
+            Err(e) => {
+                tracing::debug!("Failed to detect the toolchain channel: {}", e.message);
+                false
+            }
+        }
+    }
+}
+
+/// Whether a test binary with a custom harness (`harness = false`) failed on [`LIBTEST_JSON_ARGS`]
+fn rejected_libtest_json_args(output: &Output) -> bool {
+    const ERRORS: &[&str] = &[
+        "unrecognized option",
+        "unrecognized argument",
+        "unexpected argument",
+        "unknown option",
+        "unknown argument",
+        "invalid option",
+    ];
+    const ARGS: &[&str] = &["'z'", "-z", "unstable-options", "format", "report-time"];
+
+    let streams = [
+        output.stdout.as_ref().map(|stdout| stdout.0.as_str()),
+        output.stderr.as_ref().map(|stderr| stderr.0.as_str()),
+    ];
+    !output.success()
+        && streams
+            .into_iter()
+            .flatten()
+            .flat_map(str::lines)
+            .any(|line| {
+                let line = line.to_lowercase();
+                ERRORS.iter().any(|error| line.contains(error))
+                    && ARGS.iter().any(|arg| line.contains(arg))
+            })
 }
 
 pub struct CargoTestRmcpTool;
@@ -313,10 +381,44 @@ impl Tool for CargoTestRmcpTool {
     const DESCRIPTION: &'static str =
         "Run `cargo test` to execute Rust tests in the current project.";
     type RequestArgs = CargoTestRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<TestReport>())
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        let mut request = request;
+        // In quiet mode libtest prints a dot per test instead of its name and result,
+        // and cargo doesn't print which test binary is running
+        let quiet = matches!(request.output_verbosity.as_deref(), None | Some("quiet"));
+        if quiet {
+            request.output_verbosity = Some("normal".to_owned());
+        }
+        let mut cmd = request.build_cmd()?;
+        let libtest_json = request.libtest_json_supported(&ctx).await;
+        if libtest_json {
+            if request.test_args.is_none() {
+                cmd.arg("--");
+            }
+            cmd.args(LIBTEST_JSON_ARGS);
+        }
+        let mut output = execute_command(cmd, Self::NAME, &ctx).await?;
+        if libtest_json && rejected_libtest_json_args(&output) {
+            tracing::info!(
+                "A test target rejected the libtest JSON arguments, running the tests with text output"
+            );
+            output = execute_command(request.build_cmd()?, Self::NAME, &ctx).await?;
+        }
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        let cmd = request.build_cmd()?;
-        execute_command(cmd, Self::NAME).map(Into::into)
+        let report = TestReport::take_from(&mut output, quiet);
+        let mut response: crate::Response = output.into();
+        report.attach_to(&mut response);
+        Ok(response)
     }
 }
 #[cfg(test)]
@@ -425,4 +527,36 @@ mod tests {
 
         assert_eq!(tool.features.unwrap(), ["[\"serde\",\"tokio\"]".to_owned()]);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejected_libtest_json_args() {
+        use std::os::unix::process::ExitStatusExt;
+
+        use crate::command::{CommandLine, ExitStatus, Stderr, Stdout};
+
+        let output = |stdout: &str, stderr: &str| Output {
+            tool_name: CargoTestRmcpTool::NAME.into(),
+            cmd_line: CommandLine("cargo test".into()),
+            stdout: Some(Stdout(stdout.into())),
+            stderr: Some(Stderr(stderr.into())),
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::from_raw(101 << 8)),
+        };
+
+        // clap based harnesses, e.g. libtest-mimic
+        assert!(rejected_libtest_json_args(&output(
+            "",
+            "error: unexpected argument '-Z' found\n\nUsage: custom [OPTIONS]"
+        )));
+        // getopts based harnesses
+        assert!(rejected_libtest_json_args(&output(
+            "error: Unrecognized option: 'format'",
+            ""
+        )));
+        // a failing test that mentions neither the options nor the arguments
+        assert!(!rejected_libtest_json_args(&output(
+            "{ \"type\": \"test\", \"event\": \"failed\", \"name\": \"parse\" }",
+            "thread 'parse' panicked at src/lib.rs:3:5:\nunexpected argument count"
+        )));
+    }
 }
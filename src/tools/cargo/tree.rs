@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     response::Response,
     serde_utils::{deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags},
 };
@@ -192,10 +192,15 @@ impl Tool for CargoTreeRmcpTool {
     const TITLE: &'static str = "cargo tree";
     const DESCRIPTION: &'static str = "Display a tree visualization of a dependency graph. Useful for understanding dependency relationships, finding duplicate dependencies, and debugging dependency resolution issues.";
     type RequestArgs = CargoTreeRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
         let cmd = request.build_cmd()?;
-        let output = execute_command(cmd, Self::NAME)?;
+        let output = execute_command(cmd, Self::NAME, &ctx).await?;
 
         let stdout_len = if output.success()
             && let Some(stdout) = &output.stdout
@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{
         deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
         output_verbosity_to_cli_flags,
@@ -159,9 +159,22 @@ impl Tool for CargoUpdateRmcpTool {
     const TITLE: &'static str = "cargo update";
     const DESCRIPTION: &'static str = "Update dependencies as recorded in the local lock file. Updates the dependencies in Cargo.lock to their latest compatible versions.";
     type RequestArgs = CargoUpdateRequest;
+    const HINTS: ToolHints = ToolHints::writes().destructive().open_world().on_request();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    fn request_hints(request: &Self::RequestArgs) -> ToolHints {
+        if request.dry_run.unwrap_or(false) {
+            ToolHints::read_only().open_world()
+        } else {
+            Self::HINTS
+        }
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
         let cmd = request.build_cmd()?;
-        execute_command(cmd, Self::NAME).map(Into::into)
+        execute_command(cmd, Self::NAME, &ctx).await.map(Into::into)
     }
 }
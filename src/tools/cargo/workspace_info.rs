@@ -1,14 +1,17 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Command;
 
-use crate::{Tool, command::execute_command, serde_utils::deserialize_string};
+use crate::{
+    Tool, ToolContext, ToolHints, command::execute_command, serde_utils::deserialize_string,
+};
 use rmcp::{
     ErrorData,
     model::{AnnotateAble, Annotations, RawContent, Role},
 };
 use serde::Deserialize;
 
-#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+#[derive(Debug, Default, ::serde::Deserialize, ::schemars::JsonSchema)]
 #[schemars(title = "CargoWorkspaceInfoRequest")]
 pub struct CargoWorkspaceInfoRequest {
     /// The toolchain to use, e.g., "stable" or "nightly".
@@ -50,25 +53,21 @@ impl Tool for CargoWorkspaceInfoRmcpTool {
     const DESCRIPTION: &'static str = "Get information about crates in the current workspace, including package names, target \
          types, manifest paths, descriptions, features, and optionally dependencies. This is a cut down version of cargo metadata with the goal of saving tokens.";
     type RequestArgs = CargoWorkspaceInfoRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
         let cmd = request.build_cmd()?;
-        let mut output = execute_command(cmd, Self::NAME)?;
+        let mut output = execute_command(cmd, Self::NAME, &ctx).await?;
 
         if !output.success() {
             return Ok(output.into());
         }
 
-        let Some(stdout) = output.stdout.take() else {
-            return Err(ErrorData::internal_error(
-                "cargo metadata command produced no output".to_owned(),
-                None,
-            ));
-        };
-
-        let metadata: CargoMetadata = serde_json::from_str(&stdout.0).map_err(|e| {
-            ErrorData::internal_error(format!("failed to parse cargo metadata JSON: {e}"), None)
-        })?;
+        let metadata = CargoMetadata::parse(output.stdout.take())?;
 
         let include_deps = request.include_dependencies.unwrap_or(false);
         let mut packages: Vec<PackageInfo> = vec![];
@@ -111,32 +110,113 @@ impl Tool for CargoWorkspaceInfoRmcpTool {
 
 // Structures for parsing cargo metadata output
 #[derive(Debug, Deserialize)]
-struct CargoMetadata {
-    packages: Vec<Package>,
+pub(crate) struct CargoMetadata {
+    pub(crate) packages: Vec<Package>,
+    #[serde(default)]
+    pub(crate) workspace_members: Vec<String>,
+    pub(crate) workspace_root: PathBuf,
+    pub(crate) target_directory: PathBuf,
+    /// Only set when the dependencies are loaded
+    #[serde(default)]
+    pub(crate) resolve: Option<Resolve>,
+}
+
+impl CargoMetadata {
+    /// Runs `cargo metadata --no-deps` for the workspace the server works on
+    pub(crate) async fn load(ctx: &ToolContext) -> Result<Self, ErrorData> {
+        Self::run(CargoWorkspaceInfoRequest::default().build_cmd()?, ctx).await
+    }
+
+    /// Like [`CargoMetadata::load`], but also lists every resolved dependency
+    pub(crate) async fn load_with_dependencies(ctx: &ToolContext) -> Result<Self, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        cmd.arg("metadata").arg("--format-version").arg("1");
+        Self::run(cmd, ctx).await
+    }
+
+    /// Runs a `cargo metadata --format-version 1` command
+    pub(crate) async fn run(cmd: Command, ctx: &ToolContext) -> Result<Self, ErrorData> {
+        let mut output = execute_command(cmd, CargoWorkspaceInfoRmcpTool::NAME, ctx).await?;
+        if !output.success() {
+            let stderr = output.stderr.map(|s| s.0).unwrap_or_default();
+            return Err(ErrorData::internal_error(
+                format!("cargo metadata failed: {}", stderr.trim()),
+                None,
+            ));
+        }
+        Self::parse(output.stdout.take())
+    }
+
+    fn parse(stdout: Option<crate::command::Stdout>) -> Result<Self, ErrorData> {
+        let Some(stdout) = stdout else {
+            return Err(ErrorData::internal_error(
+                "cargo metadata command produced no output".to_owned(),
+                None,
+            ));
+        };
+
+        serde_json::from_str(&stdout.0).map_err(|e| {
+            ErrorData::internal_error(format!("failed to parse cargo metadata JSON: {e}"), None)
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
-struct Package {
-    name: String,
+pub(crate) struct Package {
+    #[serde(default)]
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) version: String,
     #[serde(default)]
     description: Option<String>,
-    manifest_path: String,
-    targets: Vec<Target>,
+    /// `None` for the packages in a local directory
+    #[serde(default)]
+    pub(crate) source: Option<String>,
+    pub(crate) manifest_path: String,
+    pub(crate) targets: Vec<Target>,
     #[serde(default)]
     features: HashMap<String, Vec<String>>,
     #[serde(default)]
-    dependencies: Vec<Dependency>,
+    pub(crate) dependencies: Vec<Dependency>,
 }
 
 #[derive(Debug, Deserialize)]
-struct Target {
-    kind: Vec<String>,
+pub(crate) struct Resolve {
+    pub(crate) nodes: Vec<ResolveNode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResolveNode {
+    pub(crate) id: String,
+    /// Features enabled by the resolution of the workspace
+    #[serde(default)]
+    pub(crate) features: Vec<String>,
+    #[serde(default)]
+    pub(crate) deps: Vec<NodeDep>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NodeDep {
+    /// Id of the package the dependency resolved to
+    pub(crate) pkg: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Target {
+    pub(crate) name: String,
+    pub(crate) kind: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, ::serde::Serialize)]
-struct Dependency {
-    name: String,
-    req: String,
+pub(crate) struct Dependency {
+    pub(crate) name: String,
+    pub(crate) req: String,
+    /// "dev" or "build", `None` for normal dependencies
+    #[serde(default, skip_serializing)]
+    pub(crate) kind: Option<String>,
+    /// `None` for path dependencies
+    #[serde(default, skip_serializing)]
+    pub(crate) source: Option<String>,
 }
 
 // Simplified output structures
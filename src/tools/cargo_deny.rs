@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags},
 };
 use rmcp::ErrorData;
@@ -239,9 +239,16 @@ impl Tool for CargoDenyCheckRmcpTool {
     const TITLE: &'static str = "Check dependencies";
     const DESCRIPTION: &'static str = "Checks a project's crate graph for security advisories, license compliance, banned crates.";
     type RequestArgs = CargoDenyCheckRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::read_only().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -272,9 +279,16 @@ impl Tool for CargoDenyInitRmcpTool {
     const TITLE: &'static str = "Initialize cargo-deny config";
     const DESCRIPTION: &'static str = "Creates a cargo-deny config from a template";
     type RequestArgs = CargoDenyInitRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -329,9 +343,16 @@ impl Tool for CargoDenyListRmcpTool {
     const DESCRIPTION: &'static str =
         "Outputs a listing of all licenses and the crates that use them";
     type RequestArgs = CargoDenyListRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -355,8 +376,15 @@ impl Tool for CargoDenyInstallRmcpTool {
     const DESCRIPTION: &'static str =
         "Installs cargo-deny tool for dependency graph analysis and security checks";
     type RequestArgs = CargoDenyInstallRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes().idempotent().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
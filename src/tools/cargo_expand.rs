@@ -3,7 +3,7 @@ use std::process::Command;
 use rmcp::ErrorData;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags},
 };
 
@@ -195,8 +195,15 @@ impl Tool for CargoExpandRmcpTool {
     const TITLE: &'static str = "Cargo Expand";
     const DESCRIPTION: &'static str = "Show the result of macro expansion for Rust code. Requires cargo-expand to be installed (cargo install cargo-expand). Useful for debugging procedural macros, derive macros, and understanding what code macros generate.";
     type RequestArgs = CargoExpandRequest;
-
-    fn call_rmcp_tool(&self, req: Self::RequestArgs) -> Result<crate::Response, rmcp::ErrorData> {
-        execute_command(req.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        req: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, rmcp::ErrorData> {
+        execute_command(req.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{deserialize_string, deserialize_string_vec, output_verbosity_to_cli_flags},
 };
 use rmcp::ErrorData;
@@ -336,9 +336,25 @@ impl Tool for CargoHackRmcpTool {
     const TITLE: &'static str = "Run cargo-hack";
     const DESCRIPTION: &'static str = "Cargo subcommand to provide various options useful for testing and continuous integration, including feature testing and multi-version compatibility. Available commands: check, test, build, clippy. Recommend using 'check' for fast validation. Example: cargo-hack with \"feature_powerset\": true, \"depth\": 3, \"keep_going\": true";
     type RequestArgs = CargoHackRequest;
+    const HINTS: ToolHints = ToolHints::writes().destructive().on_request();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    fn request_hints(request: &Self::RequestArgs) -> ToolHints {
+        // `no_dev_deps` edits Cargo.toml while running and restores it afterwards
+        if request.remove_dev_deps.unwrap_or(false) || request.no_dev_deps.unwrap_or(false) {
+            Self::HINTS
+        } else {
+            ToolHints::read_only()
+        }
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -362,8 +378,15 @@ impl Tool for CargoHackInstallRmcpTool {
     const DESCRIPTION: &'static str =
         "Installs cargo-hack tool for feature testing and continuous integration";
     type RequestArgs = CargoHackInstallRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes().idempotent().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
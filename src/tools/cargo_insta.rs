@@ -3,7 +3,7 @@ use std::process::Command;
 use rmcp::ErrorData;
 
 use crate::{
-    Tool, execute_command,
+    Tool, ToolContext, ToolHints, execute_command,
     serde_utils::{deserialize_string, deserialize_string_vec, output_verbosity_to_cli_flags},
 };
 
@@ -160,9 +160,16 @@ impl Tool for CargoInstaUpdateSnapshotsRmcpTool {
     const TITLE: &'static str = "Update insta snapshots";
     const DESCRIPTION: &'static str = "Runs `cargo test` with the `INSTA_UPDATE` environment variable to update insta snapshot files.";
     type RequestArgs = CargoInstaUpdateSnapshotsRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes().destructive().idempotent();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -0,0 +1,641 @@
+use std::{collections::BTreeMap, fmt::Write as _, path::Path, process::Command};
+
+use crate::{
+    Response, Tool, ToolContext, ToolHints,
+    diagnostics::glob_match,
+    execute_command,
+    serde_utils::{
+        deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
+        output_verbosity_to_cli_flags,
+    },
+    tool::json_schema_impl,
+};
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, Annotations, RawContent, Role},
+};
+
+#[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoLlvmCovRequest {
+    /// The toolchain to use, e.g., "stable" or "nightly".
+    #[serde(default, deserialize_with = "deserialize_string")]
+    toolchain: Option<String>,
+
+    /// If specified, only run tests containing this string in their names
+    #[serde(default, deserialize_with = "deserialize_string")]
+    testname: Option<String>,
+
+    /// Arguments for the test binary (after --)
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    test_args: Option<Vec<String>>,
+
+    /// Run all tests regardless of failure
+    #[serde(default)]
+    no_fail_fast: Option<bool>,
+
+    /// Package(s) to run tests for
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    package: Option<Vec<String>>,
+
+    /// Test all packages in the workspace
+    #[serde(default)]
+    workspace: Option<bool>,
+
+    /// Exclude packages from the test
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    exclude: Option<Vec<String>>,
+
+    /// Test only this package's library
+    #[serde(default)]
+    lib: Option<bool>,
+
+    /// Test all binaries
+    #[serde(default)]
+    bins: Option<bool>,
+
+    /// Test only the specified binary
+    #[serde(default, deserialize_with = "deserialize_string")]
+    bin: Option<String>,
+
+    /// Test all examples
+    #[serde(default)]
+    examples: Option<bool>,
+
+    /// Test only the specified example
+    #[serde(default, deserialize_with = "deserialize_string")]
+    example: Option<String>,
+
+    /// Test all targets that have `test = true` set
+    #[serde(default)]
+    tests: Option<bool>,
+
+    /// Test only the specified test target
+    #[serde(default, deserialize_with = "deserialize_string")]
+    test: Option<String>,
+
+    /// Test all targets that have `bench = true` set
+    #[serde(default)]
+    benches: Option<bool>,
+
+    /// Test only the specified bench target
+    #[serde(default, deserialize_with = "deserialize_string")]
+    bench: Option<String>,
+
+    /// Test all targets (does not include doctests)
+    #[serde(default)]
+    all_targets: Option<bool>,
+
+    /// Include doctests in the coverage (requires a nightly toolchain)
+    #[serde(default)]
+    doc: Option<bool>,
+
+    /// Space or comma separated list of features to activate
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    features: Option<Vec<String>>,
+
+    /// Activate all available features
+    #[serde(default)]
+    all_features: Option<bool>,
+
+    /// Do not activate the `default` feature
+    #[serde(default)]
+    no_default_features: Option<bool>,
+
+    /// Build artifacts in release mode, with optimizations
+    #[serde(default)]
+    release: Option<bool>,
+
+    /// Build artifacts with the specified profile
+    #[serde(default, deserialize_with = "deserialize_string")]
+    profile: Option<String>,
+
+    /// Build for the target triple
+    #[serde(default, deserialize_with = "deserialize_string")]
+    target: Option<String>,
+
+    /// Files to list the uncovered lines for, relative to the workspace root.
+    /// Glob patterns are supported, e.g. "src/parser/**".
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    files: Option<Vec<String>>,
+
+    /// Locking mode for dependency resolution.
+    ///
+    /// Valid options:
+    /// - "locked" (default): Assert that `Cargo.lock` will remain unchanged
+    /// - "unlocked": Allow `Cargo.lock` to be updated
+    /// - "offline": Run without accessing the network
+    /// - "frozen": Equivalent to specifying both --locked and --offline
+    #[serde(default, deserialize_with = "deserialize_string")]
+    locking_mode: Option<String>,
+
+    /// Output verbosity level.
+    ///
+    /// Valid options:
+    /// - "quiet" (default): Show only the essential command output
+    /// - "normal": Show standard output (no additional flags)
+    /// - "verbose": Show detailed output including build information
+    #[serde(default, deserialize_with = "deserialize_string")]
+    output_verbosity: Option<String>,
+}
+
+impl CargoLlvmCovRequest {
+    /// Runs the tests and prints the per-file coverage summary as JSON
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = self.base_cmd(None);
+
+        if let Some(testname) = &self.testname {
+            cmd.arg(testname);
+        }
+
+        if self.no_fail_fast.unwrap_or(false) {
+            cmd.arg("--no-fail-fast");
+        }
+
+        // Target selection
+        if self.lib.unwrap_or(false) {
+            cmd.arg("--lib");
+        }
+
+        if self.bins.unwrap_or(false) {
+            cmd.arg("--bins");
+        }
+
+        if let Some(bin) = &self.bin {
+            cmd.arg("--bin").arg(bin);
+        }
+
+        if self.examples.unwrap_or(false) {
+            cmd.arg("--examples");
+        }
+
+        if let Some(example) = &self.example {
+            cmd.arg("--example").arg(example);
+        }
+
+        if self.tests.unwrap_or(false) {
+            cmd.arg("--tests");
+        }
+
+        if let Some(test) = &self.test {
+            cmd.arg("--test").arg(test);
+        }
+
+        if self.benches.unwrap_or(false) {
+            cmd.arg("--benches");
+        }
+
+        if let Some(bench) = &self.bench {
+            cmd.arg("--bench").arg(bench);
+        }
+
+        if self.all_targets.unwrap_or(false) {
+            cmd.arg("--all-targets");
+        }
+
+        if self.doc.unwrap_or(false) {
+            cmd.arg("--doctests");
+        }
+
+        // Feature selection
+        if let Some(features) = &self.features
+            && !features.is_empty()
+        {
+            cmd.arg("--features").arg(features.join(","));
+        }
+
+        if self.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+
+        if self.no_default_features.unwrap_or(false) {
+            cmd.arg("--no-default-features");
+        }
+
+        // Apply locking mode flags
+        let locking_flags = locking_mode_to_cli_flags(self.locking_mode.as_deref(), "locked")?;
+        cmd.args(locking_flags);
+
+        // Output options
+        let output_flags = output_verbosity_to_cli_flags(self.output_verbosity.as_deref())?;
+        cmd.args(output_flags);
+
+        // The summary is parsed into structured content
+        cmd.arg("--json").arg("--summary-only");
+
+        // Pass test binary args after --
+        if let Some(test_args) = &self.test_args {
+            cmd.arg("--");
+            cmd.args(test_args);
+        }
+
+        Ok(cmd)
+    }
+
+    /// Prints the line coverage of the previous run as LCOV, without running the tests again
+    pub fn build_lcov_report_cmd(&self) -> Command {
+        let mut cmd = self.base_cmd(Some("report"));
+        cmd.arg("--lcov");
+        cmd
+    }
+
+    /// Options that decide where the profile data is and which packages are reported
+    fn base_cmd(&self, subcommand: Option<&str>) -> Command {
+        let mut cmd = Command::new("cargo");
+        if let Some(toolchain) = &self.toolchain {
+            cmd.arg(format!("+{toolchain}"));
+        }
+        cmd.arg("llvm-cov");
+        if let Some(subcommand) = subcommand {
+            cmd.arg(subcommand);
+        }
+
+        // Package selection
+        if let Some(packages) = &self.package {
+            for package in packages {
+                cmd.arg("--package").arg(package);
+            }
+        }
+
+        if self.workspace.unwrap_or(false) {
+            cmd.arg("--workspace");
+        }
+
+        if let Some(excludes) = &self.exclude {
+            for exclude in excludes {
+                cmd.arg("--exclude").arg(exclude);
+            }
+        }
+
+        // Compilation options
+        if self.release.unwrap_or(false) {
+            cmd.arg("--release");
+        }
+
+        if let Some(profile) = &self.profile {
+            cmd.arg("--profile").arg(profile);
+        }
+
+        if let Some(target) = &self.target {
+            cmd.arg("--target").arg(target);
+        }
+
+        cmd
+    }
+
+    fn wants_uncovered_lines(&self, file: &str) -> bool {
+        self.files.iter().flatten().any(|pattern| {
+            let pattern = pattern.trim_start_matches("./");
+            glob_match(pattern, file)
+                || file
+                    .strip_prefix(pattern)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+/// Coverage of the code exercised by the tests
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct CoverageReport {
+    /// Coverage of all reported files
+    totals: CoverageSummary,
+    /// Per-file coverage, paths are relative to the workspace root when possible
+    files: Vec<FileCoverage>,
+}
+
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct FileCoverage {
+    file: String,
+    #[serde(flatten)]
+    summary: CoverageSummary,
+    /// Line ranges that were never executed, only listed for the requested `files`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uncovered_lines: Option<Vec<LineRange>>,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub(crate) struct CoverageSummary {
+    lines: CoverageCount,
+    regions: CoverageCount,
+    functions: CoverageCount,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub(crate) struct CoverageCount {
+    /// Number of instrumented items
+    count: u64,
+    /// Number of items executed at least once
+    covered: u64,
+    percent: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct LineRange {
+    start: u32,
+    end: u32,
+}
+
+/// `llvm-cov export -summary-only` output printed by `cargo llvm-cov --json --summary-only`
+#[derive(serde::Deserialize)]
+struct LlvmCovExport {
+    data: Vec<LlvmCovData>,
+}
+
+#[derive(serde::Deserialize)]
+struct LlvmCovData {
+    files: Vec<LlvmCovFile>,
+    totals: CoverageSummary,
+}
+
+#[derive(serde::Deserialize)]
+struct LlvmCovFile {
+    filename: String,
+    summary: CoverageSummary,
+}
+
+impl CoverageReport {
+    /// Takes the JSON summary out of stdout, leaving the test output behind
+    fn take_from(output: &mut crate::command::Output, root: Option<&Path>) -> Option<Self> {
+        let stdout = output.stdout.take()?.0;
+        let mut report = None;
+        let mut other_lines = Vec::new();
+        for line in stdout.lines() {
+            match serde_json::from_str::<LlvmCovExport>(line) {
+                Ok(export) if report.is_none() => report = Some(export),
+                _ => other_lines.push(line),
+            }
+        }
+        if !other_lines.is_empty() {
+            output.stdout = Some(crate::command::Stdout(other_lines.join("\n")));
+        }
+
+        let data = report?.data.into_iter().next()?;
+        Some(CoverageReport {
+            totals: data.totals,
+            files: data
+                .files
+                .into_iter()
+                .map(|file| FileCoverage {
+                    file: relative_path(&file.filename, root),
+                    summary: file.summary,
+                    uncovered_lines: None,
+                })
+                .collect(),
+        })
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::from("lines    regions  functions  file\n");
+        let summary_line = |out: &mut String, summary: &CoverageSummary, name: &str| {
+            let _ = writeln!(
+                out,
+                "{:>6.2}%  {:>6.2}%  {:>8.2}%  {name}",
+                summary.lines.percent, summary.regions.percent, summary.functions.percent
+            );
+        };
+        for file in &self.files {
+            summary_line(&mut out, &file.summary, &file.file);
+        }
+        summary_line(&mut out, &self.totals, "TOTAL");
+
+        for file in &self.files {
+            let Some(ranges) = &file.uncovered_lines else {
+                continue;
+            };
+            let ranges: Vec<_> = ranges
+                .iter()
+                .map(|range| {
+                    if range.start == range.end {
+                        range.start.to_string()
+                    } else {
+                        format!("{}-{}", range.start, range.end)
+                    }
+                })
+                .collect();
+            if ranges.is_empty() {
+                let _ = write!(out, "\n{}: all lines covered", file.file);
+            } else {
+                let _ = write!(
+                    out,
+                    "\n{}: uncovered lines {}",
+                    file.file,
+                    ranges.join(", ")
+                );
+            }
+        }
+        out.trim_end().to_owned()
+    }
+}
+
+fn relative_path(file: &str, root: Option<&Path>) -> String {
+    root.and_then(|root| Path::new(file).strip_prefix(root).ok())
+        .map_or_else(
+            || file.to_owned(),
+            |path| path.to_string_lossy().into_owned(),
+        )
+}
+
+/// Collects the never executed lines per source file from an LCOV report.
+///
+/// Uncovered lines are merged into one range unless an executed line lies between them,
+/// so blank lines and comments don't split a range.
+fn uncovered_lines(lcov: &str, root: Option<&Path>) -> BTreeMap<String, Vec<LineRange>> {
+    let mut files = BTreeMap::new();
+    let mut current: Option<(String, Vec<LineRange>)> = None;
+    let mut last_executed = 0;
+    for line in lcov.lines() {
+        if let Some(file) = line.strip_prefix("SF:") {
+            current = Some((relative_path(file, root), Vec::new()));
+            last_executed = 0;
+        } else if line == "end_of_record" {
+            if let Some((file, ranges)) = current.take() {
+                files.insert(file, ranges);
+            }
+        } else if let Some((_, ranges)) = &mut current
+            && let Some((number, count)) =
+                line.strip_prefix("DA:").and_then(|da| da.split_once(','))
+            && let Ok(number) = number.parse::<u32>()
+        {
+            // the count is followed by a checksum in some LCOV writers
+            let executed = count.split(',').next().is_some_and(|count| count != "0");
+            if executed {
+                last_executed = number;
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(range) if range.end > last_executed => range.end = number,
+                _ => ranges.push(LineRange {
+                    start: number,
+                    end: number,
+                }),
+            }
+        }
+    }
+    files
+}
+
+pub struct CargoLlvmCovRmcpTool;
+
+impl Tool for CargoLlvmCovRmcpTool {
+    const NAME: &'static str = "cargo-llvm-cov";
+    const TITLE: &'static str = "Measure test coverage";
+    const DESCRIPTION: &'static str = "Runs the tests with cargo-llvm-cov and returns the line, region and function coverage per file. \
+        Pass `files` to also list the line ranges that the tests never executed. Requires cargo-llvm-cov to be installed (see #cargo-llvm-cov-install).";
+    type RequestArgs = CargoLlvmCovRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<CoverageReport>())
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let root = ctx
+            .workspace_root()
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::current_dir().ok());
+        let root = root.as_deref();
+
+        let mut output = execute_command(request.build_cmd()?, Self::NAME, &ctx).await?;
+        let Some(mut report) = CoverageReport::take_from(&mut output, root) else {
+            return Ok(output.into());
+        };
+
+        if report
+            .files
+            .iter()
+            .any(|file| request.wants_uncovered_lines(&file.file))
+        {
+            let lcov = execute_command(request.build_lcov_report_cmd(), Self::NAME, &ctx).await?;
+            let mut uncovered = match (lcov.success(), lcov.stdout) {
+                (true, Some(stdout)) => uncovered_lines(&stdout.0, root),
+                _ => {
+                    return Err(ErrorData::internal_error(
+                        "Failed to export the uncovered lines with `cargo llvm-cov report --lcov`",
+                        None,
+                    ));
+                }
+            };
+            for file in &mut report.files {
+                if request.wants_uncovered_lines(&file.file) {
+                    file.uncovered_lines = Some(uncovered.remove(&file.file).unwrap_or_default());
+                }
+            }
+        }
+
+        let mut response: Response = output.into();
+        let mut annotations = Annotations::default();
+        annotations.audience = Some(vec![Role::User, Role::Assistant]);
+        annotations.priority = Some(1.);
+        response.add_content(RawContent::text(report.render()).annotate(annotations));
+        response.set_structured_content(&report);
+        Ok(response)
+    }
+}
+
+#[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoLlvmCovInstallRequest {}
+
+impl CargoLlvmCovInstallRequest {
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        cmd.arg("install").arg("cargo-llvm-cov").arg("--locked");
+
+        Ok(cmd)
+    }
+}
+
+pub struct CargoLlvmCovInstallRmcpTool;
+
+impl Tool for CargoLlvmCovInstallRmcpTool {
+    const NAME: &'static str = "cargo-llvm-cov-install";
+    const TITLE: &'static str = "Install cargo-llvm-cov";
+    const DESCRIPTION: &'static str = "Installs cargo-llvm-cov tool for measuring code coverage. It also needs the `llvm-tools-preview` rustup component.";
+    type RequestArgs = CargoLlvmCovInstallRequest;
+    const HINTS: ToolHints = ToolHints::writes().idempotent().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::{CommandLine, ExitStatus, Output, Stdout};
+
+    use super::*;
+
+    const SUMMARY: &str = r#"{"data":[{"files":[{"filename":"/ws/src/lib.rs","summary":{"branches":{"count":0,"covered":0,"notcovered":0,"percent":0},"functions":{"count":4,"covered":3,"percent":75.0},"instantiations":{"count":4,"covered":3,"percent":75.0},"lines":{"count":20,"covered":15,"percent":75.0},"regions":{"count":10,"covered":6,"notcovered":4,"percent":60.0}}}],"totals":{"branches":{"count":0,"covered":0,"notcovered":0,"percent":0},"functions":{"count":4,"covered":3,"percent":75.0},"instantiations":{"count":4,"covered":3,"percent":75.0},"lines":{"count":20,"covered":15,"percent":75.0},"regions":{"count":10,"covered":6,"notcovered":4,"percent":60.0}}}],"type":"llvm.coverage.json.export","version":"2.0.1"}"#;
+
+    const LCOV: &str = "SF:/ws/src/lib.rs
+FN:1,add
+FNDA:1,add
+DA:1,1
+DA:2,1
+DA:5,0
+DA:7,0
+DA:8,3
+DA:10,0
+LF:6
+LH:3
+end_of_record
+SF:/ws/src/main.rs
+DA:1,0
+end_of_record
+";
+
+    #[test]
+    fn test_parse_summary() {
+        let mut output = Output {
+            tool_name: "cargo-llvm-cov".into(),
+            cmd_line: CommandLine("cargo llvm-cov --json --summary-only".into()),
+            stdout: Some(Stdout(format!("running 1 test\n{SUMMARY}"))),
+            stderr: None,
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
+        };
+        let report = CoverageReport::take_from(&mut output, Some(Path::new("/ws"))).unwrap();
+
+        assert_eq!(output.stdout.unwrap().0, "running 1 test");
+        assert_eq!(report.files[0].file, "src/lib.rs");
+        assert_eq!(report.files[0].summary.regions.covered, 6);
+        assert_eq!(report.totals.lines.percent, 75.0);
+        assert_eq!(
+            report.render(),
+            "lines    regions  functions  file\n 75.00%   60.00%     75.00%  src/lib.rs\n 75.00%   60.00%     75.00%  TOTAL"
+        );
+    }
+
+    #[test]
+    fn test_uncovered_lines() {
+        let files = uncovered_lines(LCOV, Some(Path::new("/ws")));
+
+        assert_eq!(
+            files["src/lib.rs"],
+            [
+                LineRange { start: 5, end: 7 },
+                LineRange { start: 10, end: 10 }
+            ]
+        );
+        assert_eq!(files["src/main.rs"], [LineRange { start: 1, end: 1 }]);
+    }
+
+    #[test]
+    fn test_wants_uncovered_lines() {
+        let request: CargoLlvmCovRequest = serde_json::from_value(serde_json::json!({
+            "files": ["./src/parser", "src/*.rs"],
+        }))
+        .unwrap();
+
+        assert!(request.wants_uncovered_lines("src/lib.rs"));
+        assert!(request.wants_uncovered_lines("src/parser/expr.rs"));
+        assert!(!request.wants_uncovered_lines("src/lexer/token.rs"));
+    }
+}
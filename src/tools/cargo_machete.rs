@@ -1,6 +1,6 @@
 use std::process::Command;
 
-use crate::{Tool, execute_command, serde_utils::deserialize_string_vec};
+use crate::{Tool, ToolContext, ToolHints, execute_command, serde_utils::deserialize_string_vec};
 use rmcp::ErrorData;
 
 #[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
@@ -65,9 +65,24 @@ impl Tool for CargoMacheteRmcpTool {
     const TITLE: &'static str = "Find unused dependencies";
     const DESCRIPTION: &'static str = "Finds unused dependencies in a fast yet imprecise way. Helps identify dependencies that are declared in Cargo.toml but not actually used in the code.";
     type RequestArgs = CargoMacheteRequest;
+    const HINTS: ToolHints = ToolHints::writes().destructive().idempotent().on_request();
 
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    fn request_hints(request: &Self::RequestArgs) -> ToolHints {
+        if request.fix.unwrap_or(false) {
+            Self::HINTS
+        } else {
+            ToolHints::read_only()
+        }
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
 
@@ -90,8 +105,15 @@ impl Tool for CargoMacheteInstallRmcpTool {
     const TITLE: &'static str = "Install cargo-machete";
     const DESCRIPTION: &'static str = "Installs cargo-machete tool for finding unused dependencies";
     type RequestArgs = CargoMacheteInstallRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::writes().idempotent().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
@@ -0,0 +1,422 @@
+use std::process::Command;
+
+use crate::{
+    Tool, ToolContext, ToolHints, execute_command,
+    serde_utils::{
+        deserialize_string, deserialize_string_vec, locking_mode_to_cli_flags,
+        output_verbosity_to_cli_flags,
+    },
+    test_report::TestReport,
+    tool::json_schema_impl,
+};
+use rmcp::ErrorData;
+
+/// Enables nextest's libtest-compatible JSON output, which is still experimental
+const LIBTEST_JSON_ENV: &str = "NEXTEST_EXPERIMENTAL_LIBTEST_JSON";
+
+/// Package, target and filter options shared by `cargo nextest run` and `cargo nextest list`
+#[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
+pub struct NextestSelection {
+    /// The toolchain to use, e.g., "stable" or "nightly".
+    #[serde(default, deserialize_with = "deserialize_string")]
+    toolchain: Option<String>,
+
+    /// Package(s) to test
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    package: Option<Vec<String>>,
+
+    /// Test all packages in the workspace
+    #[serde(default)]
+    workspace: Option<bool>,
+
+    /// Exclude packages from the test
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    exclude: Option<Vec<String>>,
+
+    /// Test only this package's library
+    #[serde(default)]
+    lib: Option<bool>,
+
+    /// Test only the specified test target
+    #[serde(default, deserialize_with = "deserialize_string")]
+    test: Option<String>,
+
+    /// Test all targets
+    #[serde(default)]
+    all_targets: Option<bool>,
+
+    /// Space or comma separated list of features to activate
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    features: Option<Vec<String>>,
+
+    /// Activate all available features
+    #[serde(default)]
+    all_features: Option<bool>,
+
+    /// Do not activate the `default` feature
+    #[serde(default)]
+    no_default_features: Option<bool>,
+
+    /// Build artifacts in release mode, with optimizations
+    #[serde(default)]
+    release: Option<bool>,
+
+    /// Build artifacts with the specified cargo profile
+    #[serde(default, deserialize_with = "deserialize_string")]
+    cargo_profile: Option<String>,
+
+    /// Nextest profile from `.config/nextest.toml`, e.g. "ci"
+    #[serde(default, deserialize_with = "deserialize_string")]
+    profile: Option<String>,
+
+    /// Filterset expressions (`-E`), e.g. "test(parse) & package(my_crate)".
+    /// Tests matching any of the expressions are selected.
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    filterset: Option<Vec<String>>,
+
+    /// Only select tests whose names contain one of these strings
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    filters: Option<Vec<String>>,
+
+    /// Select a subset of the tests, e.g. "count:1/3" or "hash:2/3"
+    #[serde(default, deserialize_with = "deserialize_string")]
+    partition: Option<String>,
+
+    /// Which ignored tests to select: "default" (skip ignored tests), "only" or "all"
+    #[serde(default, deserialize_with = "deserialize_string")]
+    run_ignored: Option<String>,
+
+    /// Locking mode for dependency resolution.
+    ///
+    /// Valid options:
+    /// - "locked" (default): Assert that `Cargo.lock` will remain unchanged
+    /// - "unlocked": Allow `Cargo.lock` to be updated
+    /// - "offline": Run without accessing the network
+    /// - "frozen": Equivalent to specifying both --locked and --offline
+    #[serde(default, deserialize_with = "deserialize_string")]
+    locking_mode: Option<String>,
+
+    /// Output verbosity level of the cargo build.
+    ///
+    /// Valid options:
+    /// - "quiet" (default): Show only the essential command output
+    /// - "normal": Show standard output (no additional flags)
+    /// - "verbose": Show detailed output including build information
+    #[serde(default, deserialize_with = "deserialize_string")]
+    output_verbosity: Option<String>,
+}
+
+impl NextestSelection {
+    fn build_cmd(&self, subcommand: &str) -> Result<Command, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        if let Some(toolchain) = &self.toolchain {
+            cmd.arg(format!("+{toolchain}"));
+        }
+        cmd.arg("nextest").arg(subcommand);
+
+        if let Some(filters) = &self.filters {
+            cmd.args(filters);
+        }
+
+        // Package selection
+        if let Some(packages) = &self.package {
+            for package in packages {
+                cmd.arg("--package").arg(package);
+            }
+        }
+
+        if self.workspace.unwrap_or(false) {
+            cmd.arg("--workspace");
+        }
+
+        if let Some(excludes) = &self.exclude {
+            for exclude in excludes {
+                cmd.arg("--exclude").arg(exclude);
+            }
+        }
+
+        // Target selection
+        if self.lib.unwrap_or(false) {
+            cmd.arg("--lib");
+        }
+
+        if let Some(test) = &self.test {
+            cmd.arg("--test").arg(test);
+        }
+
+        if self.all_targets.unwrap_or(false) {
+            cmd.arg("--all-targets");
+        }
+
+        // Feature selection
+        if let Some(features) = &self.features
+            && !features.is_empty()
+        {
+            cmd.arg("--features").arg(features.join(","));
+        }
+
+        if self.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+
+        if self.no_default_features.unwrap_or(false) {
+            cmd.arg("--no-default-features");
+        }
+
+        // Compilation options
+        if self.release.unwrap_or(false) {
+            cmd.arg("--release");
+        }
+
+        if let Some(cargo_profile) = &self.cargo_profile {
+            cmd.arg("--cargo-profile").arg(cargo_profile);
+        }
+
+        // Test selection
+        if let Some(profile) = &self.profile {
+            cmd.arg("--profile").arg(profile);
+        }
+
+        if let Some(filterset) = &self.filterset {
+            for expr in filterset {
+                cmd.arg("-E").arg(expr);
+            }
+        }
+
+        if let Some(partition) = &self.partition {
+            cmd.arg("--partition").arg(partition);
+        }
+
+        if let Some(run_ignored) = &self.run_ignored {
+            let allowed = ["default", "only", "all"];
+            if !allowed.contains(&run_ignored.as_str()) {
+                return Err(ErrorData::invalid_params(
+                    format!(
+                        "Invalid run_ignored '{run_ignored}'. Allowed values: {}",
+                        allowed.join(", ")
+                    ),
+                    None,
+                ));
+            }
+            cmd.arg("--run-ignored").arg(run_ignored);
+        }
+
+        // Apply locking mode flags
+        let locking_flags = locking_mode_to_cli_flags(self.locking_mode.as_deref(), "locked")?;
+        cmd.args(locking_flags);
+
+        // Output options, nextest forwards the cargo verbosity with dedicated flags
+        let output_flags = output_verbosity_to_cli_flags(self.output_verbosity.as_deref())?;
+        cmd.args(output_flags.into_iter().map(|flag| match flag {
+            "--quiet" => "--cargo-quiet",
+            "--verbose" => "--cargo-verbose",
+            flag => flag,
+        }));
+
+        Ok(cmd)
+    }
+}
+
+#[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoNextestRunRequest {
+    #[serde(flatten)]
+    selection: NextestSelection,
+
+    /// Number of times to retry failing tests
+    #[serde(default)]
+    retries: Option<u32>,
+
+    /// Run all tests regardless of failure
+    #[serde(default)]
+    no_fail_fast: Option<bool>,
+
+    /// Number of tests to run simultaneously
+    #[serde(default)]
+    test_threads: Option<u32>,
+}
+
+impl CargoNextestRunRequest {
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = self.selection.build_cmd("run")?;
+
+        if let Some(retries) = self.retries {
+            cmd.arg("--retries").arg(retries.to_string());
+        }
+
+        if self.no_fail_fast.unwrap_or(false) {
+            cmd.arg("--no-fail-fast");
+        }
+
+        if let Some(test_threads) = self.test_threads {
+            cmd.arg("--test-threads").arg(test_threads.to_string());
+        }
+
+        // Test results are parsed into structured content
+        cmd.env(LIBTEST_JSON_ENV, "1");
+        cmd.arg("--message-format").arg("libtest-json");
+
+        Ok(cmd)
+    }
+}
+
+pub struct CargoNextestRunRmcpTool;
+
+impl Tool for CargoNextestRunRmcpTool {
+    const NAME: &'static str = "cargo-nextest-run";
+    const TITLE: &'static str = "Run tests with cargo-nextest";
+    const DESCRIPTION: &'static str = "Runs tests with cargo-nextest, which executes every test in its own process. Supports nextest profiles, filterset expressions, partitioning and retries. Requires cargo-nextest to be installed (see #cargo-nextest-install).";
+    type RequestArgs = CargoNextestRunRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<TestReport>())
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        let mut output = execute_command(request.build_cmd()?, Self::NAME, &ctx).await?;
+
+        let report = TestReport::take_from(&mut output, false);
+        let mut response: crate::Response = output.into();
+        report.attach_to(&mut response);
+        Ok(response)
+    }
+}
+
+#[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoNextestListRequest {
+    #[serde(flatten)]
+    selection: NextestSelection,
+
+    /// Output format: "human" (default), "oneline", "json" or "json-pretty"
+    #[serde(default, deserialize_with = "deserialize_string")]
+    message_format: Option<String>,
+}
+
+impl CargoNextestListRequest {
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = self.selection.build_cmd("list")?;
+
+        if let Some(message_format) = &self.message_format {
+            cmd.arg("--message-format").arg(message_format);
+        }
+
+        Ok(cmd)
+    }
+}
+
+pub struct CargoNextestListRmcpTool;
+
+impl Tool for CargoNextestListRmcpTool {
+    const NAME: &'static str = "cargo-nextest-list";
+    const TITLE: &'static str = "List tests with cargo-nextest";
+    const DESCRIPTION: &'static str = "Lists the tests cargo-nextest would run with the given profile, filtersets and partition, without running them. Requires cargo-nextest to be installed (see #cargo-nextest-install).";
+    type RequestArgs = CargoNextestListRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
+    }
+}
+
+#[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoNextestInstallRequest {}
+
+impl CargoNextestInstallRequest {
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        cmd.arg("install").arg("cargo-nextest").arg("--locked");
+
+        Ok(cmd)
+    }
+}
+
+pub struct CargoNextestInstallRmcpTool;
+
+impl Tool for CargoNextestInstallRmcpTool {
+    const NAME: &'static str = "cargo-nextest-install";
+    const TITLE: &'static str = "Install cargo-nextest";
+    const DESCRIPTION: &'static str =
+        "Installs cargo-nextest, a next-generation test runner for Rust";
+    type RequestArgs = CargoNextestInstallRequest;
+    const HINTS: ToolHints = ToolHints::writes().idempotent().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_run_cmd() {
+        let request: CargoNextestRunRequest = serde_json::from_value(json!({
+            "package": "my_crate",
+            "profile": "ci",
+            "filterset": ["test(parse)", "package(other)"],
+            "partition": "count:1/2",
+            "retries": 2,
+            "no_fail_fast": true,
+        }))
+        .unwrap();
+        let cmd = request.build_cmd().unwrap();
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+
+        assert_eq!(
+            args,
+            [
+                "nextest",
+                "run",
+                "--package",
+                "my_crate",
+                "--profile",
+                "ci",
+                "-E",
+                "test(parse)",
+                "-E",
+                "package(other)",
+                "--partition",
+                "count:1/2",
+                "--locked",
+                "--cargo-quiet",
+                "--retries",
+                "2",
+                "--no-fail-fast",
+                "--message-format",
+                "libtest-json",
+            ]
+        );
+        assert!(
+            cmd.get_envs()
+                .any(|(key, value)| key == LIBTEST_JSON_ENV && value.is_some())
+        );
+    }
+
+    #[test]
+    fn test_list_cmd_rejects_unknown_run_ignored() {
+        let request: CargoNextestListRequest = serde_json::from_value(json!({
+            "filters": "parse",
+            "run_ignored": "sometimes",
+        }))
+        .unwrap();
+        assert!(request.build_cmd().is_err());
+    }
+}
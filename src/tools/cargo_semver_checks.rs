@@ -0,0 +1,768 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    Response, Tool, ToolContext, ToolHints,
+    command::{Output, Stdout},
+    execute_command,
+    serde_utils::{deserialize_string, deserialize_string_vec},
+    tool::json_schema_impl,
+    tools::cargo::cargo_home,
+};
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, Annotations, RawContent, Role},
+};
+
+#[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoSemverChecksRequest {
+    /// The toolchain used to build the rustdoc JSON of both versions, e.g., "stable" or "nightly".
+    #[serde(default, deserialize_with = "deserialize_string")]
+    toolchain: Option<String>,
+
+    /// Package(s) to check
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    package: Option<Vec<String>>,
+
+    /// Check all packages in the workspace
+    #[serde(default)]
+    workspace: Option<bool>,
+
+    /// Exclude packages from the check
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    exclude: Option<Vec<String>>,
+
+    /// Path to Cargo.toml
+    #[serde(default, deserialize_with = "deserialize_string")]
+    manifest_path: Option<String>,
+
+    /// Git revision to compare against, e.g. "main", "v1.2.0" or a commit hash
+    #[serde(default, deserialize_with = "deserialize_string")]
+    baseline_rev: Option<String>,
+
+    /// Directory containing the baseline sources of the package(s), e.g. another checkout
+    #[serde(default, deserialize_with = "deserialize_string")]
+    baseline_root: Option<String>,
+
+    /// Published version to compare against, e.g. "1.2.0". When a single `package` is checked and
+    /// this version is in the local registry cache, the cached sources are used without network
+    /// access, otherwise the version is downloaded from the registry.
+    /// Defaults to the latest published version if no baseline is given.
+    #[serde(default, deserialize_with = "deserialize_string")]
+    baseline_version: Option<String>,
+
+    /// Release type to check for instead of deriving it from the version numbers: "major", "minor" or "patch"
+    #[serde(default, deserialize_with = "deserialize_string")]
+    release_type: Option<String>,
+
+    /// Features to enable in both the baseline and the current version
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    features: Option<Vec<String>>,
+
+    /// Features to enable in the baseline version only
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    baseline_features: Option<Vec<String>>,
+
+    /// Features to enable in the current version only
+    #[serde(default, deserialize_with = "deserialize_string_vec")]
+    current_features: Option<Vec<String>>,
+
+    /// Enable all features, including the `unstable`, `nightly` and `_`-prefixed ones that are
+    /// left out by default
+    #[serde(default)]
+    all_features: Option<bool>,
+
+    /// Use only the default features plus the ones given in `features`, instead of all the
+    /// features except unstable ones
+    #[serde(default)]
+    default_features: Option<bool>,
+
+    /// Use only the features given in `features`, `baseline_features` and `current_features`
+    #[serde(default)]
+    only_explicit_features: Option<bool>,
+
+    /// Build for the target triple, to check platform-specific APIs
+    #[serde(default, deserialize_with = "deserialize_string")]
+    target: Option<String>,
+}
+
+impl CargoSemverChecksRequest {
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        if let Some(toolchain) = &self.toolchain {
+            cmd.arg(format!("+{toolchain}"));
+        }
+        cmd.arg("semver-checks");
+
+        // Package selection
+        if let Some(packages) = &self.package {
+            for package in packages {
+                cmd.arg("--package").arg(package);
+            }
+        }
+
+        if self.workspace.unwrap_or(false) {
+            cmd.arg("--workspace");
+        }
+
+        if let Some(excludes) = &self.exclude {
+            for exclude in excludes {
+                cmd.arg("--exclude").arg(exclude);
+            }
+        }
+
+        if let Some(manifest_path) = &self.manifest_path {
+            cmd.arg("--manifest-path").arg(manifest_path);
+        }
+
+        // Baseline
+        let baselines = [
+            self.baseline_rev.is_some(),
+            self.baseline_root.is_some(),
+            self.baseline_version.is_some(),
+        ];
+        if baselines.into_iter().filter(|given| *given).count() > 1 {
+            return Err(ErrorData::invalid_params(
+                "Only one of `baseline_rev`, `baseline_root` and `baseline_version` can be given",
+                None,
+            ));
+        }
+
+        if let Some(rev) = &self.baseline_rev {
+            cmd.arg("--baseline-rev").arg(rev);
+        }
+
+        if let Some(root) = &self.baseline_root {
+            cmd.arg("--baseline-root").arg(root);
+        }
+
+        if let Some(version) = &self.baseline_version {
+            match self.cached_sources(version) {
+                Some(sources) => cmd.arg("--baseline-root").arg(sources),
+                None => cmd.arg("--baseline-version").arg(version),
+            };
+        }
+
+        if let Some(release_type) = &self.release_type {
+            let allowed = ["major", "minor", "patch"];
+            if !allowed.contains(&release_type.as_str()) {
+                return Err(ErrorData::invalid_params(
+                    format!(
+                        "Invalid release_type '{release_type}'. Allowed values: {}",
+                        allowed.join(", ")
+                    ),
+                    None,
+                ));
+            }
+            cmd.arg("--release-type").arg(release_type);
+        }
+
+        // Feature selection
+        for (flag, features) in [
+            ("--features", &self.features),
+            ("--baseline-features", &self.baseline_features),
+            ("--current-features", &self.current_features),
+        ] {
+            for feature in features.iter().flatten() {
+                cmd.arg(flag).arg(feature);
+            }
+        }
+
+        if self.all_features.unwrap_or(false) {
+            cmd.arg("--all-features");
+        }
+
+        if self.default_features.unwrap_or(false) {
+            cmd.arg("--default-features");
+        }
+
+        if self.only_explicit_features.unwrap_or(false) {
+            cmd.arg("--only-explicit-features");
+        }
+
+        if let Some(target) = &self.target {
+            cmd.arg("--target").arg(target);
+        }
+
+        // The report is parsed into structured content
+        cmd.arg("--color").arg("never");
+
+        Ok(cmd)
+    }
+
+    /// Sources of `version` of the checked package unpacked by cargo in the local registry cache
+    fn cached_sources(&self, version: &str) -> Option<PathBuf> {
+        let [package] = self.package.as_deref()? else {
+            return None;
+        };
+        // One directory per registry, e.g. `index.crates.io-1949cf8c6b5b557f`
+        std::fs::read_dir(cargo_home()?.join("registry").join("src"))
+            .ok()?
+            .flatten()
+            .map(|registry| registry.path().join(format!("{package}-{version}")))
+            .find(|sources| sources.join("Cargo.toml").is_file())
+    }
+}
+
+/// Breaking changes found by `cargo semver-checks`
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct SemverReport {
+    /// Checked packages, in the order they were checked
+    packages: Vec<PackageReport>,
+    /// One entry per affected item of every failed lint
+    changes: Vec<SemverChange>,
+}
+
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct PackageReport {
+    name: String,
+    /// Compared versions and the release type, e.g. "v0.1.0 -> v0.2.0 (minor change)"
+    versions: String,
+    /// Number of lints that failed
+    failed: usize,
+    /// Number of lints that only warned
+    warnings: usize,
+    /// Version bump the changes require, missing if the API is compatible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    required_bump: Option<SemverBump>,
+    /// Summary printed by cargo-semver-checks, e.g. "semver requires new major version: 1 major and 0 minor checks failed"
+    summary: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct SemverChange {
+    /// Package the change was found in, missing if it couldn't be attributed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    package: Option<String>,
+    /// Lint id, explained by `cargo semver-checks --explain <lint>`
+    lint: String,
+    level: LintLevel,
+    /// Version bump the lint requires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    required_bump: Option<SemverBump>,
+    /// What the lint detects, e.g. "pub fn removed or renamed"
+    title: String,
+    /// The affected item as described by the lint, e.g. "function my_crate::parse"
+    item: String,
+    /// Path of the affected item, e.g. "my_crate::parse"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    /// Source location, in the baseline sources for removed items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LintLevel {
+    Failure,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SemverBump {
+    Major,
+    Minor,
+}
+
+impl SemverBump {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "major" => Some(SemverBump::Major),
+            "minor" => Some(SemverBump::Minor),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            SemverBump::Major => "major",
+            SemverBump::Minor => "minor",
+        }
+    }
+}
+
+impl SemverReport {
+    /// Parses the report printed on stdout and the per-package status lines printed on stderr,
+    /// taking the report out of stdout. Empty if no package was checked.
+    fn take_from(output: &mut Output, root: Option<&Path>) -> Self {
+        let stderr = output.stderr.as_ref().map(|stderr| stderr.0.as_str());
+        let packages = parse_packages(stderr.unwrap_or_default());
+        if packages.is_empty() {
+            return Self::default();
+        }
+        let stdout = output
+            .stdout
+            .take()
+            .map(|stdout| stdout.0)
+            .unwrap_or_default();
+        let (mut changes, sections, other_lines) = parse_changes(&stdout, root);
+        if !other_lines.is_empty() {
+            output.stdout = Some(Stdout(other_lines.join("\n")));
+        }
+
+        // Packages are checked one after the other, each printing its failed and warning lints
+        let owners: Vec<&String> = packages
+            .iter()
+            .flat_map(|package| {
+                std::iter::repeat_n(&package.name, package.failed + package.warnings)
+            })
+            .collect();
+        if owners.len() == sections {
+            for change in &mut changes {
+                change.change.package = Some(owners[change.section].clone());
+            }
+        }
+
+        SemverReport {
+            packages,
+            changes: changes.into_iter().map(|change| change.change).collect(),
+        }
+    }
+
+    /// Adds the rendered report to the response if a package was checked, and the report as
+    /// structured content in any case
+    fn attach_to(self, response: &mut Response) {
+        if !self.packages.is_empty() {
+            let mut annotations = Annotations::default();
+            annotations.audience = Some(vec![Role::User, Role::Assistant]);
+            annotations.priority = Some(1.);
+            response.add_content(RawContent::text(self.render()).annotate(annotations));
+        }
+        response.set_structured_content(&self);
+    }
+
+    /// Fills in the version bump each lint requires, from `cargo semver-checks --list`
+    fn set_required_bumps(&mut self, list: &str) {
+        let bumps: HashMap<&str, SemverBump> = list
+            .lines()
+            .filter_map(|line| {
+                let mut columns = line.split_whitespace();
+                Some((columns.next()?, SemverBump::parse(columns.next()?)?))
+            })
+            .collect();
+        for change in &mut self.changes {
+            change.required_bump = bumps.get(change.lint.as_str()).copied();
+        }
+    }
+
+    /// Lists the changes under their package, or after all the packages if some couldn't be attributed
+    fn render(&self) -> String {
+        let attributed = self.changes.iter().all(|change| change.package.is_some());
+        let mut out = String::new();
+        for package in &self.packages {
+            let _ = writeln!(
+                out,
+                "{} {}: {}",
+                package.name, package.versions, package.summary
+            );
+            if attributed {
+                self.changes
+                    .iter()
+                    .filter(|change| change.package.as_ref() == Some(&package.name))
+                    .for_each(|change| render_change(&mut out, change));
+            }
+        }
+        if !attributed {
+            self.changes
+                .iter()
+                .for_each(|change| render_change(&mut out, change));
+        }
+        out.trim_end().to_owned()
+    }
+}
+
+fn render_change(out: &mut String, change: &SemverChange) {
+    let bump = change.required_bump.map_or("", SemverBump::as_str);
+    let level = match change.level {
+        LintLevel::Failure => "",
+        LintLevel::Warning => " (warning)",
+    };
+    let _ = write!(out, "- {bump} {}{level}: {}", change.lint, change.item);
+    if let Some(location) = &change.location {
+        let _ = write!(out, " at {location}");
+    }
+    out.push('\n');
+}
+
+/// Reads the `Checking`, `Checked` and `Summary` status lines of each package
+fn parse_packages(stderr: &str) -> Vec<PackageReport> {
+    let mut packages: Vec<PackageReport> = Vec::new();
+    for line in stderr.lines().map(str::trim) {
+        if let Some(checking) = line.strip_prefix("Checking ") {
+            let (name, versions) = checking.split_once(' ').unwrap_or((checking, ""));
+            packages.push(PackageReport {
+                name: name.to_owned(),
+                versions: versions.to_owned(),
+                ..PackageReport::default()
+            });
+        } else if let Some(package) = packages.last_mut() {
+            if let Some(checked) = line.strip_prefix("Checked ") {
+                // e.g. "[   0.087s] 202 checks: 198 pass, 4 fail, 0 warn, 58 skip"
+                let counts = checked.split_once(": ").map_or("", |(_, counts)| counts);
+                for count in counts.split(", ") {
+                    match count.split_once(' ') {
+                        Some((n, "fail")) => package.failed = n.parse().unwrap_or_default(),
+                        Some((n, "warn")) => package.warnings = n.parse().unwrap_or_default(),
+                        _ => {}
+                    }
+                }
+            } else if let Some(summary) = line.strip_prefix("Summary ") {
+                package.summary = summary.to_owned();
+                package.required_bump = if summary.starts_with("semver requires new major version")
+                {
+                    Some(SemverBump::Major)
+                } else if summary.starts_with("semver requires new minor version") {
+                    Some(SemverBump::Minor)
+                } else {
+                    None
+                };
+            }
+        }
+    }
+    packages
+}
+
+struct ParsedChange {
+    /// Index of the lint section the change was listed in
+    section: usize,
+    change: SemverChange,
+}
+
+/// Reads the `--- failure lint: title ---` sections of the report, returning the changes, the
+/// number of lint sections and the lines outside of them
+fn parse_changes<'a>(
+    stdout: &'a str,
+    root: Option<&Path>,
+) -> (Vec<ParsedChange>, usize, Vec<&'a str>) {
+    let mut changes = Vec::new();
+    let mut sections = 0;
+    let mut other_lines = Vec::new();
+    let mut current: Option<(LintLevel, &str, &str)> = None;
+    let mut in_items = false;
+    for line in stdout.lines() {
+        if let Some(header) = line
+            .strip_prefix("--- ")
+            .and_then(|l| l.strip_suffix(" ---"))
+        {
+            let (level, rest) = header.split_once(' ').unwrap_or((header, ""));
+            let level = match level {
+                "failure" => LintLevel::Failure,
+                "warning" => LintLevel::Warning,
+                _ => {
+                    other_lines.push(line);
+                    continue;
+                }
+            };
+            let (lint, title) = rest.split_once(": ").unwrap_or((rest, ""));
+            current = Some((level, lint, title));
+            sections += 1;
+            in_items = false;
+        } else if let Some((level, lint, title)) = current {
+            if line == "Failed in:" {
+                in_items = true;
+            } else if in_items && let Some(item) = line.strip_prefix("  ") {
+                let (item, location) = split_location(item, root);
+                changes.push(ParsedChange {
+                    section: sections - 1,
+                    change: SemverChange {
+                        package: None,
+                        lint: lint.to_owned(),
+                        level,
+                        required_bump: None,
+                        title: title.to_owned(),
+                        path: item_path(&item),
+                        item,
+                        location,
+                    },
+                });
+            } else if in_items && !line.is_empty() {
+                in_items = false;
+            }
+        } else if !line.trim().is_empty() {
+            other_lines.push(line);
+        }
+    }
+    (changes, sections, other_lines)
+}
+
+/// Splits `function my_crate::parse, previously in file /ws/src/lib.rs:1` into the item and its
+/// location, relative to the workspace root
+fn split_location(item: &str, root: Option<&Path>) -> (String, Option<String>) {
+    let Some((item, location)) = item.rsplit_once(" in ") else {
+        return (item.to_owned(), None);
+    };
+    let item = item.trim_end_matches(", previously").trim_end_matches(',');
+    let location = location.strip_prefix("file ").unwrap_or(location);
+    let location = root
+        .and_then(|root| Path::new(location).strip_prefix(root).ok())
+        .map_or_else(
+            || location.to_owned(),
+            |path| path.to_string_lossy().into_owned(),
+        );
+    (item.to_owned(), Some(location))
+}
+
+/// First `::` separated path in the item description
+fn item_path(item: &str) -> Option<String> {
+    item.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != ':'))
+        .find(|word| word.contains("::"))
+        .map(str::to_owned)
+}
+
+pub struct CargoSemverChecksRmcpTool;
+
+impl Tool for CargoSemverChecksRmcpTool {
+    const NAME: &'static str = "cargo-semver-checks";
+    const TITLE: &'static str = "Check semver compatibility";
+    const DESCRIPTION: &'static str = "Compares the public API of library crates with a baseline (a git revision, a directory or a published version, by default the latest published one) with cargo-semver-checks. \
+        Returns the breaking changes with their lint, affected item and the version bump they require. Run it before publishing a new version. \
+        Requires cargo-semver-checks to be installed (see #cargo-semver-checks-install).";
+    type RequestArgs = CargoSemverChecksRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<SemverReport>())
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let root = ctx
+            .workspace_root()
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::current_dir().ok());
+
+        let mut output = execute_command(request.build_cmd()?, Self::NAME, &ctx).await?;
+        let mut report = SemverReport::take_from(&mut output, root.as_deref());
+
+        if !report.changes.is_empty() {
+            let mut cmd = Command::new("cargo");
+            cmd.arg("semver-checks").arg("--list");
+            let mut list = execute_command(cmd, Self::NAME, &ctx).await?;
+            if list.success()
+                && let Some(stdout) = list.stdout.take()
+            {
+                report.set_required_bumps(&stdout.0);
+            }
+        }
+
+        let mut response: Response = output.into();
+        report.attach_to(&mut response);
+        Ok(response)
+    }
+}
+
+#[derive(Debug, ::serde::Deserialize, schemars::JsonSchema)]
+pub struct CargoSemverChecksInstallRequest {}
+
+impl CargoSemverChecksInstallRequest {
+    pub fn build_cmd(&self) -> Result<Command, ErrorData> {
+        let mut cmd = Command::new("cargo");
+        cmd.arg("install")
+            .arg("cargo-semver-checks")
+            .arg("--locked");
+
+        Ok(cmd)
+    }
+}
+
+pub struct CargoSemverChecksInstallRmcpTool;
+
+impl Tool for CargoSemverChecksInstallRmcpTool {
+    const NAME: &'static str = "cargo-semver-checks-install";
+    const TITLE: &'static str = "Install cargo-semver-checks";
+    const DESCRIPTION: &'static str = "Installs cargo-semver-checks tool for finding breaking changes in the public API of crates";
+    type RequestArgs = CargoSemverChecksInstallRequest;
+    const HINTS: ToolHints = ToolHints::writes().idempotent().open_world();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::{CommandLine, ExitStatus, Stderr};
+
+    use super::*;
+
+    const STDOUT: &str = "
+--- failure enum_variant_added: enum variant added on exhaustive enum ---
+
+Description:
+A publicly-visible enum without #[non_exhaustive] has a new variant.
+        ref: https://doc.rust-lang.org/cargo/reference/semver.html#enum-variant-new
+       impl: https://github.com/obi1kenobi/cargo-semver-checks/tree/v0.51.0/src/lints/enum_variant_added.ron
+
+Failed in:
+  variant E:C in /ws/src/lib.rs:1
+
+--- failure function_missing: pub fn removed or renamed ---
+
+Description:
+A publicly-visible function cannot be imported by its prior path. A `pub use` may have been removed, or the function itself may have been renamed or removed entirely.
+        ref: https://doc.rust-lang.org/cargo/reference/semver.html#item-remove
+       impl: https://github.com/obi1kenobi/cargo-semver-checks/tree/v0.51.0/src/lints/function_missing.ron
+
+Failed in:
+  function semv::foo, previously in file /ws/target/semver-checks/git-HEAD/c5f37f48/src/lib.rs:1
+
+--- failure function_missing: pub fn removed or renamed ---
+
+Description:
+A publicly-visible function cannot be imported by its prior path. A `pub use` may have been removed, or the function itself may have been renamed or removed entirely.
+        ref: https://doc.rust-lang.org/cargo/reference/semver.html#item-remove
+       impl: https://github.com/obi1kenobi/cargo-semver-checks/tree/v0.51.0/src/lints/function_missing.ron
+
+Failed in:
+  function semvb::gone, previously in file /ws/target/semver-checks/git-HEAD/c5f37f48/b/src/lib.rs:1
+";
+
+    const STDERR: &str = "     Cloning HEAD
+    Building semv v0.1.0 (current)
+       Built [   0.751s] (current)
+    Checking semv v0.1.0 -> v0.1.0 (no change; assume minor)
+     Checked [   0.087s] 202 checks: 200 pass, 2 fail, 0 warn, 58 skip
+
+     Summary semver requires new major version: 2 major and 0 minor checks failed
+    Finished [   2.215s] semv
+    Checking semvb v0.2.0 -> v0.2.0 (no change; assume minor)
+     Checked [   0.074s] 202 checks: 201 pass, 1 fail, 0 warn, 58 skip
+
+     Summary semver requires new major version: 1 major and 0 minor checks failed
+    Finished [   2.193s] semvb
+    Checking semvc v0.3.0 -> v0.3.0 (no change; assume minor)
+     Checked [   0.076s] 202 checks: 202 pass, 58 skip
+     Summary no semver update required
+    Finished [   1.857s] semvc";
+
+    const LIST: &str = "id                 type  description
+==                 ====  ===========
+enum_variant_added major A public enum has a new variant.
+function_missing   major A function can no longer be imported by its prior path.
+enum_must_use_added minor An enum has been marked with #[must_use].";
+
+    #[test]
+    fn test_cmd() {
+        let request: CargoSemverChecksRequest = serde_json::from_value(serde_json::json!({
+            "package": ["my_crate", "other"],
+            "baseline_rev": "main",
+            "release_type": "minor",
+            "features": "serde",
+            "baseline_features": ["old"],
+        }))
+        .unwrap();
+        let cmd = request.build_cmd().unwrap();
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+
+        assert_eq!(
+            args,
+            [
+                "semver-checks",
+                "--package",
+                "my_crate",
+                "--package",
+                "other",
+                "--baseline-rev",
+                "main",
+                "--release-type",
+                "minor",
+                "--features",
+                "serde",
+                "--baseline-features",
+                "old",
+                "--color",
+                "never",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cmd_rejects_several_baselines() {
+        let request: CargoSemverChecksRequest = serde_json::from_value(serde_json::json!({
+            "baseline_rev": "main",
+            "baseline_version": "1.0.0",
+        }))
+        .unwrap();
+        assert!(request.build_cmd().is_err());
+    }
+
+    #[test]
+    fn test_parse_report() {
+        let mut output = Output {
+            tool_name: "cargo-semver-checks".into(),
+            cmd_line: CommandLine("cargo semver-checks --workspace".into()),
+            stdout: Some(Stdout(STDOUT.into())),
+            stderr: Some(Stderr(STDERR.into())),
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
+        };
+        let mut report = SemverReport::take_from(&mut output, Some(Path::new("/ws")));
+        report.set_required_bumps(LIST);
+
+        assert!(output.stdout.is_none());
+        assert_eq!(report.packages.len(), 3);
+        assert_eq!(report.packages[0].failed, 2);
+        assert_eq!(report.packages[0].required_bump, Some(SemverBump::Major));
+        assert_eq!(report.packages[2].required_bump, None);
+        assert_eq!(
+            report.changes[1],
+            SemverChange {
+                package: Some("semv".into()),
+                lint: "function_missing".into(),
+                level: LintLevel::Failure,
+                required_bump: Some(SemverBump::Major),
+                title: "pub fn removed or renamed".into(),
+                item: "function semv::foo".into(),
+                path: Some("semv::foo".into()),
+                location: Some("target/semver-checks/git-HEAD/c5f37f48/src/lib.rs:1".into()),
+            }
+        );
+        assert_eq!(report.changes[2].package.as_deref(), Some("semvb"));
+        assert_eq!(
+            report.render(),
+            "semv v0.1.0 -> v0.1.0 (no change; assume minor): semver requires new major version: 2 major and 0 minor checks failed
+- major enum_variant_added: variant E:C at src/lib.rs:1
+- major function_missing: function semv::foo at target/semver-checks/git-HEAD/c5f37f48/src/lib.rs:1
+semvb v0.2.0 -> v0.2.0 (no change; assume minor): semver requires new major version: 1 major and 0 minor checks failed
+- major function_missing: function semvb::gone at target/semver-checks/git-HEAD/c5f37f48/b/src/lib.rs:1
+semvc v0.3.0 -> v0.3.0 (no change; assume minor): no semver update required"
+        );
+    }
+
+    #[test]
+    fn test_attach_empty_report() {
+        let mut output = Output {
+            tool_name: "cargo-semver-checks".into(),
+            cmd_line: CommandLine("cargo semver-checks --workspace".into()),
+            stdout: Some(Stdout("no baseline".into())),
+            stderr: Some(Stderr("error: could not find a baseline".into())),
+            exit_status: ExitStatus::Exited(std::process::ExitStatus::default()),
+        };
+        let report = SemverReport::take_from(&mut output, None);
+        assert!(output.stdout.is_some());
+        assert!(report.packages.is_empty());
+
+        let mut response: Response = output.into();
+        report.attach_to(&mut response);
+        let result = response.into_rmcp_result(false);
+        assert_eq!(
+            result.structured_content,
+            Some(serde_json::json!({ "packages": [], "changes": [] }))
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt::Write as _;
+
+use regex::Regex;
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, RawContent},
+};
+
+use crate::{
+    Response, Tool, ToolContext, ToolHints,
+    output_store::{self, Stream},
+    serde_utils::deserialize_string,
+};
+
+/// Number of lines returned by a call that does not set `end_line`
+const PAGE_LINES: usize = 200;
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct GetCommandOutputRequest {
+    /// ID of the full output, given in place of the elided lines of a tool result (e.g. "output-5f3a9c21-3")
+    output_id: String,
+
+    /// Stream to read: "stdout" (default) or "stderr"
+    #[serde(default, deserialize_with = "deserialize_string")]
+    stream: Option<String>,
+
+    /// First line to return, starting at 1
+    #[serde(default)]
+    start_line: Option<usize>,
+
+    /// Last line to return, inclusive. Defaults to 200 lines after `start_line`, or to the last line with a `pattern`
+    #[serde(default)]
+    end_line: Option<usize>,
+
+    /// Regular expression, only the matching lines are returned (e.g. "^error|panicked")
+    #[serde(default, deserialize_with = "deserialize_string")]
+    pattern: Option<String>,
+}
+
+impl GetCommandOutputRequest {
+    fn stream(&self) -> Result<Stream, ErrorData> {
+        match self.stream.as_deref() {
+            None | Some("stdout") => Ok(Stream::Stdout),
+            Some("stderr") => Ok(Stream::Stderr),
+            Some(other) => Err(ErrorData::invalid_params(
+                format!("Unknown stream `{other}`, expected \"stdout\" or \"stderr\""),
+                None,
+            )),
+        }
+    }
+}
+
+/// Numbered lines of `text` in the requested range, stopping after [`PAGE_LINES`] lines or `max_bytes`
+fn render_lines(
+    text: &str,
+    start_line: usize,
+    end_line: Option<usize>,
+    pattern: Option<&Regex>,
+    max_bytes: usize,
+) -> String {
+    let total = text.lines().count();
+    let page_end = end_line.is_none() && pattern.is_none();
+    let end_line = end_line
+        .unwrap_or(if pattern.is_some() {
+            total
+        } else {
+            start_line.saturating_add(PAGE_LINES - 1)
+        })
+        .min(total);
+
+    let mut rendered = String::new();
+    let mut shown = 0;
+    let mut next_line = None;
+    for (number, line) in text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .take(end_line)
+        .skip(start_line - 1)
+    {
+        if pattern.is_some_and(|pattern| !pattern.is_match(line)) {
+            continue;
+        }
+        if shown == PAGE_LINES || (shown > 0 && rendered.len() + line.len() > max_bytes) {
+            next_line = Some(number);
+            break;
+        }
+        if line.len() > max_bytes {
+            let end = line.floor_char_boundary(max_bytes);
+            _ = writeln!(
+                rendered,
+                "{number}: {} [... {} bytes elided]",
+                &line[..end],
+                line.len() - end
+            );
+        } else {
+            _ = writeln!(rendered, "{number}: {line}");
+        }
+        shown += 1;
+    }
+    if next_line.is_none() && page_end && end_line < total {
+        next_line = Some(end_line + 1);
+    }
+
+    let mut summary = match pattern {
+        Some(pattern) => format!(
+            "{shown} line(s) matching `{pattern}` in lines {start_line}-{end_line} of {total}"
+        ),
+        None if shown == 0 => {
+            format!("No lines in {start_line}-{end_line}, the stream has {total}")
+        }
+        None => format!("Lines {start_line}-{} of {total}", start_line + shown - 1),
+    };
+    if let Some(next_line) = next_line {
+        _ = write!(
+            summary,
+            ", more lines follow: call again with start_line {next_line}"
+        );
+    }
+    format!("{summary}\n{rendered}")
+}
+
+pub struct GetCommandOutputRmcpTool;
+
+impl Tool for GetCommandOutputRmcpTool {
+    const NAME: &'static str = "get-command-output";
+    const TITLE: &'static str = "Read full command output";
+    const DESCRIPTION: &'static str = "Reads the full output of a recent command, e.g. one whose tool result was truncated to `max_output_bytes`. Pages through a stream by line range or returns the lines matching a regular expression. The output ID is given in place of the elided lines and listed by list-recent-invocations, only the most recent outputs are kept.";
+    type RequestArgs = GetCommandOutputRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let stream = request.stream()?;
+        let output = output_store::get(&request.output_id).ok_or_else(|| {
+            ErrorData::invalid_params(
+                format!(
+                    "Unknown output_id `{}`, only the most recent outputs are kept",
+                    request.output_id
+                ),
+                None,
+            )
+        })?;
+        let pattern = request
+            .pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| ErrorData::invalid_params(format!("Invalid pattern: {e}"), None))?;
+
+        let lines = render_lines(
+            output.stream(stream),
+            request.start_line.unwrap_or(1).max(1),
+            request.end_line,
+            pattern.as_ref(),
+            ctx.max_output_bytes().unwrap_or(usize::MAX),
+        );
+        let mut response = Response::without_output();
+        response.add_content(
+            RawContent::text(format!(
+                "{} of `{}`: {lines}",
+                stream.name(),
+                output.cmd_line
+            ))
+            .no_annotation(),
+        );
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_lines() {
+        let text: String = (1..=300).map(|i| format!("line {i}\n")).collect();
+
+        let page = render_lines(&text, 1, None, None, usize::MAX);
+        assert!(page.starts_with(
+            "Lines 1-200 of 300, more lines follow: call again with start_line 201\n1: line 1\n"
+        ));
+        assert!(page.ends_with("200: line 200\n"));
+
+        assert_eq!(
+            render_lines(&text, 299, Some(400), None, usize::MAX),
+            "Lines 299-300 of 300\n299: line 299\n300: line 300\n"
+        );
+
+        let pattern = Regex::new("^line 2.5$").unwrap();
+        assert_eq!(
+            render_lines(&text, 1, None, Some(&pattern), usize::MAX),
+            "10 line(s) matching `^line 2.5$` in lines 1-300 of 300\n205: line 205\n215: line 215\n\
+            225: line 225\n235: line 235\n245: line 245\n255: line 255\n265: line 265\n275: line 275\n\
+            285: line 285\n295: line 295\n"
+        );
+
+        assert_eq!(
+            render_lines(&text, 10, Some(20), None, 25),
+            "Lines 10-11 of 300, more lines follow: call again with start_line 12\n10: line 10\n11: line 11\n"
+        );
+    }
+}
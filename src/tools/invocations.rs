@@ -0,0 +1,116 @@
+use std::fmt::Write as _;
+
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, RawContent},
+};
+
+use crate::{
+    Response, Tool, ToolContext, ToolHints,
+    audit::{self, Invocation},
+    serde_utils::deserialize_string,
+    tool::json_schema_impl,
+};
+
+/// Number of invocations listed when the request does not set `limit`
+pub(crate) const DEFAULT_LIMIT: usize = 20;
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct ListRecentInvocationsRequest {
+    /// Maximum number of invocations to list, the most recent first. Defaults to 20.
+    #[serde(default)]
+    limit: Option<usize>,
+
+    /// Only list the calls of this tool (e.g. "cargo-test")
+    #[serde(default, deserialize_with = "deserialize_string")]
+    tool: Option<String>,
+
+    /// Only list the calls that failed
+    #[serde(default)]
+    failed_only: Option<bool>,
+}
+
+/// Tool calls recorded in the audit log
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct InvocationList {
+    /// Most recent first
+    pub(crate) invocations: Vec<Invocation>,
+}
+
+impl InvocationList {
+    pub(crate) fn render(&self) -> String {
+        if self.invocations.is_empty() {
+            return "No matching invocation recorded".to_owned();
+        }
+
+        let mut text = String::new();
+        for invocation in &self.invocations {
+            let status = match (invocation.is_error, invocation.exit_code) {
+                (false, _) => "✅".to_owned(),
+                (true, Some(code)) => format!("❌ exit code {code}"),
+                (true, None) => "❌".to_owned(),
+            };
+            _ = write!(
+                text,
+                "{} {} {status} ({:.1}s)",
+                invocation.timestamp,
+                invocation.tool,
+                invocation.duration_ms as f64 / 1000.0
+            );
+            if let Some(command_line) = &invocation.command_line {
+                _ = write!(text, ": `{command_line}`");
+            }
+            if let Some(dir) = &invocation.working_directory {
+                _ = write!(text, " in {}", dir.display());
+            }
+            if let Some(output_id) = &invocation.output_id {
+                _ = write!(
+                    text,
+                    " (stdout {} bytes, stderr {} bytes, output_id \"{output_id}\")",
+                    invocation.stdout_bytes, invocation.stderr_bytes
+                );
+            }
+            if let Some(error) = &invocation.error {
+                _ = write!(text, ": {error}");
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+pub struct ListRecentInvocationsRmcpTool;
+
+impl Tool for ListRecentInvocationsRmcpTool {
+    const NAME: &'static str = "list-recent-invocations";
+    const TITLE: &'static str = "List recent tool calls";
+    const DESCRIPTION: &'static str = "Lists the recent tool calls recorded in the audit log: time, tool, arguments, command line, working directory, duration, exit code and output sizes. Use it to recall what was already run, the full output of the most recent calls can be read with get-command-output and their output_id instead of running the command again.";
+    type RequestArgs = ListRecentInvocationsRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<InvocationList>())
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        _ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let failed_only = request.failed_only.unwrap_or(false);
+        let list = InvocationList {
+            invocations: audit::recent(request.limit.unwrap_or(DEFAULT_LIMIT), |invocation| {
+                request
+                    .tool
+                    .as_ref()
+                    .is_none_or(|tool| invocation.tool == *tool)
+                    && (!failed_only || invocation.is_error)
+            }),
+        };
+
+        let mut response = Response::without_output();
+        response.add_content(RawContent::text(list.render()).no_annotation());
+        response.set_structured_content(&list);
+        Ok(response)
+    }
+}
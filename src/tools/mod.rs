@@ -3,9 +3,16 @@ pub mod cargo_deny;
 pub mod cargo_expand;
 pub mod cargo_hack;
 pub mod cargo_insta;
+pub mod cargo_llvm_cov;
 pub mod cargo_machete;
+pub mod cargo_nextest;
+pub mod cargo_semver_checks;
+pub mod command_output;
+pub mod invocations;
+pub mod rust_analyzer;
 pub mod rustc;
 pub mod rustup;
+pub mod std_doc;
+pub mod workspaces;
 
-pub use crate::globals::get_workspace_root;
 pub use crate::serde_utils::Registry;
@@ -0,0 +1,585 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, RawContent},
+};
+use serde_json::{Value, json};
+
+use crate::{
+    Response, Tool, ToolContext, ToolHints,
+    lsp::{self, LspClient},
+    serde_utils::deserialize_string,
+    workspace::{file_uri_to_path, path_to_file_uri},
+};
+
+/// LSP `SymbolKind` names, the kind `n` is at index `n - 1`
+const SYMBOL_KINDS: [&str; 26] = [
+    "file",
+    "module",
+    "namespace",
+    "package",
+    "class",
+    "method",
+    "property",
+    "field",
+    "constructor",
+    "enum",
+    "interface",
+    "function",
+    "variable",
+    "constant",
+    "string",
+    "number",
+    "boolean",
+    "array",
+    "object",
+    "key",
+    "null",
+    "enum member",
+    "struct",
+    "event",
+    "operator",
+    "type parameter",
+];
+
+const STILL_INDEXING: &str =
+    "Note: rust-analyzer is still indexing the workspace, the results may be incomplete.";
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct SymbolPositionRequest {
+    /// Source file, relative to the workspace root or absolute
+    file: String,
+
+    /// Line of the symbol, starting at 1
+    line: usize,
+
+    /// Column of the symbol in characters, starting at 1. Defaults to the first occurrence of `symbol` on the line
+    #[serde(default)]
+    column: Option<usize>,
+
+    /// Name of the symbol, used to find its column on the line when `column` is not set (e.g. "parse_args")
+    #[serde(default, deserialize_with = "deserialize_string")]
+    symbol: Option<String>,
+}
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct FindReferencesRequest {
+    #[serde(flatten)]
+    position: SymbolPositionRequest,
+
+    /// Also list the declaration of the symbol. Defaults to false.
+    #[serde(default)]
+    include_declaration: Option<bool>,
+}
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct WorkspaceSymbolRequest {
+    /// Name or fuzzy pattern of the symbols to search for (e.g. "ToolContext", "parse_args")
+    query: String,
+
+    /// Also search the dependencies of the workspace. Defaults to false.
+    #[serde(default)]
+    include_dependencies: Option<bool>,
+}
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct DocumentSymbolsRequest {
+    /// Source file, relative to the workspace root or absolute
+    file: String,
+}
+
+/// Absolute path of a file argument, relative paths are resolved against the workspace root
+fn resolve_file(file: &str, root: &Path) -> Result<PathBuf, ErrorData> {
+    let path = root.join(file);
+    if !path.is_file() {
+        return Err(ErrorData::invalid_params(
+            format!("File not found: {}", path.display()),
+            None,
+        ));
+    }
+    Ok(path)
+}
+
+fn workspace_root(ctx: &ToolContext) -> PathBuf {
+    ctx.workspace_root()
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default()
+}
+
+/// LSP position (0-based line and UTF-16 column) of the symbol the request points at
+fn lsp_position(text: &str, request: &SymbolPositionRequest) -> Result<Value, ErrorData> {
+    let line_count = text.lines().count();
+    let Some(line) = request
+        .line
+        .checked_sub(1)
+        .and_then(|index| text.lines().nth(index))
+    else {
+        return Err(ErrorData::invalid_params(
+            format!(
+                "Line {} is out of range, {} has {line_count} lines",
+                request.line, request.file
+            ),
+            None,
+        ));
+    };
+
+    let byte = match (request.column, &request.symbol) {
+        (Some(column), _) => line
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(line.len(), |(byte, _)| byte),
+        (None, Some(symbol)) => find_symbol(line, symbol).ok_or_else(|| {
+            ErrorData::invalid_params(
+                format!("`{symbol}` not found on line {}: {line}", request.line),
+                None,
+            )
+        })?,
+        (None, None) => {
+            return Err(ErrorData::invalid_params(
+                "Either `column` or `symbol` must be set",
+                None,
+            ));
+        }
+    };
+    Ok(json!({
+        "line": request.line - 1,
+        "character": line[..byte].encode_utf16().count(),
+    }))
+}
+
+/// Byte offset of the first occurrence of `symbol` on the line that is a whole identifier,
+/// or of its first occurrence if none is
+fn find_symbol(line: &str, symbol: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut matches = line.match_indices(symbol).map(|(byte, _)| byte);
+    let first = matches.clone().next()?;
+    Some(
+        matches
+            .find(|byte| {
+                !line[..*byte].ends_with(is_ident)
+                    && !line[byte + symbol.len()..].starts_with(is_ident)
+            })
+            .unwrap_or(first),
+    )
+}
+
+/// A position returned by rust-analyzer, 0-based with a UTF-16 column
+struct Location {
+    path: PathBuf,
+    line: usize,
+    character: usize,
+}
+
+impl Location {
+    /// Parses a `Location`, a `LocationLink` or the location of a symbol
+    fn from_lsp(value: &Value) -> Option<Self> {
+        let (uri, range) = match value.get("targetUri") {
+            Some(uri) => (uri, value.get("targetSelectionRange")?),
+            None => (
+                value.get("uri")?,
+                value.get("range").unwrap_or(&Value::Null),
+            ),
+        };
+        Some(Self {
+            path: file_uri_to_path(uri.as_str()?)?,
+            line: range["start"]["line"].as_u64().unwrap_or(0) as usize,
+            character: range["start"]["character"].as_u64().unwrap_or(0) as usize,
+        })
+    }
+
+    fn from_lsp_list(value: &Value) -> Vec<Self> {
+        match value {
+            Value::Array(items) => items.iter().filter_map(Self::from_lsp).collect(),
+            Value::Null => Vec::new(),
+            value => Self::from_lsp(value).into_iter().collect(),
+        }
+    }
+}
+
+/// Renders locations as `path:line:column: source line`, with 1-based lines and character columns
+#[derive(Default)]
+struct LocationRenderer {
+    files: HashMap<PathBuf, Option<String>>,
+}
+
+impl LocationRenderer {
+    fn render(&mut self, location: &Location, root: &Path) -> String {
+        let text = self
+            .files
+            .entry(location.path.clone())
+            .or_insert_with(|| std::fs::read_to_string(&location.path).ok());
+        let line = text
+            .as_deref()
+            .and_then(|text| text.lines().nth(location.line));
+        let column = line.map_or(location.character, |line| {
+            let mut utf16 = 0;
+            line.chars()
+                .take_while(|c| {
+                    utf16 += c.len_utf16();
+                    utf16 <= location.character
+                })
+                .count()
+        });
+        let path = location
+            .path
+            .strip_prefix(root)
+            .unwrap_or(&location.path)
+            .display();
+        match line {
+            Some(line) => format!(
+                "{path}:{}:{}: {}",
+                location.line + 1,
+                column + 1,
+                line.trim()
+            ),
+            None => format!("{path}:{}:{}", location.line + 1, column + 1),
+        }
+    }
+}
+
+fn symbol_kind(value: &Value) -> &'static str {
+    value["kind"]
+        .as_u64()
+        .and_then(|kind| SYMBOL_KINDS.get((kind as usize).checked_sub(1)?))
+        .copied()
+        .unwrap_or("symbol")
+}
+
+/// Text of a hover result, whichever of the LSP `MarkedString` or `MarkupContent` forms it has
+fn hover_text(contents: &Value) -> String {
+    match contents {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(hover_text)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        Value::Object(object) => match (object.get("language"), object.get("value")) {
+            (Some(language), Some(value)) => format!(
+                "```{}\n{}\n```",
+                language.as_str().unwrap_or_default(),
+                value.as_str().unwrap_or_default()
+            ),
+            (None, Some(value)) => value.as_str().unwrap_or_default().to_owned(),
+            _ => String::new(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Renders a `DocumentSymbol` tree, or a flat list of `SymbolInformation`, one symbol per line
+fn render_document_symbols(symbols: &[Value], depth: usize, text: &mut String) {
+    for symbol in symbols {
+        let range = symbol
+            .get("range")
+            .or_else(|| symbol["location"].get("range"));
+        let line = |position: &str| {
+            range
+                .and_then(|range| range[position]["line"].as_u64())
+                .unwrap_or(0)
+                + 1
+        };
+        _ = write!(
+            text,
+            "{:indent$}{} {} (lines {}-{})",
+            "",
+            symbol_kind(symbol),
+            symbol["name"].as_str().unwrap_or_default(),
+            line("start"),
+            line("end"),
+            indent = depth * 2
+        );
+        if let Some(detail) = symbol["detail"]
+            .as_str()
+            .filter(|detail| !detail.is_empty())
+        {
+            _ = write!(text, ": {detail}");
+        }
+        text.push('\n');
+        if let Some(children) = symbol["children"].as_array() {
+            render_document_symbols(children, depth + 1, text);
+        }
+    }
+}
+
+/// Starts rust-analyzer if needed and waits until it has loaded the workspace
+async fn ready_analyzer(ctx: &ToolContext) -> Result<(std::sync::Arc<LspClient>, bool), ErrorData> {
+    let analyzer = lsp::rust_analyzer(ctx).await?;
+    let ready = analyzer.wait_until_ready(ctx).await?;
+    Ok((analyzer, ready))
+}
+
+/// Sends a request about the symbol at `position`, with the file synced first
+async fn position_request(
+    method: &str,
+    position: &SymbolPositionRequest,
+    extra_params: Value,
+    ctx: &ToolContext,
+) -> Result<(Value, bool), ErrorData> {
+    let root = workspace_root(ctx);
+    let path = resolve_file(&position.file, &root)?;
+    let (analyzer, ready) = ready_analyzer(ctx).await?;
+    let text = analyzer.sync_document(&path).await?;
+    let mut params = json!({
+        "textDocument": { "uri": path_to_file_uri(&path) },
+        "position": lsp_position(&text, position)?,
+    });
+    if let (Value::Object(params), Value::Object(extra)) = (&mut params, extra_params) {
+        params.extend(extra);
+    }
+    Ok((analyzer.request(method, params, ctx).await?, ready))
+}
+
+fn text_response(mut text: String, ready: bool) -> Response {
+    if !ready {
+        _ = write!(text, "\n{STILL_INDEXING}");
+    }
+    let mut response = Response::without_output();
+    response.add_content(RawContent::text(text).no_annotation());
+    response
+}
+
+fn render_locations(locations: &[Location], root: &Path, empty: &str) -> String {
+    if locations.is_empty() {
+        return empty.to_owned();
+    }
+    let mut renderer = LocationRenderer::default();
+    let mut text = String::new();
+    for location in locations {
+        _ = writeln!(text, "{}", renderer.render(location, root));
+    }
+    text
+}
+
+pub struct FindDefinitionRmcpTool;
+
+impl Tool for FindDefinitionRmcpTool {
+    const NAME: &'static str = "find-definition";
+    const TITLE: &'static str = "Find definition";
+    const DESCRIPTION: &'static str = "Finds where the symbol at a position is defined, using rust-analyzer. Point at the symbol with `file`, `line` and either `column` or the `symbol` name. Works across the workspace, its dependencies and the standard library.";
+    type RequestArgs = SymbolPositionRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let (result, ready) =
+            position_request("textDocument/definition", &request, Value::Null, &ctx).await?;
+        let text = render_locations(
+            &Location::from_lsp_list(&result),
+            &workspace_root(&ctx),
+            "No definition found",
+        );
+        Ok(text_response(text, ready))
+    }
+}
+
+pub struct FindReferencesRmcpTool;
+
+impl Tool for FindReferencesRmcpTool {
+    const NAME: &'static str = "find-references";
+    const TITLE: &'static str = "Find references";
+    const DESCRIPTION: &'static str = "Lists every place in the workspace that refers to the symbol at a position, using rust-analyzer. Point at the symbol with `file`, `line` and either `column` or the `symbol` name.";
+    type RequestArgs = FindReferencesRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let include_declaration = request.include_declaration.unwrap_or(false);
+        let (result, ready) = position_request(
+            "textDocument/references",
+            &request.position,
+            json!({ "context": { "includeDeclaration": include_declaration } }),
+            &ctx,
+        )
+        .await?;
+        let locations = Location::from_lsp_list(&result);
+        let mut text = format!("{} reference(s)\n", locations.len());
+        text.push_str(&render_locations(&locations, &workspace_root(&ctx), ""));
+        Ok(text_response(text, ready))
+    }
+}
+
+pub struct HoverRmcpTool;
+
+impl Tool for HoverRmcpTool {
+    const NAME: &'static str = "hover";
+    const TITLE: &'static str = "Show type and docs";
+    const DESCRIPTION: &'static str = "Shows the type, signature and documentation of the symbol at a position, using rust-analyzer. Point at the symbol with `file`, `line` and either `column` or the `symbol` name.";
+    type RequestArgs = SymbolPositionRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let (result, ready) =
+            position_request("textDocument/hover", &request, Value::Null, &ctx).await?;
+        let text = hover_text(&result["contents"]).trim().to_owned();
+        let text = if text.is_empty() {
+            format!("No hover information at {}:{}", request.file, request.line)
+        } else {
+            text
+        };
+        Ok(text_response(text, ready))
+    }
+}
+
+pub struct WorkspaceSymbolRmcpTool;
+
+impl Tool for WorkspaceSymbolRmcpTool {
+    const NAME: &'static str = "workspace-symbol";
+    const TITLE: &'static str = "Search symbols";
+    const DESCRIPTION: &'static str = "Searches the functions, types, traits, modules, constants and other items of the workspace by name, using rust-analyzer. The query is matched fuzzily. Returns the kind and location of every match.";
+    type RequestArgs = WorkspaceSymbolRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let (analyzer, ready) = ready_analyzer(&ctx).await?;
+        let scope = if request.include_dependencies.unwrap_or(false) {
+            "workspaceAndDependencies"
+        } else {
+            "workspace"
+        };
+        // `searchScope` and `searchKind` are rust-analyzer extensions, it only searches types by default
+        let result = analyzer
+            .request(
+                "workspace/symbol",
+                json!({ "query": request.query, "searchScope": scope, "searchKind": "allSymbols" }),
+                &ctx,
+            )
+            .await?;
+
+        let root = workspace_root(&ctx);
+        let mut renderer = LocationRenderer::default();
+        let symbols = result.as_array().map(Vec::as_slice).unwrap_or_default();
+        let mut text = String::new();
+        for symbol in symbols {
+            _ = write!(
+                text,
+                "{} {}",
+                symbol_kind(symbol),
+                symbol["name"].as_str().unwrap_or_default()
+            );
+            if let Some(container) = symbol["containerName"].as_str() {
+                _ = write!(text, " in {container}");
+            }
+            if let Some(location) = Location::from_lsp(&symbol["location"]) {
+                _ = write!(text, " at {}", renderer.render(&location, &root));
+            }
+            text.push('\n');
+        }
+        if symbols.is_empty() {
+            text = format!("No symbol matching `{}`", request.query);
+        }
+        Ok(text_response(text, ready))
+    }
+}
+
+pub struct DocumentSymbolsRmcpTool;
+
+impl Tool for DocumentSymbolsRmcpTool {
+    const NAME: &'static str = "document-symbols";
+    const TITLE: &'static str = "List file symbols";
+    const DESCRIPTION: &'static str = "Outlines a source file with rust-analyzer: its modules, types, functions, fields, impls and their members, nested as in the file, with their line ranges.";
+    type RequestArgs = DocumentSymbolsRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let path = resolve_file(&request.file, &workspace_root(&ctx))?;
+        let (analyzer, ready) = ready_analyzer(&ctx).await?;
+        analyzer.sync_document(&path).await?;
+        let result = analyzer
+            .request(
+                "textDocument/documentSymbol",
+                json!({ "textDocument": { "uri": path_to_file_uri(&path) } }),
+                &ctx,
+            )
+            .await?;
+
+        let mut text = String::new();
+        render_document_symbols(
+            result.as_array().map(Vec::as_slice).unwrap_or_default(),
+            0,
+            &mut text,
+        );
+        if text.is_empty() {
+            text = format!("No symbols in {}", request.file);
+        }
+        Ok(text_response(text, ready))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lsp_position() {
+        let text = "fn main() {\n    let parsed = parse(\"é\", parse_args());\n}\n";
+        let request = |column, symbol: Option<&str>| SymbolPositionRequest {
+            file: "src/main.rs".to_owned(),
+            line: 2,
+            column,
+            symbol: symbol.map(str::to_owned),
+        };
+
+        assert_eq!(
+            lsp_position(text, &request(Some(9), None)).unwrap(),
+            json!({ "line": 1, "character": 8 })
+        );
+        // The columns rust-analyzer expects are in UTF-16 code units, `é` is one of them
+        assert_eq!(
+            lsp_position(text, &request(None, Some("parse_args"))).unwrap(),
+            json!({ "line": 1, "character": 28 })
+        );
+        // Whole identifiers are preferred over the first occurrence
+        assert_eq!(
+            lsp_position(text, &request(None, Some("parse"))).unwrap(),
+            json!({ "line": 1, "character": 17 })
+        );
+        assert!(lsp_position(text, &request(None, Some("missing"))).is_err());
+        assert!(lsp_position(text, &request(None, None)).is_err());
+    }
+
+    #[test]
+    fn test_render_document_symbols() {
+        let symbols = json!([{
+            "name": "Server",
+            "kind": 23,
+            "range": { "start": { "line": 9, "character": 0 }, "end": { "line": 14, "character": 1 } },
+            "children": [{
+                "name": "tools",
+                "kind": 8,
+                "detail": "Vec<Tool>",
+                "range": { "start": { "line": 10, "character": 4 }, "end": { "line": 10, "character": 20 } },
+            }],
+        }]);
+        let mut text = String::new();
+        render_document_symbols(symbols.as_array().unwrap(), 0, &mut text);
+        assert_eq!(
+            text,
+            "struct Server (lines 10-15)\n  field tools (lines 11-11): Vec<Tool>\n"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::process::Command;
 
-use crate::{Tool, execute_command, serde_utils::deserialize_string};
+use crate::{Tool, ToolContext, ToolHints, execute_command, serde_utils::deserialize_string};
 use rmcp::ErrorData;
 
 #[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
@@ -35,8 +35,15 @@ impl Tool for RustcExplainRmcpTool {
     const TITLE: &'static str = "Explain Rust error";
     const DESCRIPTION: &'static str = "Provide a detailed explanation of a Rust compiler error code. This tool allows AI agents to request more information about compilation errors by providing the error code (e.g., E0001, E0308, etc.). Very useful for understanding and resolving Rust compilation errors.";
     type RequestArgs = RustcExplainRequest;
-
-    fn call_rmcp_tool(&self, request: Self::RequestArgs) -> Result<crate::Response, ErrorData> {
-        execute_command(request.build_cmd()?, Self::NAME).map(Into::into)
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<crate::Response, ErrorData> {
+        execute_command(request.build_cmd()?, Self::NAME, &ctx)
+            .await
+            .map(Into::into)
     }
 }
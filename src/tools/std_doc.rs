@@ -0,0 +1,480 @@
+use std::{
+    collections::{BTreeMap, HashMap, hash_map::Entry},
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, RawContent},
+};
+
+use crate::{
+    Response, Tool, ToolContext, ToolHints, execute_command,
+    rustdoc::{Query, RustdocIndex, match_score},
+    serde_utils::deserialize_string,
+};
+
+/// Crates of the standard library that can be queried, paths outside of them are relative to `std`
+const STD_CRATES: [&str; 3] = ["std", "core", "alloc"];
+
+/// Number of items listed by a search
+const MAX_SEARCH_RESULTS: usize = 20;
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct StdDocRequest {
+    /// Path of an item of std, core or alloc (e.g. "std::sync::Mutex::lock" or "core::mem::swap"). Paths that don't start with one of these crates are relative to std, and a path that doesn't resolve (e.g. "Vec::push") is looked up by name.
+    #[serde(default, deserialize_with = "deserialize_string")]
+    path: Option<String>,
+
+    /// Name to search for among the items and methods of std, core and alloc instead of a `path`, matched by prefix, substring or as a subsequence (e.g. "retain", "Vec::ret" or "btmap").
+    #[serde(default, deserialize_with = "deserialize_string")]
+    search: Option<String>,
+
+    /// What to return for `path`.
+    ///
+    /// Valid options:
+    /// - "item": declaration, stability and documentation of the item (default, except for modules)
+    /// - "items": public items of a module (default for modules), or the members of a type or a trait
+    /// - "impls": trait implementations of a type, or the implementations of a trait
+    #[serde(default, deserialize_with = "deserialize_string")]
+    query: Option<String>,
+
+    /// Toolchain whose documentation is read (e.g. "nightly"). Defaults to the toolchain the workspace
+    /// uses, selected by its `rust-toolchain.toml` or a rustup override.
+    #[serde(default, deserialize_with = "deserialize_string")]
+    toolchain: Option<String>,
+}
+
+/// Directory of the rustdoc JSON of the standard library in the sysroot of a toolchain, installed by
+/// the `rust-docs-json` component
+async fn json_dir(toolchain: Option<&str>, ctx: &ToolContext) -> Result<PathBuf, ErrorData> {
+    let mut cmd = Command::new("rustc");
+    if let Some(toolchain) = toolchain {
+        cmd.arg(format!("+{toolchain}"));
+    }
+    cmd.arg("--print").arg("sysroot");
+    let mut output = execute_command(cmd, StdDocRmcpTool::NAME, ctx).await?;
+    if !output.success() {
+        let stderr = output.stderr.map(|s| s.0).unwrap_or_default();
+        return Err(ErrorData::internal_error(
+            format!("rustc --print sysroot failed: {}", stderr.trim()),
+            None,
+        ));
+    }
+    let sysroot = PathBuf::from(output.stdout.take().map(|s| s.0).unwrap_or_default().trim());
+
+    let dir = sysroot.join("share").join("doc").join("rust").join("json");
+    if !dir.join("std.json").is_file() {
+        let toolchain = sysroot
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| sysroot.display().to_string());
+        return Err(ErrorData::invalid_params(
+            format!(
+                "The JSON documentation of the standard library is not installed for toolchain `{toolchain}`, \
+                 install it with `rustup component add rust-docs-json --toolchain {toolchain}`. \
+                 The component is only published for nightly toolchains, pass `toolchain` to read the documentation of an installed nightly instead."
+            ),
+            None,
+        ));
+    }
+    Ok(dir)
+}
+
+async fn load(dir: &Path, krate: &str) -> Result<Arc<RustdocIndex>, ErrorData> {
+    RustdocIndex::load(dir.join(format!("{krate}.json"))).await
+}
+
+/// The index documenting `path` and the path of the item in it, following the re-exports of
+/// `core` and `alloc` items by `std`. `None` if the path doesn't resolve.
+async fn resolve(dir: &Path, path: &str) -> Result<Option<(Arc<RustdocIndex>, String)>, ErrorData> {
+    let path = path.trim().trim_start_matches("::");
+    let mut krate = path
+        .split("::")
+        .next()
+        .filter(|krate| STD_CRATES.contains(krate))
+        .unwrap_or("std")
+        .to_owned();
+    let mut path = path.to_owned();
+    let mut index = load(dir, &krate).await?;
+    // Paths lead to another crate at most once per crate
+    for _ in STD_CRATES {
+        if index.resolves(&path) {
+            break;
+        }
+        let Some(target) = index.reexport_target(&path) else {
+            break;
+        };
+        let target_crate = target.split("::").next().unwrap_or_default();
+        if target_crate == krate || !STD_CRATES.contains(&target_crate) {
+            break;
+        }
+        krate = target_crate.to_owned();
+        index = load(dir, &krate).await?;
+        path = target;
+    }
+    Ok(index.resolves(&path).then_some((index, path)))
+}
+
+/// Items of std, core and alloc matching a name optionally qualified by its parent, e.g.
+/// `Vec::push`, with their path in std when it re-exports them, best matches first
+async fn search(dir: &Path, query: &str) -> Result<Vec<(String, String)>, ErrorData> {
+    let query = query.trim();
+    let (qualifier, name) = query.rsplit_once("::").unwrap_or(("", query));
+    let qualifier = qualifier.to_lowercase();
+
+    let std = load(dir, "std").await?;
+    // Shortest path in std of the items it re-exports, by their path in core or alloc
+    let mut reexports: HashMap<String, &str> = HashMap::new();
+    for (path, target) in std.reexports() {
+        match reexports.entry(target) {
+            Entry::Occupied(mut entry) if entry.get().len() > path.len() => _ = entry.insert(path),
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => _ = entry.insert(path),
+        }
+    }
+    let in_std = |path: &str| -> Option<String> {
+        let segments: Vec<&str> = path.split("::").collect();
+        (1..=segments.len())
+            .filter_map(|len| {
+                let reexport = reexports.get(&segments[..len].join("::"))?;
+                Some(
+                    std::iter::once(*reexport)
+                        .chain(segments[len..].iter().copied())
+                        .collect::<Vec<_>>()
+                        .join("::"),
+                )
+            })
+            .min_by_key(String::len)
+    };
+
+    let mut hits: BTreeMap<String, String> = BTreeMap::new();
+    for krate in STD_CRATES {
+        for hit in load(dir, krate).await?.search(name) {
+            let path = [hit.definition.as_deref(), Some(&hit.path)]
+                .into_iter()
+                .flatten()
+                .filter(|_| krate != "std")
+                .filter_map(in_std)
+                .min_by_key(String::len)
+                .unwrap_or(hit.path);
+            hits.entry(path).or_insert(hit.description);
+        }
+    }
+
+    let mut hits: Vec<((u8, usize), String, String)> = hits
+        .into_iter()
+        .filter(|(path, _)| {
+            let parent = path
+                .rsplit_once("::")
+                .map_or("", |(parent, _)| parent)
+                .to_lowercase();
+            qualifier.is_empty()
+                || parent == qualifier
+                || parent.ends_with(&format!("::{qualifier}"))
+        })
+        .filter_map(|(path, description)| Some((match_score(name, &path)?, path, description)))
+        .collect();
+    hits.sort();
+    Ok(hits
+        .into_iter()
+        .map(|(_, path, description)| (path, description))
+        .collect())
+}
+
+/// Answers a query about the item at `path`, looking it up by name when the path doesn't resolve
+async fn lookup(dir: &Path, path: &str, query: Option<Query>) -> Result<String, ErrorData> {
+    if let Some((index, path)) = resolve(dir, path).await? {
+        return index.query(&path, query);
+    }
+
+    let hits = search(dir, path).await?;
+    let name = path.rsplit("::").next().unwrap_or(path);
+    let exact: Vec<&String> = hits
+        .iter()
+        .map(|(hit, _)| hit)
+        .filter(|hit| hit.rsplit("::").next() == Some(name))
+        .collect();
+    let tail = |hit: &String| {
+        hit.rsplitn(3, "::")
+            .take(2)
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+    let resolved = match exact.first() {
+        // A single item, possibly documented both in std and in core or alloc
+        Some(first) if exact.iter().all(|hit| tail(hit) == tail(first)) => {
+            let hit = exact
+                .iter()
+                .find(|hit| hit.starts_with("std::"))
+                .unwrap_or(first);
+            resolve(dir, hit).await?
+        }
+        _ => None,
+    };
+    let Some((index, resolved)) = resolved else {
+        let mut message = format!("`{path}` not found in std, core or alloc");
+        if !hits.is_empty() {
+            let similar: Vec<&str> = hits
+                .iter()
+                .take(MAX_SEARCH_RESULTS / 2)
+                .map(|(path, _)| path.as_str())
+                .collect();
+            _ = write!(message, ", similar items: {}", similar.join(", "));
+        }
+        return Err(ErrorData::invalid_params(message, None));
+    };
+    index.query(&resolved, query)
+}
+
+pub struct StdDocRmcpTool;
+
+impl Tool for StdDocRmcpTool {
+    const NAME: &'static str = "std-doc";
+    const TITLE: &'static str = "Look up standard library documentation";
+    const DESCRIPTION: &'static str = "Returns the signature, stability and documentation of an item of std, core or alloc such as `std::sync::Mutex::lock`, or searches their items and methods by name. Reads the rustdoc JSON installed with the toolchain the workspace uses by the `rust-docs-json` rustup component, so it works offline and matches the compiler. Use it to check a standard library API before using it.";
+    type RequestArgs = StdDocRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    async fn call_rmcp_tool(
+        &self,
+        request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let query = request.query.as_deref().map(Query::parse).transpose()?;
+        let dir = json_dir(request.toolchain.as_deref(), &ctx).await?;
+
+        let text = match (&request.path, &request.search) {
+            (Some(path), _) => lookup(&dir, path, query).await?,
+            (None, Some(name)) => {
+                let hits = search(&dir, name).await?;
+                // e.g. "1.90.0-nightly\t(4b4e9c4d9\t2025-07-11)"
+                let version = load(&dir, "std")
+                    .await?
+                    .crate_version()
+                    .map(|version| version.split_whitespace().collect::<Vec<_>>().join(" "));
+                let mut text = format!(
+                    "{} item(s) matching `{name}` in the standard library{}",
+                    hits.len(),
+                    version
+                        .map(|version| format!(" of Rust {version}"))
+                        .unwrap_or_default()
+                );
+                if hits.len() > MAX_SEARCH_RESULTS {
+                    _ = write!(text, ", showing the first {MAX_SEARCH_RESULTS}");
+                }
+                text.push_str(":\n");
+                for (path, description) in hits.iter().take(MAX_SEARCH_RESULTS) {
+                    _ = writeln!(text, "- {path} {description}");
+                }
+                text
+            }
+            (None, None) => {
+                return Err(ErrorData::invalid_params(
+                    "Either `path` or `search` is required".to_owned(),
+                    None,
+                ));
+            }
+        };
+
+        let mut response = Response::without_output();
+        response.add_content(RawContent::text(text).no_annotation());
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn function(name: &str, docs: &str) -> Value {
+        json!({
+            "name": name, "visibility": "public", "docs": docs,
+            "inner": { "function": {
+                "sig": { "inputs": [], "output": null, "is_c_variadic": false },
+                "generics": { "params": [], "where_predicates": [] },
+                "header": { "is_const": false, "is_unsafe": false, "is_async": false, "abi": "Rust" },
+                "has_body": true,
+            } },
+        })
+    }
+
+    fn module(name: &str, items: &[u32]) -> Value {
+        json!({ "name": name, "visibility": "public", "docs": null, "inner": { "module": { "items": items } } })
+    }
+
+    fn reexport(name: &str, id: u32) -> Value {
+        json!({ "name": null, "visibility": "public", "docs": null, "inner": { "use": { "source": "", "name": name, "id": id, "is_glob": false } } })
+    }
+
+    fn inherent_impl(items: &[u32]) -> Value {
+        json!({ "name": null, "visibility": "default", "docs": null, "inner": { "impl": {
+            "generics": { "params": [], "where_predicates": [] },
+            "trait": null, "items": items, "is_negative": false, "is_synthetic": false, "blanket_impl": null,
+        } } })
+    }
+
+    /// The JSON of a small standard library, where std re-exports `core::mem`, `core::option` and
+    /// `alloc::vec` and defines `std::env::args` itself:
+    ///
+    /// ```ignore
+    /// // core
+    /// pub mod mem { pub fn swap() {} }
+    /// pub mod option { pub enum Option {} impl Option { pub fn map() {} } }
+    /// pub mod iter { pub trait Iterator { fn map() {} } }
+    /// // alloc
+    /// pub mod vec { pub struct Vec; impl Vec { pub fn push() {} } }
+    /// ```
+    fn sample_dir(test: &str) -> PathBuf {
+        let core = json!({
+            "root": 0,
+            "format_version": 57,
+            "index": {
+                "0": module("core", &[1, 2, 6]),
+                "1": module("mem", &[3]),
+                "3": function("swap", "Swaps two values"),
+                "2": module("option", &[4]),
+                "4": { "name": "Option", "visibility": "public", "docs": "Optional values", "inner": { "enum": {
+                    "generics": { "params": [], "where_predicates": [] },
+                    "variants": [], "has_stripped_variants": false, "impls": [5],
+                } } },
+                "5": inherent_impl(&[10]),
+                "10": function("map", "Maps an `Option`"),
+                "6": module("iter", &[7]),
+                "7": { "name": "Iterator", "visibility": "public", "docs": "An iterator", "inner": { "trait": {
+                    "generics": { "params": [], "where_predicates": [] },
+                    "items": [8], "bounds": [], "implementations": [], "is_auto": false, "is_unsafe": false, "is_dyn_compatible": true,
+                } } },
+                "8": function("map", "Maps the items of an iterator"),
+            },
+            "paths": {
+                "1": { "crate_id": 0, "path": ["core", "mem"], "kind": "module" },
+                "2": { "crate_id": 0, "path": ["core", "option"], "kind": "module" },
+                "4": { "crate_id": 0, "path": ["core", "option", "Option"], "kind": "enum" },
+                "7": { "crate_id": 0, "path": ["core", "iter", "Iterator"], "kind": "trait" },
+            },
+        });
+        let alloc = json!({
+            "root": 0,
+            "format_version": 57,
+            "index": {
+                "0": module("alloc", &[1]),
+                "1": module("vec", &[2]),
+                "2": { "name": "Vec", "visibility": "public", "docs": "A growable array", "inner": { "struct": {
+                    "kind": "unit", "generics": { "params": [], "where_predicates": [] }, "impls": [3],
+                } } },
+                "3": inherent_impl(&[4]),
+                "4": function("push", "Appends an element"),
+            },
+            "paths": {
+                "1": { "crate_id": 0, "path": ["alloc", "vec"], "kind": "module" },
+                "2": { "crate_id": 0, "path": ["alloc", "vec", "Vec"], "kind": "struct" },
+            },
+        });
+        let std = json!({
+            "root": 0,
+            "format_version": 57,
+            "crate_version": "1.90.0-nightly\t(4b4e9c4d9\t2025-07-11)",
+            "index": {
+                "0": module("std", &[1, 2, 3, 4]),
+                "1": reexport("mem", 100),
+                "2": reexport("option", 101),
+                "3": reexport("vec", 102),
+                "4": module("env", &[5]),
+                "5": function("args", "Returns the arguments of the process"),
+            },
+            "paths": {
+                "100": { "crate_id": 1, "path": ["core", "mem"], "kind": "module" },
+                "101": { "crate_id": 1, "path": ["core", "option"], "kind": "module" },
+                "102": { "crate_id": 2, "path": ["alloc", "vec"], "kind": "module" },
+            },
+        });
+
+        let dir = std::env::temp_dir().join(format!("rust-mcp-server-std-doc-{test}-test"));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (krate, json) in [("core", core), ("alloc", alloc), ("std", std)] {
+            std::fs::write(dir.join(format!("{krate}.json")), json.to_string()).unwrap();
+        }
+        dir
+    }
+
+    async fn resolved_path(dir: &Path, path: &str) -> Option<String> {
+        resolve(dir, path).await.unwrap().map(|(_, path)| path)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_follows_reexports() {
+        let dir = sample_dir("resolve");
+        assert_eq!(
+            resolved_path(&dir, "std::option::Option::map")
+                .await
+                .as_deref(),
+            Some("core::option::Option::map")
+        );
+        assert_eq!(
+            resolved_path(&dir, "vec::Vec::push").await.as_deref(),
+            Some("alloc::vec::Vec::push")
+        );
+        assert_eq!(
+            resolved_path(&dir, "core::mem::swap").await.as_deref(),
+            Some("core::mem::swap")
+        );
+        assert_eq!(
+            resolved_path(&dir, "std::env::args").await.as_deref(),
+            Some("std::env::args")
+        );
+        assert_eq!(resolved_path(&dir, "Vec::push").await, None);
+        assert_eq!(resolved_path(&dir, "std::env::vars").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_search_prefers_std_paths() {
+        let dir = sample_dir("search");
+        let paths = |hits: Vec<(String, String)>| -> Vec<String> {
+            hits.into_iter().map(|(path, _)| path).collect()
+        };
+        assert_eq!(
+            paths(search(&dir, "map").await.unwrap()),
+            ["std::option::Option::map", "core::iter::Iterator::map"]
+        );
+        assert_eq!(
+            paths(search(&dir, "Option::map").await.unwrap()),
+            ["std::option::Option::map"]
+        );
+        assert_eq!(
+            paths(search(&dir, "iter::Iterator::map").await.unwrap()),
+            ["core::iter::Iterator::map"]
+        );
+        let hits = search(&dir, "psh").await.unwrap();
+        assert_eq!(
+            hits,
+            [(
+                "std::vec::Vec::push".to_owned(),
+                "(function): Appends an element".to_owned()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lookup_falls_back_to_search() {
+        let dir = sample_dir("lookup");
+        let text = lookup(&dir, "Vec::push", None).await.unwrap();
+        assert!(text.starts_with("function alloc::vec::Vec::push"), "{text}");
+        assert!(text.contains("Appends an element"), "{text}");
+        let error = lookup(&dir, "map", None).await.unwrap_err();
+        assert_eq!(
+            error.message,
+            "`map` not found in std, core or alloc, similar items: std::option::Option::map, core::iter::Iterator::map"
+        );
+        let error = lookup(&dir, "Option::filter", None).await.unwrap_err();
+        assert_eq!(
+            error.message,
+            "`Option::filter` not found in std, core or alloc"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::{fmt::Write as _, path::PathBuf};
+
+use rmcp::{
+    ErrorData,
+    model::{AnnotateAble, RawContent},
+};
+
+use crate::{Response, Tool, ToolContext, ToolHints, tool::json_schema_impl, workspace::Workspace};
+
+#[derive(Debug, ::serde::Deserialize, ::schemars::JsonSchema)]
+pub struct WorkspaceListRequest {}
+
+/// Workspaces the tools can run in
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct WorkspaceList {
+    /// Workspace used by the calls that do not pass `workspace_root`
+    default: Option<PathBuf>,
+    workspaces: Vec<Workspace>,
+}
+
+impl WorkspaceList {
+    fn render(&self) -> String {
+        if self.workspaces.is_empty() {
+            return match &self.default {
+                Some(default) => format!("No workspace detected, default: {}", default.display()),
+                None => "No workspace detected, the tools run in the server's current directory"
+                    .to_owned(),
+            };
+        }
+
+        let mut text = String::new();
+        for workspace in &self.workspaces {
+            let marker = if self.default.as_ref() == Some(&workspace.path) {
+                " (default)"
+            } else {
+                ""
+            };
+            _ = writeln!(
+                text,
+                "{}: {}{marker}",
+                workspace.name,
+                workspace.path.display()
+            );
+        }
+        text
+    }
+}
+
+pub struct WorkspaceListRmcpTool;
+
+impl Tool for WorkspaceListRmcpTool {
+    const NAME: &'static str = "workspace-list";
+    const TITLE: &'static str = "List workspaces";
+    const DESCRIPTION: &'static str = "Lists the Cargo workspaces detected among the MCP client roots, and which one is the default. Pass the name or path of a workspace as `workspace_root` to run any other tool in it.";
+    type RequestArgs = WorkspaceListRequest;
+    const HINTS: ToolHints = ToolHints::read_only();
+
+    fn output_schema() -> Option<serde_json::Map<String, serde_json::Value>> {
+        Some(json_schema_impl::<WorkspaceList>())
+    }
+
+    async fn call_rmcp_tool(
+        &self,
+        _request: Self::RequestArgs,
+        ctx: ToolContext,
+    ) -> Result<Response, ErrorData> {
+        let list = WorkspaceList {
+            default: ctx.workspaces().default_root(),
+            workspaces: ctx.workspaces().list(),
+        };
+
+        let mut response = Response::without_output();
+        response.add_content(RawContent::text(list.render()).no_annotation());
+        response.set_structured_content(&list);
+        Ok(response)
+    }
+}
@@ -0,0 +1,148 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use ohno::IntoAppError;
+use rmcp::{
+    ServiceExt,
+    service::QuitReason,
+    transport::{
+        StreamableHttpServerConfig, StreamableHttpService,
+        streamable_http_server::session::local::LocalSessionManager,
+    },
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::rmcp_server::Server;
+
+/// Path under which the streamable HTTP endpoint is mounted
+pub const HTTP_ENDPOINT: &str = "/mcp";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TransportKind {
+    /// Serve a single client over stdin/stdout
+    Stdio,
+    /// Serve any number of clients over MCP streamable HTTP
+    Http,
+}
+
+/// Serves a single client over stdio until the client disconnects
+pub async fn serve_stdio(server: Server) -> Result<(), ohno::AppError> {
+    let service = server
+        .serve(rmcp::transport::stdio())
+        .await
+        .into_app_err("Failed to start server")?;
+
+    eprintln!("Rust MCP Server started on stdio");
+
+    match service.waiting().await {
+        Ok(QuitReason::Closed) => tracing::info!("Server closed normally"),
+        Ok(QuitReason::Cancelled) => tracing::info!("Server was cancelled"),
+        Ok(QuitReason::JoinError(error)) => {
+            tracing::error!("Server join error: {error}");
+            return Err(error.into());
+        }
+        Ok(reason) => {
+            tracing::info!("Server exited with reason: {reason:?}");
+        }
+        Err(error) => {
+            tracing::error!("Server encountered an error: {error}");
+            return Err(error.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Serves clients over MCP streamable HTTP until Ctrl+C is received.
+///
+/// Every client gets its own session, all of them sharing the same tool set.
+pub async fn serve_http(
+    server: Server,
+    bind: SocketAddr,
+    allowed_hosts: &[String],
+) -> Result<(), ohno::AppError> {
+    let listener = tokio::net::TcpListener::bind(bind)
+        .await
+        .into_app_err_with(|| format!("Failed to bind to {bind}"))?;
+    let local_addr = listener
+        .local_addr()
+        .into_app_err("Failed to get local address")?;
+
+    let ct = CancellationToken::new();
+    let shutdown = ct.clone();
+    tokio::spawn(async move {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {e}");
+        }
+        tracing::info!("Shutdown requested");
+        shutdown.cancel();
+    });
+
+    eprintln!("Rust MCP Server started on http://{local_addr}{HTTP_ENDPOINT}");
+    serve_http_on(listener, server, allowed_hosts, ct).await?;
+    tracing::info!("Server closed normally");
+    Ok(())
+}
+
+async fn serve_http_on(
+    listener: tokio::net::TcpListener,
+    server: Server,
+    allowed_hosts: &[String],
+    ct: CancellationToken,
+) -> Result<(), ohno::AppError> {
+    let mut config =
+        StreamableHttpServerConfig::default().with_cancellation_token(ct.child_token());
+    config.allowed_hosts.extend(allowed_hosts.iter().cloned());
+
+    let service = StreamableHttpService::new(
+        move || Ok(server.for_session()),
+        Arc::new(LocalSessionManager::default()),
+        config,
+    );
+    let router = axum::Router::new().nest_service(HTTP_ENDPOINT, service);
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+        .await
+        .into_app_err("HTTP server failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::{ServiceExt, transport::StreamableHttpClientTransport};
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_http_transport_serves_multiple_clients() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let ct = CancellationToken::new();
+        let server = Server::new(
+            &crate::config::Config::default(),
+            false,
+            None,
+            crate::prompts::PromptLibrary::default(),
+        );
+        let handle = tokio::spawn(serve_http_on(listener, server, &[], ct.clone()));
+
+        let uri = format!("http://{addr}{HTTP_ENDPOINT}");
+        let first =
+            ().serve(StreamableHttpClientTransport::from_uri(uri.as_str()))
+                .await
+                .expect("first client should connect");
+        let second =
+            ().serve(StreamableHttpClientTransport::from_uri(uri.as_str()))
+                .await
+                .expect("second client should connect");
+
+        for client in [&first, &second] {
+            let tools = client.list_all_tools().await.expect("should list tools");
+            assert!(tools.iter().any(|t| t.name == "cargo-check"));
+        }
+
+        first.cancel().await.unwrap();
+        second.cancel().await.unwrap();
+        ct.cancel();
+        handle.await.unwrap().unwrap();
+    }
+}
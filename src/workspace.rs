@@ -1,38 +1,151 @@
-use rmcp::{model::Root, service::NotificationContext};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
 
-use crate::globals;
+use rmcp::{ErrorData, Peer, RoleServer, model::Root};
 
-/// Applies the workspace root to a command if it is set
-pub fn apply_workspace_root(cmd: &mut std::process::Command) {
-    if let Some(root) = globals::get_workspace_root() {
+use crate::ToolContext;
+
+/// Tool parameter selecting the workspace a call runs in, accepted by every tool
+pub(crate) const WORKSPACE_ROOT_PARAM: &str = "workspace_root";
+
+/// A Cargo workspace the tools can run in
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+pub(crate) struct Workspace {
+    /// Name of the client root, or of the directory when the root has none
+    pub(crate) name: String,
+    /// Directory containing the root `Cargo.toml`
+    pub(crate) path: PathBuf,
+}
+
+impl Workspace {
+    pub(crate) fn new(name: Option<String>, path: PathBuf) -> Self {
+        let name = name
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| path.display().to_string());
+        Self { name, path }
+    }
+}
+
+/// Applies the workspace root of the call to a command if it is set
+pub fn apply_workspace_root(cmd: &mut std::process::Command, ctx: &ToolContext) {
+    if let Some(root) = ctx.workspace_root() {
         cmd.current_dir(root);
     }
 }
 
-/// If CWD contains `Cargo.toml` then function does nothing. Otherwise it tries to detect workspace root from client roots.
+/// Cargo workspaces of one client session, shared by the calls of that session
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SessionWorkspaces(Arc<RwLock<WorkspaceState>>);
+
+#[derive(Debug, Default)]
+struct WorkspaceState {
+    /// Used by the calls that do not pass `workspace_root`
+    default: Option<PathBuf>,
+    workspaces: Vec<Workspace>,
+}
+
+impl SessionWorkspaces {
+    /// Starts with the workspace given on the command line, if any
+    pub(crate) fn new(fixed: Option<PathBuf>) -> Self {
+        let state = WorkspaceState {
+            workspaces: fixed
+                .iter()
+                .map(|path| Workspace::new(None, path.clone()))
+                .collect(),
+            default: fixed,
+        };
+        Self(Arc::new(RwLock::new(state)))
+    }
+
+    /// Default workspace root, used by tool calls that do not pass `workspace_root`
+    pub(crate) fn default_root(&self) -> Option<PathBuf> {
+        self.0
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .default
+            .clone()
+    }
+
+    pub(crate) fn list(&self) -> Vec<Workspace> {
+        self.0
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .workspaces
+            .clone()
+    }
+
+    /// Replaces the detected workspaces, the default root is kept while it is still detected
+    fn update(&self, workspaces: Vec<Workspace>) {
+        let mut state = self.0.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(root) = default_workspace_root(state.default.as_deref(), &workspaces)
+            && state.default.as_ref() != Some(&root)
+        {
+            tracing::info!("Setting default workspace root: {}", root.display());
+            state.default = Some(root);
+        }
+        state.workspaces = workspaces;
+    }
+
+    /// Resolves the `workspace_root` parameter: the name or path of a detected workspace,
+    /// or another directory with a `Cargo.toml`, relative paths start at the default workspace root
+    pub(crate) fn resolve(&self, value: &str) -> Result<PathBuf, ErrorData> {
+        let workspaces = self.list();
+        if let Some(workspace) = workspaces.iter().find(|workspace| workspace.name == value) {
+            return Ok(workspace.path.clone());
+        }
+
+        let path = Path::new(value);
+        let path = if path.is_relative() {
+            self.default_root()
+                .or_else(|| std::env::current_dir().ok())
+                .unwrap_or_default()
+                .join(path)
+        } else {
+            path.to_path_buf()
+        };
+        if path.join("Cargo.toml").is_file() {
+            return Ok(path);
+        }
+
+        let names: Vec<_> = workspaces
+            .iter()
+            .map(|workspace| workspace.name.as_str())
+            .collect();
+        Err(ErrorData::invalid_params(
+            format!(
+                "`{value}` is neither a detected workspace nor a directory with a Cargo.toml, detected workspaces: {}",
+                if names.is_empty() {
+                    "none".to_owned()
+                } else {
+                    names.join(", ")
+                }
+            ),
+            None,
+        ))
+    }
+}
+
+/// Detects the Cargo workspaces containing the current directory or found in the client roots.
+/// The default workspace root is kept while it is still detected, otherwise the first workspace becomes the default.
 #[expect(
     deprecated,
     reason = "we will use it while it lasts, but eventually the whole feature will go away"
 )]
-pub fn detect_rust_workspace(context: NotificationContext<rmcp::RoleServer>) {
+pub(crate) fn detect_rust_workspaces(peer: Peer<RoleServer>, session: SessionWorkspaces) {
     let cwd = std::env::current_dir().ok();
     tracing::info!("Checking current working directory for Cargo project: {cwd:?}");
-    if let Some(cwd_path) = cwd {
-        if cwd_path.join("Cargo.toml").exists() {
-            tracing::info!(
-                "Cargo.toml found in CWD ({}), using it as workspace root (no auto-detection needed)",
-                cwd_path.display()
-            );
-            return;
-        }
-        tracing::info!(
-            "No Cargo.toml in CWD ({}), will attempt workspace detection via client roots",
-            cwd_path.display()
-        );
-    }
+    let cwd_workspace = cwd
+        .as_deref()
+        .and_then(locate_workspace)
+        .map(|path| Workspace::new(None, path));
 
-    let supports_roots = context
-        .peer
+    let supports_roots = peer
         .peer_info()
         .and_then(|info| info.capabilities.roots.clone())
         .is_some();
@@ -40,6 +153,7 @@ pub fn detect_rust_workspace(context: NotificationContext<rmcp::RoleServer>) {
     tracing::info!("Checking client roots capability: supports_roots={supports_roots}");
     if !supports_roots {
         tracing::warn!("Client does not support roots capability; cannot auto-detect workspace");
+        session.update(cwd_workspace.into_iter().collect());
         return;
     }
 
@@ -48,10 +162,11 @@ pub fn detect_rust_workspace(context: NotificationContext<rmcp::RoleServer>) {
     // processing this notification before responding to roots/list.
     let fut = async move {
         tracing::info!("Requesting workspace roots from client");
-        let result = match context.peer.list_roots().await {
+        let result = match peer.list_roots().await {
             Ok(result) => result,
             Err(e) => {
                 tracing::warn!("Failed to fetch client roots: {e}");
+                session.update(cwd_workspace.into_iter().collect());
                 return;
             }
         };
@@ -61,26 +176,27 @@ pub fn detect_rust_workspace(context: NotificationContext<rmcp::RoleServer>) {
             result.roots.len(),
             result.roots
         );
-        for Root { uri, .. } in result.roots {
+        let mut workspaces: Vec<_> = cwd_workspace.into_iter().collect();
+        for Root { uri, name, .. } in result.roots {
             let Some(path) = file_uri_to_path(&uri) else {
                 tracing::warn!("Could not convert root URI to a filesystem path: {uri}");
                 continue;
             };
             tracing::info!(
-                "Checking root for Cargo project: {uri} -> {}",
+                "Checking root for Cargo projects: {uri} -> {}",
                 path.display()
             );
-            if path.join("Cargo.toml").exists() {
-                tracing::info!(
-                    "Found Cargo project in root, setting as workspace: {}",
-                    path.display()
-                );
-                globals::try_set_workspace_root(path);
-                return;
+            for workspace in discover_workspaces(&path, name) {
+                if !workspaces.iter().any(|known| known.path == workspace.path) {
+                    tracing::info!("Found Cargo workspace: {}", workspace.path.display());
+                    workspaces.push(workspace);
+                }
             }
-            tracing::debug!("No Cargo.toml found in root: {}", path.display());
         }
-        tracing::warn!("No Cargo project found in any client root; workspace unset");
+        if workspaces.is_empty() {
+            tracing::warn!("No Cargo project found in any client root");
+        }
+        session.update(workspaces);
     };
 
     tokio::spawn(async move {
@@ -92,6 +208,153 @@ pub fn detect_rust_workspace(context: NotificationContext<rmcp::RoleServer>) {
     });
 }
 
+/// How many directory levels below a client root are scanned for Cargo projects
+const MAX_SCAN_DEPTH: usize = 3;
+
+/// Directories that never contain the workspaces of a client root
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+/// Finds the workspaces of a client root: the workspace the root belongs to and those
+/// found up to [`MAX_SCAN_DEPTH`] levels below it, the outermost workspace comes first
+fn discover_workspaces(root: &Path, root_name: Option<String>) -> Vec<Workspace> {
+    let mut paths: Vec<PathBuf> = locate_workspace(root).into_iter().collect();
+    scan_workspaces(root, MAX_SCAN_DEPTH, &mut paths);
+    paths.sort_by(|a, b| {
+        a.components()
+            .count()
+            .cmp(&b.components().count())
+            .then_with(|| a.cmp(b))
+    });
+    paths.dedup();
+
+    let root_name = root_name.filter(|name| !name.is_empty()).or_else(|| {
+        root.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    });
+    paths
+        .into_iter()
+        .map(|path| {
+            // Workspaces below the root are named after it so that they stay unique across roots
+            let name = match (&root_name, path.strip_prefix(root)) {
+                (Some(root_name), Ok(relative)) if relative.as_os_str().is_empty() => {
+                    Some(root_name.clone())
+                }
+                (Some(root_name), Ok(relative)) => Some(format!(
+                    "{root_name}/{}",
+                    relative.to_string_lossy().replace('\\', "/")
+                )),
+                _ => None,
+            };
+            Workspace::new(name, path)
+        })
+        .collect()
+}
+
+fn scan_workspaces(dir: &Path, depth: usize, workspaces: &mut Vec<PathBuf>) {
+    if depth == 0 {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        // `file_type` does not follow symlinks, which keeps the scan from looping
+        if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            continue;
+        }
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()) {
+            continue;
+        }
+        let path = entry.path();
+        if path.join("Cargo.toml").is_file() {
+            workspaces.push(workspace_root(&path));
+        }
+        scan_workspaces(&path, depth - 1, workspaces);
+    }
+}
+
+/// Finds the workspace root of the package containing `dir`, like `cargo locate-project --workspace`
+fn locate_workspace(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(workspace_root)
+}
+
+/// Finds the workspace root of the package in `dir`: the package itself when it declares a `[workspace]`,
+/// the `package.workspace` it points to, or the closest parent workspace that does not exclude it
+fn workspace_root(dir: &Path) -> PathBuf {
+    let Some(manifest) = read_manifest(dir) else {
+        return dir.to_path_buf();
+    };
+    if manifest.contains_key("workspace") {
+        return dir.to_path_buf();
+    }
+    if let Some(workspace) = manifest
+        .get("package")
+        .and_then(|package| package.get("workspace"))
+        .and_then(|workspace| workspace.as_str())
+    {
+        return normalize(&dir.join(workspace));
+    }
+
+    for parent in dir.ancestors().skip(1) {
+        let Some(workspace) = read_manifest(parent)
+            .and_then(|manifest| manifest.get("workspace").cloned())
+            .and_then(|workspace| workspace.as_table().cloned())
+        else {
+            continue;
+        };
+        let lists = |key: &str| {
+            workspace
+                .get(key)
+                .and_then(|paths| paths.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|path| path.as_str())
+                .any(|path| dir.starts_with(parent.join(path)))
+        };
+        if lists("members") || !lists("exclude") {
+            return parent.to_path_buf();
+        }
+    }
+    dir.to_path_buf()
+}
+
+fn read_manifest(dir: &Path) -> Option<toml::Table> {
+    let content = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    content
+        .parse()
+        .inspect_err(|e| tracing::debug!("Failed to parse {}/Cargo.toml: {e}", dir.display()))
+        .ok()
+}
+
+/// Removes the `.` and `..` components of a path without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Keeps the current default while it is detected, the first workspace replaces it otherwise
+fn default_workspace_root(current: Option<&Path>, workspaces: &[Workspace]) -> Option<PathBuf> {
+    match current {
+        Some(current) if workspaces.iter().any(|workspace| workspace.path == current) => {
+            Some(current.to_path_buf())
+        }
+        _ => workspaces.first().map(|workspace| workspace.path.clone()),
+    }
+}
+
 /// Convert a `file://` URI to a local filesystem path.
 ///
 /// Handles:
@@ -99,7 +362,7 @@ pub fn detect_rust_workspace(context: NotificationContext<rmcp::RoleServer>) {
 /// - `file:///C:/path/to/dir` (Windows, leading slash before drive letter stripped)
 /// - `file:///d%3A/path` (Windows, percent-encoded colon in drive letter)
 /// - `file://localhost/path` (optional localhost authority)
-fn file_uri_to_path(uri: &str) -> Option<std::path::PathBuf> {
+pub(crate) fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
     let path = uri.strip_prefix("file://")?;
     let path = path.strip_prefix("localhost").unwrap_or(path);
     let decoded = percent_encoding::percent_decode_str(path)
@@ -117,7 +380,31 @@ fn file_uri_to_path(uri: &str) -> Option<std::path::PathBuf> {
             decoded
         }
     };
-    Some(std::path::PathBuf::from(decoded))
+    Some(PathBuf::from(decoded))
+}
+
+/// Characters that can't appear unescaped in the path of a `file://` URI
+const URI_PATH: &percent_encoding::AsciiSet = &percent_encoding::CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Convert an absolute filesystem path to a `file://` URI, the inverse of [`file_uri_to_path`].
+pub(crate) fn path_to_file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    // Windows paths start with the drive letter, e.g. `C:/`
+    let separator = if path.starts_with('/') { "" } else { "/" };
+    format!(
+        "file://{separator}{}",
+        percent_encoding::utf8_percent_encode(&path, URI_PATH)
+    )
 }
 
 #[cfg(test)]
@@ -165,6 +452,105 @@ mod tests {
         assert_eq!(path, std::path::PathBuf::from("/home/user/project"));
     }
 
+    #[cfg(not(windows))]
+    #[test]
+    fn test_path_to_file_uri_roundtrip() {
+        let path = std::path::Path::new("/path with spaces/#1/Cargo.toml");
+        let uri = path_to_file_uri(path);
+        assert_eq!(uri, "file:///path%20with%20spaces/%231/Cargo.toml");
+        assert_eq!(file_uri_to_path(&uri).unwrap(), path);
+    }
+
+    #[test]
+    fn test_default_workspace_root() {
+        let workspaces = [
+            Workspace::new(Some("app".to_owned()), PathBuf::from("/work/app")),
+            Workspace::new(None, PathBuf::from("/work/lib")),
+        ];
+        assert_eq!(workspaces[1].name, "lib");
+
+        assert_eq!(
+            default_workspace_root(Some(Path::new("/work/lib")), &workspaces),
+            Some(PathBuf::from("/work/lib"))
+        );
+        assert_eq!(
+            default_workspace_root(Some(Path::new("/work/old")), &workspaces),
+            Some(PathBuf::from("/work/app"))
+        );
+        assert_eq!(
+            default_workspace_root(None, &workspaces[1..]),
+            Some(PathBuf::from("/work/lib"))
+        );
+        assert_eq!(
+            default_workspace_root(Some(Path::new("/work/old")), &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_session_workspaces_are_independent() {
+        let first = SessionWorkspaces::new(None);
+        let second = SessionWorkspaces::new(None);
+        first.update(vec![Workspace::new(None, PathBuf::from("/work/app"))]);
+        second.update(vec![Workspace::new(None, PathBuf::from("/work/lib"))]);
+
+        assert_eq!(first.default_root(), Some(PathBuf::from("/work/app")));
+        assert_eq!(second.default_root(), Some(PathBuf::from("/work/lib")));
+        assert_eq!(first.resolve("app").unwrap(), PathBuf::from("/work/app"));
+        assert!(second.resolve("app").is_err());
+
+        let fixed = SessionWorkspaces::new(Some(PathBuf::from("/work/fixed")));
+        assert_eq!(fixed.default_root(), Some(PathBuf::from("/work/fixed")));
+        assert_eq!(fixed.list()[0].name, "fixed");
+    }
+
+    #[test]
+    fn test_discover_workspaces() {
+        let root = std::env::temp_dir().join("rust-mcp-server-workspace-test");
+        let manifests = [
+            (
+                "rust",
+                "[workspace]\nmembers = [\"engine\"]\nexclude = [\"standalone\"]\n",
+            ),
+            ("rust/engine", "[package]\nname = \"engine\"\n"),
+            ("rust/standalone", "[package]\nname = \"standalone\"\n"),
+            ("services/api", "[package]\nname = \"api\"\n"),
+            ("web/node_modules/wasm", "[package]\nname = \"wasm\"\n"),
+        ];
+        for (dir, manifest) in manifests {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join("Cargo.toml"), manifest).unwrap();
+        }
+
+        let workspaces: Vec<_> = discover_workspaces(&root, Some("mono".to_owned()))
+            .into_iter()
+            .map(|workspace| (workspace.name, workspace.path))
+            .collect();
+        assert_eq!(
+            workspaces,
+            [
+                ("mono/rust".to_owned(), root.join("rust")),
+                (
+                    "mono/rust/standalone".to_owned(),
+                    root.join("rust/standalone")
+                ),
+                ("mono/services/api".to_owned(), root.join("services/api")),
+            ]
+        );
+
+        // A member crate opened as a root belongs to the workspace above it
+        assert_eq!(
+            discover_workspaces(&root.join("rust/engine"), None),
+            [Workspace::new(None, root.join("rust"))]
+        );
+        assert_eq!(
+            locate_workspace(&root.join("rust/engine/src")),
+            Some(root.join("rust"))
+        );
+        assert_eq!(locate_workspace(&root.join("web")), None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
     #[cfg(not(windows))]
     #[test]
     fn test_localhost_authority() {